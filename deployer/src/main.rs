@@ -0,0 +1,48 @@
+use std::process::ExitCode;
+
+use deployer_lib::ProgramDefinition;
+
+/// Every program of the workspace, a new program must be added here to be deployed
+const PROGRAMS: [&dyn ProgramDefinition; 9] = [
+    &bootstrap_ntrn_dntrn::TestBootstrapNtrnDntrn,
+    &ntrn_migrate_usdc::TestMigrateNtrnUsdcLiquidity,
+    &neutron_stake_drop::TestNeutronStakeDrop,
+    &prod_dics_bootstrap_ntrn_dntrn::BootstrapNtrnDntrnLiquidity,
+    &prod_dics_gradual_ls::GradualLs,
+    &prod_dics_ntrn_allocation::NtrnAllocation,
+    &prod_dics_ntrn_instant_ls::NtrnInstantLs,
+    &migrate_usdc_ntrn_prod::MigrateUsdcNtrnLiquidity,
+    &program_template::ExampleProgram,
+];
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    deployer_lib::deployer_main(&PROGRAMS).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::PROGRAMS;
+
+    #[test]
+    fn all_programs_are_registered() {
+        let programs_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../programs");
+        let registered = PROGRAMS
+            .iter()
+            .map(|program| program.path().canonicalize().unwrap())
+            .collect::<Vec<_>>();
+
+        for entry in std::fs::read_dir(programs_path).unwrap() {
+            let path = entry.unwrap().path().canonicalize().unwrap();
+            if path.join("Cargo.toml").exists() {
+                assert!(
+                    registered.contains(&path),
+                    "{} is not registered in the deployer",
+                    path.display()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,554 @@
+use std::{collections::BTreeMap, error::Error, fmt::Display, path::Path};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    denoms::DenomRegistry,
+    program_params::{read_program_params, CHECKS_TABLE},
+};
+
+/// Decimals are compared as integers with 18 decimal places, like cosmwasm `Decimal`
+const DECIMAL_PLACES: usize = 18;
+const DECIMAL_ONE: u128 = 1_000_000_000_000_000_000;
+
+/// A `DecimalRange` wider than this around its midpoint is most likely a typo,
+/// envs that use wider ranges on purpose raise it in their `[checks]` table
+const DEFAULT_MAX_RANGE_DEVIATION_PERCENT: u128 = 50;
+
+/// Expectations on amounts declared in the `[checks]` table of the params file
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct AmountChecks {
+    /// Amounts the fixed amount splits of a splitter must add up to
+    #[serde(default)]
+    pub split_inputs: Vec<SplitInput>,
+    /// Highest max amount a forwarder may forward in one batch
+    #[serde(default)]
+    pub max_forward_amounts: Vec<DenomAmount>,
+    /// Ranges wider than this around their midpoint are reported as warnings, defaults to 50
+    pub max_range_deviation_percent: Option<u128>,
+}
+
+/// Denoms are values and not keys, as params keys are paths that can't hold every denom
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct DenomAmount {
+    pub denom: String,
+    pub amount: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct SplitInput {
+    /// Name of the splitter library
+    pub library: String,
+    /// Only needed if the splitter splits more than one denom
+    pub denom: Option<String>,
+    pub amount: String,
+}
+
+/// An amount or ratio in the program config that doesn't look right
+#[derive(Debug, Clone, Serialize)]
+pub struct AmountIssue {
+    /// Library name or authorization label
+    pub source: String,
+    pub message: String,
+    /// Unusual but possibly intended, it doesn't stop the deployment
+    pub warning: bool,
+}
+
+impl Display for AmountIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.warning {
+            true => write!(f, "{}: warning, {}", self.source, self.message),
+            false => write!(f, "{}: {}", self.source, self.message),
+        }
+    }
+}
+
+/// Read the amount checks of the environment, a missing `[checks]` table only disables
+/// the checks that need a declared value
+pub(crate) fn get_amount_checks(
+    program_path: &Path,
+    env: &str,
+) -> Result<AmountChecks, Box<dyn Error>> {
+    let params = read_program_params(program_path, env)?;
+    let Some(checks) = params.get(CHECKS_TABLE) else {
+        return Ok(AmountChecks::default());
+    };
+
+    // Amounts can be written in whole tokens of the env denoms like amount params, Ex: "150M NTRN"
+    let checks =
+        DenomRegistry::load(program_path, env)?.resolve_amounts(checks.clone(), &["amount"])?;
+
+    Ok(serde_json::from_value(checks)
+        .map_err(|e| format!("Invalid [checks] in {}.toml params: {}", env, e))?)
+}
+
+/// Check splitter ratios sum to one per denom, fixed amounts sum to the declared input,
+/// forwarder max amounts are non zero and below the declared cap,
+/// and decimal ranges (Ex: expected pool ratio) are ordered and plausible
+pub(crate) fn check_amounts(
+    program_config: &Value,
+    checks: &AmountChecks,
+) -> Result<Vec<AmountIssue>, Box<dyn Error>> {
+    let mut issues = vec![];
+    let mut issue = |source: &str, message: String| {
+        issues.push(AmountIssue {
+            source: source.to_string(),
+            message,
+            warning: false,
+        })
+    };
+    let max_deviation = checks
+        .max_range_deviation_percent
+        .unwrap_or(DEFAULT_MAX_RANGE_DEVIATION_PERCENT);
+    let mut range_issues = vec![];
+    let mut range_issue = |source: &str, message: String, warning: bool| {
+        range_issues.push(AmountIssue {
+            source: source.to_string(),
+            message,
+            warning,
+        })
+    };
+
+    let libraries = program_config["libraries"]
+        .as_object()
+        .map(|l| l.values().collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    for input in &checks.split_inputs {
+        let exists = libraries.iter().any(|library| {
+            library["name"] == input.library
+                && library["config"].get("ValenceSplitterLibrary").is_some()
+        });
+        if !exists {
+            issue(
+                &input.library,
+                "declared in split_inputs but there is no splitter with this name".to_string(),
+            );
+        }
+    }
+
+    for library in &libraries {
+        let name = library["name"].as_str().unwrap_or_default();
+        let config = &library["config"];
+
+        if let Some(splitter) = config.get("ValenceSplitterLibrary") {
+            // Denom to the sum of ratios and the sum of fixed amounts
+            let mut sums: BTreeMap<String, (Option<Sum>, Option<Sum>)> = BTreeMap::new();
+            for split in splitter["splits"].as_array().cloned().unwrap_or_default() {
+                let denom = get_denom(&split["denom"]);
+                let sum = sums.entry(denom.clone()).or_default();
+
+                if let Some(ratio) = split["amount"].get("fixed_ratio") {
+                    let ratio = parse_decimal(ratio)
+                        .ok_or_else(|| format!("{} has an invalid ratio for {}", name, denom))?;
+                    if ratio == 0 {
+                        issue(name, format!("split of {} has a ratio of 0", denom));
+                    }
+                    sum.0 = Some(add(sum.0, ratio));
+                } else if let Some(amount) = split["amount"].get("fixed_amount") {
+                    let amount = parse_amount(amount)
+                        .ok_or_else(|| format!("{} has an invalid amount for {}", name, denom))?;
+                    if amount == 0 {
+                        issue(name, format!("split of {} has an amount of 0", denom));
+                    }
+                    sum.1 = Some(add(sum.1, amount));
+                }
+            }
+
+            for (denom, (ratios, amounts)) in &sums {
+                match ratios {
+                    Some(None) => issue(name, format!("ratios of {} overflow", denom)),
+                    Some(Some(ratios)) if *ratios != DECIMAL_ONE => issue(
+                        name,
+                        format!(
+                            "ratios of {} sum to {}, not 1",
+                            denom,
+                            format_decimal(*ratios)
+                        ),
+                    ),
+                    _ => (),
+                }
+
+                let declared = checks.split_inputs.iter().find(|input| {
+                    input.library == name
+                        && input.denom.as_ref().is_none_or(|d| d == denom)
+                        && (input.denom.is_some() || sums.len() == 1)
+                });
+                match (declared, amounts) {
+                    (_, Some(None)) => issue(name, format!("fixed amounts of {} overflow", denom)),
+                    (Some(declared), Some(Some(amounts))) => {
+                        let expected = declared.amount.parse::<u128>().map_err(|_| {
+                            format!("Invalid split_inputs amount for {}", declared.library)
+                        })?;
+                        if *amounts != expected {
+                            issue(
+                                name,
+                                format!(
+                                    "fixed amounts of {} sum to {}, expected {}",
+                                    denom, amounts, expected
+                                ),
+                            );
+                        }
+                    }
+                    (Some(_), None) => issue(
+                        name,
+                        format!("has an expected input of {} but no fixed amounts", denom),
+                    ),
+                    _ => (),
+                }
+            }
+        }
+
+        if let Some(forwarder) = config.get("ValenceForwarderLibrary") {
+            for forwarding_config in forwarder["forwarding_configs"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default()
+            {
+                let denom = get_denom(&forwarding_config["denom"]);
+                let max_amount = parse_amount(&forwarding_config["max_amount"])
+                    .ok_or_else(|| format!("{} has an invalid max amount for {}", name, denom))?;
+
+                if max_amount == 0 {
+                    issue(name, format!("max amount of {} is 0", denom));
+                }
+
+                if let Some(cap) = checks.max_forward_amounts.iter().find(|c| c.denom == denom) {
+                    let cap = cap
+                        .amount
+                        .parse::<u128>()
+                        .map_err(|_| format!("Invalid max_forward_amounts for {}", denom))?;
+                    if max_amount > cap {
+                        issue(
+                            name,
+                            format!(
+                                "max amount of {} is {}, above the cap of {}",
+                                denom, max_amount, cap
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+
+        check_ranges(config, max_deviation, &mut |message, warning| {
+            range_issue(name, message, warning)
+        });
+    }
+
+    // Ranges in authorizations are only in the base64 encoded values of the restrictions
+    for authorization in program_config["authorizations"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default()
+    {
+        let label = authorization["label"].as_str().unwrap_or_default();
+        let mut restrictions = vec![];
+        collect_values(
+            &authorization["subroutine"],
+            "must_be_value",
+            &mut restrictions,
+        );
+
+        for restriction in restrictions {
+            let Some(encoded) = restriction.get(1).and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let Some(value) = STANDARD
+                .decode(encoded)
+                .ok()
+                .and_then(|bytes| serde_json::from_slice::<Value>(&bytes).ok())
+            else {
+                continue;
+            };
+
+            check_ranges(&value, max_deviation, &mut |message, warning| {
+                range_issue(label, message, warning)
+            });
+        }
+    }
+
+    issues.extend(range_issues);
+    Ok(issues)
+}
+
+/// Running sum of amounts or ratios, `None` once it overflowed
+type Sum = Option<u128>;
+
+fn add(sum: Option<Sum>, value: u128) -> Sum {
+    sum.unwrap_or(Some(0))?.checked_add(value)
+}
+
+/// Check every `{"min": .., "max": ..}` decimal range in the value, an invalid or inverted range
+/// is a failure and a range that only looks too wide or starts at 0 a warning
+fn check_ranges(value: &Value, max_deviation: u128, issue: &mut impl FnMut(String, bool)) {
+    match value {
+        Value::Object(map) => {
+            if let (2, Some(min), Some(max)) = (map.len(), map.get("min"), map.get("max")) {
+                let (Some(min), Some(max)) = (parse_decimal(min), parse_decimal(max)) else {
+                    if min.is_string() || max.is_string() {
+                        issue(
+                            format!("range {}..{} is not a decimal range", min, max),
+                            false,
+                        );
+                    }
+                    return;
+                };
+
+                let range = format!("{}..{}", format_decimal(min), format_decimal(max));
+                if min >= max {
+                    issue(format!("range {} has min not below max", range), false);
+                } else if min == 0 {
+                    issue(format!("range {} starts at 0", range), true);
+                } else {
+                    let width = (max - min).checked_mul(100);
+                    let allowed = max
+                        .checked_add(min)
+                        .and_then(|sum| sum.checked_mul(max_deviation));
+
+                    match (width, allowed) {
+                        (Some(width), Some(allowed)) if width <= allowed => (),
+                        (Some(_), Some(_)) => issue(
+                            format!(
+                                "range {} is more than ±{}% around its midpoint",
+                                range, max_deviation
+                            ),
+                            true,
+                        ),
+                        _ => issue(format!("range {} is too large to check", range), true),
+                    }
+                }
+                return;
+            }
+
+            map.values()
+                .for_each(|v| check_ranges(v, max_deviation, issue));
+        }
+        Value::Array(values) => values
+            .iter()
+            .for_each(|v| check_ranges(v, max_deviation, issue)),
+        _ => (),
+    }
+}
+
+fn collect_values<'a>(value: &'a Value, key: &str, found: &mut Vec<&'a Value>) {
+    match value {
+        Value::Object(map) => map.iter().for_each(|(k, v)| {
+            if k == key {
+                found.push(v);
+            }
+            collect_values(v, key, found);
+        }),
+        Value::Array(values) => values.iter().for_each(|v| collect_values(v, key, found)),
+        _ => (),
+    }
+}
+
+/// Denom of `{"native": denom}` or `{"cw20": addr}`
+pub(crate) fn get_denom(denom: &Value) -> String {
+    denom
+        .as_object()
+        .and_then(|d| d.values().next())
+        .and_then(|d| d.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn parse_amount(amount: &Value) -> Option<u128> {
+    amount.as_str()?.parse().ok()
+}
+
+/// Parse a decimal string into an integer with 18 decimal places
+fn parse_decimal(decimal: &Value) -> Option<u128> {
+    let (int, fraction) = match decimal.as_str()?.split_once('.') {
+        Some((int, fraction)) => (int, fraction),
+        None => (decimal.as_str()?, ""),
+    };
+    if fraction.len() > DECIMAL_PLACES || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let int = int.parse::<u128>().ok()?;
+    let fraction = format!("{:0<width$}", fraction, width = DECIMAL_PLACES)
+        .parse::<u128>()
+        .ok()?;
+
+    int.checked_mul(DECIMAL_ONE)?.checked_add(fraction)
+}
+
+fn format_decimal(atomics: u128) -> String {
+    let fraction = format!("{:018}", atomics % DECIMAL_ONE);
+    let fraction = fraction.trim_end_matches('0');
+
+    if fraction.is_empty() {
+        (atomics / DECIMAL_ONE).to_string()
+    } else {
+        format!("{}.{}", atomics / DECIMAL_ONE, fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde_json::{json, Value};
+
+    use super::{check_amounts, AmountChecks, SplitInput};
+
+    fn splitter(splits: Value) -> Value {
+        json!({
+            "libraries": {
+                "1": {
+                    "name": "splitter",
+                    "config": { "ValenceSplitterLibrary": { "splits": splits } },
+                }
+            }
+        })
+    }
+
+    fn split(amount: Value) -> Value {
+        json!({ "denom": { "native": "untrn" }, "amount": amount })
+    }
+
+    fn messages(program_config: &Value, checks: &AmountChecks) -> Vec<String> {
+        check_amounts(program_config, checks)
+            .unwrap()
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn splits_adding_up_pass() {
+        let program_config = splitter(json!([
+            split(json!({ "fixed_amount": "100" })),
+            split(json!({ "fixed_amount": "50" })),
+        ]));
+        let checks = AmountChecks {
+            split_inputs: vec![SplitInput {
+                library: "splitter".to_string(),
+                denom: None,
+                amount: "150".to_string(),
+            }],
+            ..Default::default()
+        };
+
+        assert!(messages(&program_config, &checks).is_empty());
+    }
+
+    #[test]
+    fn ratios_not_adding_up_fail() {
+        let program_config = splitter(json!([
+            split(json!({ "fixed_ratio": "0.5" })),
+            split(json!({ "fixed_ratio": "0.4" })),
+        ]));
+
+        assert_eq!(
+            messages(&program_config, &AmountChecks::default()),
+            vec!["splitter: ratios of untrn sum to 0.9, not 1"]
+        );
+    }
+
+    #[test]
+    fn overflowing_sums_fail() {
+        let max = u128::MAX.to_string();
+        let program_config = splitter(json!([
+            split(json!({ "fixed_amount": max })),
+            split(json!({ "fixed_amount": max })),
+        ]));
+        assert_eq!(
+            messages(&program_config, &AmountChecks::default()),
+            vec!["splitter: fixed amounts of untrn overflow"]
+        );
+
+        let program_config = splitter(json!([
+            split(json!({ "fixed_ratio": "300000000000000000000" })),
+            split(json!({ "fixed_ratio": "300000000000000000000" })),
+        ]));
+        assert_eq!(
+            messages(&program_config, &AmountChecks::default()),
+            vec!["splitter: ratios of untrn overflow"]
+        );
+    }
+
+    #[test]
+    fn ranges_are_checked() {
+        let authorization = |min: &str, max: &str| {
+            let range = json!({ "min": min, "max": max }).to_string();
+            json!({
+                "authorizations": [{
+                    "label": "provide",
+                    "subroutine": { "must_be_value": [["path"], STANDARD.encode(range)] },
+                }]
+            })
+        };
+        let check =
+            |min: &str, max: &str| messages(&authorization(min, max), &AmountChecks::default());
+
+        assert!(check("0.98", "1.02").is_empty());
+        assert_eq!(
+            check("2", "1"),
+            vec!["provide: range 2..1 has min not below max"]
+        );
+        assert_eq!(
+            check("one", "2"),
+            vec![r#"provide: range "one".."2" is not a decimal range"#]
+        );
+        assert_eq!(
+            check("0.1", "10"),
+            vec!["provide: warning, range 0.1..10 is more than ±50% around its midpoint"]
+        );
+        assert_eq!(
+            check("1", "300000000000000000000"),
+            vec!["provide: warning, range 1..300000000000000000000 is too large to check"]
+        );
+    }
+
+    #[test]
+    fn only_invalid_ranges_fail() {
+        let program_config = json!({
+            "libraries": {
+                "1": {
+                    "name": "lper",
+                    "config": { "ValenceAstroportLper": {
+                        "pool_ratio": { "min": "0.5", "max": "2" },
+                        "max_spread": { "min": "0", "max": "0.1" },
+                    }},
+                },
+                "2": {
+                    "name": "withdrawer",
+                    "config": { "ValenceAstroportWithdrawer": { "ratio": { "min": "2", "max": "2" } } },
+                },
+            }
+        });
+
+        let issues = check_amounts(&program_config, &AmountChecks::default()).unwrap();
+        let failures = issues
+            .iter()
+            .filter(|issue| !issue.warning)
+            .map(|issue| issue.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(issues.len(), 3);
+        assert_eq!(
+            failures,
+            vec!["withdrawer: range 2..2 has min not below max"]
+        );
+
+        // The threshold of the env allows the wide range
+        let checks = AmountChecks {
+            max_range_deviation_percent: Some(60),
+            ..Default::default()
+        };
+        assert_eq!(
+            messages(&program_config, &checks),
+            vec![
+                "lper: warning, range 0..0.1 starts at 0",
+                "withdrawer: range 2..2 has min not below max",
+            ]
+        );
+    }
+}
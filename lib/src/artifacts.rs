@@ -0,0 +1,408 @@
+use std::{error::Error, path::Path};
+
+use k256::ecdsa::{
+    signature::{Signer as _, Verifier as _},
+    Signature, SigningKey, VerifyingKey,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    audit::{read_audit_log, AuditEntry, AUDIT_LOG_PATH},
+    helpers::{get_public_key, is_key_of_address},
+    manifest::Manifest,
+    verify::Mismatch,
+    workspace_config::WorkspaceConfig,
+};
+
+/// Checksums of every file of an output directory, in the format of `sha256sum`
+pub(crate) const CHECKSUMS_FILE: &str = "SHA256SUMS";
+/// Signature of the checksums by the deployer key
+pub(crate) const SIGNATURE_FILE: &str = "SHA256SUMS.sig.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ArtifactsSignature {
+    /// hex of the compressed secp256k1 public key of the deployer
+    public_key: String,
+    /// hex of the ecdsa signature of the checksums file
+    signature: String,
+}
+
+/// Write the checksums of every file of the output directory and sign them if a key is given,
+/// the manifest is one of the files so signing the checksums signs it too
+pub(crate) fn write_checksums(
+    output_path: &Path,
+    signing_key: Option<&SigningKey>,
+) -> Result<(), Box<dyn Error>> {
+    let mut checksums = String::new();
+    for file in get_artifacts(output_path)? {
+        let content = std::fs::read(output_path.join(&file))?;
+        checksums.push_str(&format!(
+            "{}  {}\n",
+            hex::encode(Sha256::digest(&content)),
+            file
+        ));
+    }
+
+    std::fs::write(output_path.join(CHECKSUMS_FILE), &checksums)?;
+
+    if let Some(key) = signing_key {
+        let signature: Signature = key.sign(checksums.as_bytes());
+        let signature = ArtifactsSignature {
+            public_key: get_public_key(key),
+            signature: hex::encode(signature.to_bytes()),
+        };
+        std::fs::write(
+            output_path.join(SIGNATURE_FILE),
+            serde_json::to_string_pretty(&signature)?,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Check the files of an output directory match their checksums, the signature was made by its deployer
+/// in the audit log of the workspace or a deployer of `deployer.toml`, and the manifest agrees with
+/// the instantiated program config
+pub(crate) fn verify_artifacts(
+    output_path: &Path,
+    workspace: &Path,
+) -> Result<Vec<Mismatch>, Box<dyn Error>> {
+    let mut mismatches = vec![];
+    let mut mismatch = |check: &str, expected: Value, actual: Value| {
+        mismatches.push(Mismatch {
+            contract: "artifacts".to_string(),
+            check: check.to_string(),
+            expected,
+            actual,
+        })
+    };
+
+    let manifest = Manifest::read(output_path).ok();
+
+    // The files of the output directory can be rewritten with the manifest, so the signer
+    // is expected to be the one of the audit log or of the workspace config
+    let entry = get_audit_entry(workspace, output_path)?;
+    let deployers = WorkspaceConfig::load(workspace)?.audit.deployers;
+    let signed = manifest.as_ref().is_some_and(|m| m.signed_output)
+        || entry.as_ref().and_then(|e| e.output_signed) == Some(true);
+
+    let checksums = match std::fs::read_to_string(output_path.join(CHECKSUMS_FILE)) {
+        Ok(checksums) => checksums,
+        // Deployments made before checksums were written have nothing to check
+        Err(_) if !signed && !manifest.as_ref().is_some_and(|m| m.checksums) => {
+            log::warn!("{} has no checksums", output_path.display());
+            return Ok(mismatches);
+        }
+        Err(_) => {
+            mismatch(CHECKSUMS_FILE, json!("present"), Value::Null);
+            return Ok(mismatches);
+        }
+    };
+
+    let mut listed = vec![];
+    for line in checksums.lines().filter(|line| !line.trim().is_empty()) {
+        let Some((expected, file)) = line.split_once("  ") else {
+            mismatch(CHECKSUMS_FILE, json!("<sha256>  <file>"), json!(line));
+            continue;
+        };
+        listed.push(file.to_string());
+
+        let actual = std::fs::read(output_path.join(file))
+            .map(|content| json!(hex::encode(Sha256::digest(&content))))
+            .unwrap_or(Value::Null);
+        if actual != json!(expected) {
+            mismatch(file, json!(expected), actual);
+        }
+    }
+
+    for file in get_artifacts(output_path)? {
+        if !listed.contains(&file) {
+            mismatch(&file, Value::Null, json!("not in the checksums"));
+        }
+    }
+
+    match std::fs::read_to_string(output_path.join(SIGNATURE_FILE)) {
+        Ok(content) => match serde_json::from_str::<ArtifactsSignature>(&content) {
+            Ok(signature) => {
+                let expected = match &entry {
+                    Some(entry) => json!(entry.public_key),
+                    None => json!(deployers),
+                };
+                let is_deployer_key = match &entry {
+                    Some(entry) => entry.public_key.as_ref() == Some(&signature.public_key),
+                    None => deployers
+                        .iter()
+                        .any(|deployer| is_key_of_address(&signature.public_key, deployer)),
+                };
+
+                if !is_valid_signature(&signature, &checksums) {
+                    mismatch(SIGNATURE_FILE, json!("valid signature"), json!("invalid"));
+                } else if !is_deployer_key {
+                    mismatch(SIGNATURE_FILE, expected, json!(signature.public_key));
+                }
+            }
+            Err(e) => mismatch(
+                SIGNATURE_FILE,
+                json!("valid signature"),
+                json!(e.to_string()),
+            ),
+        },
+        Err(_) if signed => mismatch(SIGNATURE_FILE, json!("present"), Value::Null),
+        Err(_) => (),
+    }
+
+    // The registry entry is looked up by the program id of the instantiated program config
+    if let Some(manifest) = &manifest {
+        let program_id =
+            std::fs::read_to_string(output_path.join("instantiated-program-config.json"))
+                .ok()
+                .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+                .and_then(|config| config.get("id").and_then(|id| id.as_u64()));
+
+        if program_id.is_some() && program_id != manifest.program_id {
+            mismatch("program_id", json!(manifest.program_id), json!(program_id));
+        }
+    }
+
+    Ok(mismatches)
+}
+
+/// Every file of the output directory except the checksums and their signature, sorted by name
+fn get_artifacts(output_path: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut files = std::fs::read_dir(output_path)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file())
+        .filter_map(|path| Some(path.file_name()?.to_str()?.to_string()))
+        .filter(|name| name != CHECKSUMS_FILE && name != SIGNATURE_FILE)
+        .collect::<Vec<_>>();
+    files.sort();
+
+    Ok(files)
+}
+
+fn is_valid_signature(signature: &ArtifactsSignature, checksums: &str) -> bool {
+    let (Ok(public_key), Ok(signature)) = (
+        hex::decode(&signature.public_key),
+        hex::decode(&signature.signature),
+    ) else {
+        return false;
+    };
+    let (Ok(key), Ok(signature)) = (
+        VerifyingKey::from_sec1_bytes(&public_key),
+        Signature::from_slice(&signature),
+    ) else {
+        return false;
+    };
+
+    key.verify(checksums.as_bytes(), &signature).is_ok()
+}
+
+/// Last entry of the audit log of the workspace for the output directory
+fn get_audit_entry(
+    workspace: &Path,
+    output_path: &Path,
+) -> Result<Option<AuditEntry>, Box<dyn Error>> {
+    let output_path = output_path.canonicalize()?;
+
+    Ok(read_audit_log(&workspace.join(AUDIT_LOG_PATH))?
+        .into_iter()
+        .rev()
+        .find(|entry| {
+            entry.output_dir.as_ref().is_some_and(|dir| {
+                workspace
+                    .join(dir)
+                    .canonicalize()
+                    .is_ok_and(|dir| dir == output_path)
+            })
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use serde_json::{json, Value};
+    use tempfile::{tempdir, TempDir};
+
+    use super::{verify_artifacts, write_checksums, CHECKSUMS_FILE, SIGNATURE_FILE};
+    use crate::{
+        audit::{append_audit_entry, AuditAction, AuditRecord},
+        helpers::{derive_address, derive_wallet_key, get_public_key},
+    };
+
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
+    const OTHER_MNEMONIC: &str = "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote";
+    const OUTPUT_DIR: &str = "programs/gradual-ls/output/mainnet-2025-03-31_18:19:39-success";
+
+    /// Workspace with an output directory with a raw and an instantiated program config
+    fn workspace() -> TempDir {
+        let workspace = tempdir().unwrap();
+        std::fs::create_dir_all(workspace.path().join(OUTPUT_DIR)).unwrap();
+        std::fs::write(
+            output_file(&workspace, "raw-program-config.json"),
+            r#"{"id":0}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            output_file(&workspace, "instantiated-program-config.json"),
+            r#"{"id":2}"#,
+        )
+        .unwrap();
+        workspace
+    }
+
+    fn output_file(workspace: &TempDir, file: &str) -> PathBuf {
+        workspace.path().join(OUTPUT_DIR).join(file)
+    }
+
+    /// Write the checksums of the output signed with the key of the mnemonic
+    fn sign(workspace: &TempDir, mnemonic: &str) {
+        let key = derive_wallet_key(mnemonic).unwrap();
+        write_checksums(&workspace.path().join(OUTPUT_DIR), Some(&key)).unwrap();
+    }
+
+    /// Record the deployment of the signed output in the audit log
+    fn audit(workspace: &TempDir, mnemonic: &str) {
+        let record = AuditRecord {
+            timestamp: "2025-03-31T18:19:39Z".parse().unwrap(),
+            action: AuditAction::Deploy,
+            program: "gradual-ls".to_string(),
+            env: "mainnet".to_string(),
+            deployer_address: Some(derive_address(mnemonic, "neutron", 118).unwrap()),
+            config_hash: "hash".to_string(),
+            program_id: Some(2),
+            status: "success".to_string(),
+            output_dir: Some(workspace.path().join(OUTPUT_DIR)),
+            output_signed: true,
+        };
+        append_audit_entry(workspace.path(), record, mnemonic).unwrap();
+    }
+
+    /// Checks of the mismatches
+    fn verify(workspace: &TempDir) -> Vec<(String, Value)> {
+        verify_artifacts(&workspace.path().join(OUTPUT_DIR), workspace.path())
+            .unwrap()
+            .into_iter()
+            .map(|m| (m.check, m.actual))
+            .collect()
+    }
+
+    fn public_key(mnemonic: &str) -> Value {
+        json!(get_public_key(&derive_wallet_key(mnemonic).unwrap()))
+    }
+
+    #[test]
+    fn untouched_output_verifies() {
+        let workspace = workspace();
+        sign(&workspace, MNEMONIC);
+        audit(&workspace, MNEMONIC);
+
+        let checksums = std::fs::read_to_string(output_file(&workspace, CHECKSUMS_FILE)).unwrap();
+        assert_eq!(checksums.lines().count(), 2);
+        assert!(checksums
+            .lines()
+            .all(|l| l.ends_with("-program-config.json")));
+        assert!(output_file(&workspace, SIGNATURE_FILE).exists());
+        assert_eq!(verify(&workspace), vec![]);
+    }
+
+    #[test]
+    fn modified_and_added_files_are_detected() {
+        let workspace = workspace();
+        write_checksums(&workspace.path().join(OUTPUT_DIR), None).unwrap();
+        std::fs::write(
+            output_file(&workspace, "instantiated-program-config.json"),
+            r#"{"id":3}"#,
+        )
+        .unwrap();
+        std::fs::write(output_file(&workspace, "notes.txt"), "deployed").unwrap();
+
+        let mismatches = verify(&workspace);
+
+        assert_eq!(mismatches.len(), 2);
+        assert_eq!(mismatches[0].0, "instantiated-program-config.json");
+        assert_eq!(
+            mismatches[1],
+            ("notes.txt".to_string(), json!("not in the checksums"))
+        );
+    }
+
+    #[test]
+    fn rewritten_checksums_break_the_signature() {
+        let workspace = workspace();
+        sign(&workspace, MNEMONIC);
+        audit(&workspace, MNEMONIC);
+        let signature = std::fs::read(output_file(&workspace, SIGNATURE_FILE)).unwrap();
+
+        // Checksums made again after a change, without the key of the deployer
+        std::fs::write(
+            output_file(&workspace, "instantiated-program-config.json"),
+            r#"{"id":3}"#,
+        )
+        .unwrap();
+        write_checksums(&workspace.path().join(OUTPUT_DIR), None).unwrap();
+        std::fs::write(output_file(&workspace, SIGNATURE_FILE), signature).unwrap();
+
+        assert_eq!(
+            verify(&workspace),
+            vec![(SIGNATURE_FILE.to_string(), json!("invalid"))]
+        );
+    }
+
+    #[test]
+    fn output_signed_by_another_key_than_the_audited_one_is_detected() {
+        let workspace = workspace();
+        audit(&workspace, MNEMONIC);
+        sign(&workspace, OTHER_MNEMONIC);
+
+        assert_eq!(
+            verify(&workspace),
+            vec![(SIGNATURE_FILE.to_string(), public_key(OTHER_MNEMONIC))]
+        );
+    }
+
+    #[test]
+    fn removed_signature_of_a_signed_output_is_detected() {
+        let workspace = workspace();
+        sign(&workspace, MNEMONIC);
+        audit(&workspace, MNEMONIC);
+        std::fs::remove_file(output_file(&workspace, SIGNATURE_FILE)).unwrap();
+
+        assert_eq!(
+            verify(&workspace),
+            vec![(SIGNATURE_FILE.to_string(), Value::Null)]
+        );
+    }
+
+    #[test]
+    fn listed_deployers_are_expected_without_an_audit_entry() {
+        let workspace = workspace();
+        std::fs::write(
+            workspace.path().join("deployer.toml"),
+            format!(
+                "[audit]\ndeployers = [\"{}\"]\n",
+                derive_address(MNEMONIC, "neutron", 118).unwrap()
+            ),
+        )
+        .unwrap();
+
+        sign(&workspace, MNEMONIC);
+        assert_eq!(verify(&workspace), vec![]);
+
+        sign(&workspace, OTHER_MNEMONIC);
+        assert_eq!(
+            verify(&workspace),
+            vec![(SIGNATURE_FILE.to_string(), public_key(OTHER_MNEMONIC))]
+        );
+    }
+
+    #[test]
+    fn missing_checksums_of_old_deployments_are_ignored() {
+        let workspace = workspace();
+
+        assert_eq!(verify(&workspace), vec![]);
+    }
+}
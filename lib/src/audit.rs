@@ -0,0 +1,659 @@
+use std::{
+    collections::BTreeSet,
+    error::Error,
+    fmt::Display,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Timelike, Utc};
+use clap::Subcommand;
+use k256::ecdsa::{
+    signature::{Signer as _, Verifier as _},
+    Signature, VerifyingKey,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    deployments::{get_deployments, get_package_name, OUTPUT_TIMESTAMP_FORMAT},
+    error::DeployerError,
+    helpers::{canonical_hash, derive_wallet_key, get_public_key, is_key_of_address},
+    workspace_config::WorkspaceConfig,
+};
+
+/// Audit log of the workspace, it is committed with the repo unlike the output directories
+pub(crate) const AUDIT_LOG_PATH: &str = "audit/deployments.jsonl";
+
+/// Who may write to the audit log, from the `[audit]` table of `deployer.toml`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuditConfig {
+    /// Addresses of the deployers, entries signed by the key of any other address are reported
+    #[serde(default)]
+    pub deployers: Vec<String>,
+}
+
+/// A line of the audit log, every entry is chained to the previous one by its hash
+/// and signed by the deployer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuditEntry {
+    /// Position in the log, starting at 0
+    pub seq: u64,
+    /// When the deployment started, same as the timestamp of its output directory
+    pub timestamp: DateTime<Utc>,
+    /// What was done, see `AuditAction`
+    pub action: String,
+    /// Program directory name
+    pub program: String,
+    pub env: String,
+    pub deployer_address: Option<String>,
+    /// Hash of the raw program config, same as in the manifest
+    pub config_hash: String,
+    pub program_id: Option<u64>,
+    pub status: String,
+    /// Output directory path relative to the workspace, if the deployment was written to one
+    pub output_dir: Option<String>,
+    /// The checksums of the output were signed with the key of the entry, unset in entries
+    /// written before it was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_signed: Option<bool>,
+    /// Hash of the previous entry, empty for the first one
+    pub prev_hash: String,
+    /// hex of the compressed secp256k1 public key of the deployer
+    pub public_key: Option<String>,
+    /// sha256 of the canonical json of the entry without `hash` and `signature`
+    pub hash: String,
+    /// hex of the ecdsa signature of `hash`
+    pub signature: Option<String>,
+}
+
+/// What a deployment run did
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum AuditAction {
+    /// The program was instantiated
+    Deploy,
+    /// Instantiating the program failed, its output has the debug program config
+    Failure,
+}
+
+impl Display for AuditAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuditAction::Deploy => write!(f, "deploy"),
+            AuditAction::Failure => write!(f, "failure"),
+        }
+    }
+}
+
+/// What a deployment adds to the audit log, the chain fields are filled on append
+#[derive(Debug, Clone)]
+pub(crate) struct AuditRecord {
+    pub timestamp: DateTime<Utc>,
+    pub action: AuditAction,
+    pub program: String,
+    pub env: String,
+    pub deployer_address: Option<String>,
+    pub config_hash: String,
+    pub program_id: Option<u64>,
+    pub status: String,
+    pub output_dir: Option<PathBuf>,
+    pub output_signed: bool,
+}
+
+impl AuditEntry {
+    fn compute_hash(&self) -> Result<String, Box<dyn Error>> {
+        let mut value = serde_json::to_value(self)?;
+        if let Some(entry) = value.as_object_mut() {
+            entry.remove("hash");
+            entry.remove("signature");
+        }
+
+        canonical_hash(&value)
+    }
+
+    fn verify_signature(&self) -> Result<(), String> {
+        let (Some(public_key), Some(signature)) = (&self.public_key, &self.signature) else {
+            return Err("is not signed".to_string());
+        };
+
+        let invalid = || "has an invalid signature".to_string();
+        let public_key = hex::decode(public_key).map_err(|_| invalid())?;
+        let signature = hex::decode(signature).map_err(|_| invalid())?;
+        let key = VerifyingKey::from_sec1_bytes(&public_key).map_err(|_| invalid())?;
+        let signature = Signature::from_slice(&signature).map_err(|_| invalid())?;
+
+        key.verify(self.hash.as_bytes(), &signature)
+            .map_err(|_| invalid())
+    }
+
+    /// The key of the signature must be the one of the deployer address of the entry,
+    /// and of one of the deployers when they are listed
+    fn verify_deployer(&self, deployers: &[String]) -> Result<(), String> {
+        let public_key = self.public_key.as_deref().unwrap_or_default();
+        let Some(address) = &self.deployer_address else {
+            return Err("has no deployer address to check its key against".to_string());
+        };
+
+        if !is_key_of_address(public_key, address) {
+            return Err(format!(
+                "is signed by a key that is not the one of its deployer {}",
+                address
+            ));
+        }
+        if !deployers.is_empty()
+            && !deployers
+                .iter()
+                .any(|deployer| is_key_of_address(public_key, deployer))
+        {
+            return Err(format!(
+                "is signed by {}, which is not a deployer of deployer.toml",
+                address
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Append a deployment to the audit log of the workspace, signed with the key of the mnemonic
+pub(crate) fn append_audit_entry(
+    workspace: &Path,
+    record: AuditRecord,
+    mnemonic: &str,
+) -> Result<AuditEntry, DeployerError> {
+    let path = workspace.join(AUDIT_LOG_PATH);
+    let output_error =
+        |e: &dyn Display| DeployerError::Output(format!("{}: {}", path.display(), e));
+
+    let last = read_audit_log(&path)?.pop();
+
+    let mut entry = AuditEntry {
+        seq: last.as_ref().map(|e| e.seq + 1).unwrap_or_default(),
+        timestamp: record.timestamp,
+        action: record.action.to_string(),
+        program: record.program,
+        env: record.env,
+        deployer_address: record.deployer_address,
+        config_hash: record.config_hash,
+        program_id: record.program_id,
+        status: record.status,
+        output_dir: record.output_dir.map(|dir| {
+            dir.strip_prefix(workspace)
+                .unwrap_or(&dir)
+                .display()
+                .to_string()
+        }),
+        output_signed: Some(record.output_signed),
+        prev_hash: last.map(|e| e.hash).unwrap_or_default(),
+        public_key: None,
+        hash: String::new(),
+        signature: None,
+    };
+
+    // Signing is best effort so a deployment that happened is always recorded,
+    // `audit verify` reports unsigned entries
+    let key = derive_wallet_key(mnemonic).ok();
+    entry.public_key = key.as_ref().map(get_public_key);
+    entry.hash = entry.compute_hash().map_err(|e| output_error(&e))?;
+    entry.signature = key.map(|key| {
+        let signature: Signature = key.sign(entry.hash.as_bytes());
+        hex::encode(signature.to_bytes())
+    });
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| output_error(&e))?;
+    }
+    let line = serde_json::to_string(&entry).map_err(|e| output_error(&e))?;
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| writeln!(file, "{}", line))
+        .map_err(|e| output_error(&e))?;
+
+    Ok(entry)
+}
+
+/// Read all entries of an audit log, a missing log is empty
+pub fn read_audit_log(path: &Path) -> Result<Vec<AuditEntry>, DeployerError> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| DeployerError::Path(format!("{}: {}", path.display(), e)))?;
+
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                DeployerError::Validation(format!(
+                    "Invalid audit entry at {}:{}: {}",
+                    path.display(),
+                    i + 1,
+                    e
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Something wrong with the audit log, either tampering or a deployment missing from it
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditIssue {
+    /// Sequence number of the entry, if the issue is about an entry
+    pub seq: Option<u64>,
+    pub message: String,
+}
+
+impl Display for AuditIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.seq {
+            Some(seq) => write!(f, "Entry {}: {}", seq, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Check the hash chain and the signatures of the audit log of the workspace, that entries
+/// were signed by their deployer, and that every deployment output made since the log started has an entry
+pub fn verify_audit_log(workspace: &Path) -> Result<Vec<AuditIssue>, DeployerError> {
+    let config = WorkspaceConfig::load(workspace)?.audit;
+    if config.deployers.is_empty() {
+        log::warn!("No deployers listed in deployer.toml, any key of the deployer address of an entry is accepted");
+    }
+
+    let entries = read_audit_log(&workspace.join(AUDIT_LOG_PATH))?;
+    let mut issues = vec![];
+    let mut issue = |seq: Option<u64>, message: String| issues.push(AuditIssue { seq, message });
+
+    let mut prev: Option<&AuditEntry> = None;
+    for entry in &entries {
+        let expected_seq = prev.map(|p| p.seq + 1).unwrap_or_default();
+        if entry.seq != expected_seq {
+            issue(
+                Some(entry.seq),
+                format!(
+                    "expected sequence number {}, entries are missing",
+                    expected_seq
+                ),
+            );
+        }
+
+        let expected_prev_hash = prev.map(|p| p.hash.as_str()).unwrap_or_default();
+        if entry.prev_hash != expected_prev_hash {
+            issue(
+                Some(entry.seq),
+                "does not chain to the previous entry".to_string(),
+            );
+        }
+
+        match entry.compute_hash() {
+            Ok(hash) if hash == entry.hash => {
+                if let Err(e) = entry
+                    .verify_signature()
+                    .and_then(|_| entry.verify_deployer(&config.deployers))
+                {
+                    issue(Some(entry.seq), e);
+                }
+            }
+            _ => issue(
+                Some(entry.seq),
+                "hash does not match its content, it was modified".to_string(),
+            ),
+        }
+
+        prev = Some(entry);
+    }
+
+    // Deployments before the log existed are not expected to be in it
+    let Some(first) = entries.first() else {
+        return Ok(issues);
+    };
+    let since = first
+        .timestamp
+        .with_nanosecond(0)
+        .unwrap_or(first.timestamp);
+    let logged = entries
+        .iter()
+        .filter_map(|e| e.output_dir.as_deref())
+        .collect::<BTreeSet<_>>();
+
+    let deployments = get_deployments(workspace)
+        .map_err(|e| DeployerError::categorize(e, DeployerError::Path))?;
+    for deployment in deployments {
+        if deployment.timestamp >= since
+            && deployment.status != "process"
+            && !logged.contains(deployment.output_dir.as_str())
+        {
+            issue(
+                None,
+                format!(
+                    "Deployment {} is missing from the log",
+                    deployment.output_dir
+                ),
+            );
+        }
+    }
+
+    Ok(issues)
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum AuditCommand {
+    /// Print the entries of the audit log
+    Show {
+        #[command(flatten)]
+        filter: AuditFilter,
+        /// Print the entries as json
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check the audit log was not tampered with and no deployment is missing from it
+    Verify {
+        /// Print the issues as json
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(clap::Args, Debug, Default, Clone)]
+pub(crate) struct AuditFilter {
+    /// Program directory or package name
+    #[arg(short, long)]
+    program: Option<String>,
+    /// Enviroment of the deployment Ex: mainnet, testnet
+    #[arg(short, long)]
+    env: Option<String>,
+}
+
+impl AuditFilter {
+    fn matches(&self, workspace: &Path, entry: &AuditEntry) -> bool {
+        let program_matches = self.program.as_ref().is_none_or(|program| {
+            &entry.program == program
+                || get_package_name(&workspace.join("programs").join(&entry.program)).as_ref()
+                    == Some(program)
+        });
+        let env_matches = self
+            .env
+            .as_ref()
+            .is_none_or(|env| entry.env.eq_ignore_ascii_case(env));
+
+        program_matches && env_matches
+    }
+}
+
+/// Run an `audit` command of the deployer
+pub(crate) fn run_audit_command(
+    workspace_path: &Path,
+    command: AuditCommand,
+) -> Result<(), DeployerError> {
+    let to_json = |e: serde_json::Error| DeployerError::Output(e.to_string());
+
+    match command {
+        AuditCommand::Show { filter, json } => {
+            let entries = read_audit_log(&workspace_path.join(AUDIT_LOG_PATH))?
+                .into_iter()
+                .filter(|e| filter.matches(workspace_path, e))
+                .collect::<Vec<_>>();
+
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&entries).map_err(to_json)?
+                );
+            } else {
+                print_audit_entries(&entries);
+            }
+        }
+        AuditCommand::Verify { json } => {
+            let issues = verify_audit_log(workspace_path)?;
+
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&issues).map_err(to_json)?
+                );
+            } else if issues.is_empty() {
+                println!("Audit log is intact");
+            } else {
+                issues.iter().for_each(|issue| println!("{issue}"));
+            }
+
+            if !issues.is_empty() {
+                return Err(DeployerError::Validation(format!(
+                    "{} issues found in the audit log",
+                    issues.len()
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_audit_entries(entries: &[AuditEntry]) {
+    println!(
+        "{:<5} {:<20} {:<8} {:<40} {:<15} {:<8} {:<6} DEPLOYER",
+        "SEQ", "TIMESTAMP", "ACTION", "PROGRAM", "ENV", "STATUS", "ID"
+    );
+
+    for e in entries {
+        println!(
+            "{:<5} {:<20} {:<8} {:<40} {:<15} {:<8} {:<6} {}",
+            e.seq,
+            e.timestamp.format(OUTPUT_TIMESTAMP_FORMAT),
+            e.action,
+            e.program,
+            e.env,
+            e.status,
+            e.program_id.map(|id| id.to_string()).unwrap_or_default(),
+            e.deployer_address.as_deref().unwrap_or_default(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use chrono::{DateTime, TimeDelta, Utc};
+    use tempfile::{tempdir, TempDir};
+
+    use super::{
+        append_audit_entry, read_audit_log, verify_audit_log, AuditAction, AuditEntry, AuditRecord,
+        AUDIT_LOG_PATH,
+    };
+    use crate::helpers::derive_address;
+
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
+    const OTHER_MNEMONIC: &str = "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote";
+
+    fn workspace() -> TempDir {
+        let workspace = tempdir().unwrap();
+        std::fs::create_dir_all(workspace.path().join("programs")).unwrap();
+        workspace
+    }
+
+    fn started_at() -> DateTime<Utc> {
+        "2025-03-31T18:19:39Z".parse().unwrap()
+    }
+
+    fn deployer(mnemonic: &str) -> String {
+        derive_address(mnemonic, "neutron", 118).unwrap()
+    }
+
+    /// Append deployments of the deployer of the mnemonic a minute apart, the last one failed
+    fn append(workspace: &Path, count: i64) -> Vec<AuditEntry> {
+        append_signed(workspace, count, &deployer(MNEMONIC), MNEMONIC)
+    }
+
+    /// Append deployments of the deployer signed with the key of the mnemonic
+    fn append_signed(
+        workspace: &Path,
+        count: i64,
+        deployer: &str,
+        mnemonic: &str,
+    ) -> Vec<AuditEntry> {
+        (0..count)
+            .map(|i| {
+                let action = match i == count - 1 {
+                    true => AuditAction::Failure,
+                    false => AuditAction::Deploy,
+                };
+                let record = AuditRecord {
+                    timestamp: started_at() + TimeDelta::minutes(i),
+                    action,
+                    program: "gradual-ls".to_string(),
+                    env: "mainnet".to_string(),
+                    deployer_address: Some(deployer.to_string()),
+                    config_hash: format!("hash{}", i),
+                    program_id: Some(i as u64),
+                    status: "success".to_string(),
+                    output_dir: None,
+                    output_signed: false,
+                };
+
+                append_audit_entry(workspace, record, mnemonic).unwrap()
+            })
+            .collect()
+    }
+
+    fn rewrite(workspace: &Path, entries: &[AuditEntry]) {
+        let lines = entries
+            .iter()
+            .map(|e| serde_json::to_string(e).unwrap() + "\n")
+            .collect::<String>();
+        std::fs::write(workspace.join(AUDIT_LOG_PATH), lines).unwrap();
+    }
+
+    fn verify(workspace: &Path) -> Vec<String> {
+        let issues = verify_audit_log(workspace).unwrap();
+
+        issues.iter().map(|issue| issue.to_string()).collect()
+    }
+
+    #[test]
+    fn appended_entries_are_chained_and_signed() {
+        let tmp = workspace();
+        let workspace = tmp.path();
+        let entries = append(workspace, 2);
+
+        assert_eq!(entries[0].action, "deploy");
+        assert_eq!(entries[1].action, "failure");
+        assert_eq!(entries[0].prev_hash, "");
+        assert_eq!(entries[1].prev_hash, entries[0].hash);
+        assert!(entries.iter().all(|e| e.signature.is_some()));
+        assert_eq!(verify(workspace), Vec::<String>::new());
+    }
+
+    #[test]
+    fn modified_entry_is_detected() {
+        let tmp = workspace();
+        let workspace = tmp.path();
+        let mut entries = append(workspace, 2);
+        entries[0].program_id = Some(42);
+        rewrite(workspace, &entries);
+
+        assert_eq!(
+            verify(workspace),
+            vec!["Entry 0: hash does not match its content, it was modified"]
+        );
+    }
+
+    #[test]
+    fn removed_entry_breaks_the_chain() {
+        let tmp = workspace();
+        let workspace = tmp.path();
+        let mut entries = append(workspace, 3);
+        entries.remove(1);
+        rewrite(workspace, &entries);
+
+        assert_eq!(
+            verify(workspace),
+            vec![
+                "Entry 2: expected sequence number 1, entries are missing",
+                "Entry 2: does not chain to the previous entry",
+            ]
+        );
+    }
+
+    #[test]
+    fn bad_signature_is_detected() {
+        let tmp = workspace();
+        let workspace = tmp.path();
+        let mut entries = append(workspace, 2);
+        entries[1].signature = entries[0].signature.clone();
+        rewrite(workspace, &entries);
+
+        assert_eq!(verify(workspace), vec!["Entry 1: has an invalid signature"]);
+    }
+
+    #[test]
+    fn entry_signed_by_another_key_than_its_deployer_is_detected() {
+        let tmp = workspace();
+        let workspace = tmp.path();
+        append_signed(workspace, 1, &deployer(MNEMONIC), OTHER_MNEMONIC);
+
+        assert_eq!(
+            verify(workspace),
+            vec![format!(
+                "Entry 0: is signed by a key that is not the one of its deployer {}",
+                deployer(MNEMONIC)
+            )]
+        );
+    }
+
+    #[test]
+    fn only_listed_deployers_are_accepted() {
+        let tmp = workspace();
+        let workspace = tmp.path();
+        std::fs::write(
+            workspace.join("deployer.toml"),
+            format!("[audit]\ndeployers = [\"{}\"]\n", deployer(MNEMONIC)),
+        )
+        .unwrap();
+        append(workspace, 1);
+        append_signed(workspace, 1, &deployer(OTHER_MNEMONIC), OTHER_MNEMONIC);
+
+        assert_eq!(
+            verify(workspace),
+            vec![format!(
+                "Entry 1: is signed by {}, which is not a deployer of deployer.toml",
+                deployer(OTHER_MNEMONIC)
+            )]
+        );
+    }
+
+    #[test]
+    fn deployment_missing_from_the_log_is_detected() {
+        let tmp = workspace();
+        let workspace = tmp.path();
+        append(workspace, 1);
+        let before_log =
+            workspace.join("programs/gradual-ls/output/mainnet-2025-03-30_10:00:00-success");
+        let after_log =
+            workspace.join("programs/gradual-ls/output/mainnet-2025-04-01_10:00:00-success");
+        std::fs::create_dir_all(&before_log).unwrap();
+        std::fs::create_dir_all(&after_log).unwrap();
+
+        assert_eq!(
+            verify(workspace),
+            vec!["Deployment programs/gradual-ls/output/mainnet-2025-04-01_10:00:00-success is missing from the log"]
+        );
+    }
+
+    #[test]
+    fn missing_log_is_empty() {
+        let tmp = workspace();
+        let workspace = tmp.path();
+
+        assert!(read_audit_log(&workspace.join(AUDIT_LOG_PATH))
+            .unwrap()
+            .is_empty());
+        assert_eq!(verify(workspace), Vec::<String>::new());
+    }
+}
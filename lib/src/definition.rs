@@ -0,0 +1,378 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use serde_json::Value;
+use valence_program_manager::program_config::ProgramConfig;
+
+use crate::{
+    deployments::get_package_name,
+    equivalence::get_program_envs,
+    error::DeployerError,
+    program_params::{get_resolved_params, ProgramParams},
+};
+
+/// A program and what tooling needs to know about it without building it
+pub trait ProgramDefinition {
+    /// Directory of the program crate, Ex: `env!("CARGO_MANIFEST_DIR").into()`
+    fn path(&self) -> PathBuf;
+
+    /// Defaults to the name of the program directory
+    fn name(&self) -> String {
+        self.path()
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    fn description(&self) -> String {
+        String::new()
+    }
+
+    /// Environments the program can be built for, defaults to the ones with a params file
+    fn envs(&self) -> Vec<String> {
+        get_program_envs(&self.path()).unwrap_or_default()
+    }
+
+    /// Params the builder reads, they are validated before building if not empty
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![]
+    }
+
+    /// Accounts the program expects funds in
+    fn inputs(&self) -> Vec<ProgramIo> {
+        vec![]
+    }
+
+    /// Where the funds end up
+    fn outputs(&self) -> Vec<ProgramIo> {
+        vec![]
+    }
+
+    fn build(&self, params: ProgramParams) -> Result<ProgramConfig, Box<dyn Error>>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamKind {
+    Address,
+    AddressList,
+    Denom,
+    /// Integer amount, or an amount in whole tokens resolved by the denom registry
+    Amount,
+    Decimal,
+    Seconds,
+    Text,
+}
+
+impl Display for ParamKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = serde_json::to_value(self).map_err(|_| std::fmt::Error)?;
+        write!(f, "{}", kind.as_str().unwrap_or_default())
+    }
+}
+
+/// A param of the program params files
+#[derive(Debug, Clone, Serialize)]
+pub struct ParamSpec {
+    pub name: String,
+    pub kind: ParamKind,
+    pub description: String,
+    /// Can be missing or empty
+    pub optional: bool,
+}
+
+impl ParamSpec {
+    pub fn new(name: &str, kind: ParamKind, description: &str) -> Self {
+        ParamSpec {
+            name: name.to_string(),
+            kind,
+            description: description.to_string(),
+            optional: false,
+        }
+    }
+
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+
+    fn validate(&self, value: Option<&Value>) -> Result<(), String> {
+        let value = match value {
+            None | Some(Value::Null) if self.optional => return Ok(()),
+            None | Some(Value::Null) => return Err("is missing".to_string()),
+            Some(Value::String(s)) if s.is_empty() && self.optional => return Ok(()),
+            Some(Value::String(s)) if s.is_empty() && self.kind != ParamKind::Text => {
+                return Err("is empty".to_string())
+            }
+            Some(value) => value,
+        };
+
+        let valid = match (self.kind, value) {
+            (ParamKind::AddressList, Value::Array(addrs)) => addrs
+                .iter()
+                .all(|addr| addr.as_str().is_some_and(is_address)),
+            (ParamKind::AddressList, _) => false,
+            (_, Value::Array(_) | Value::Object(_)) => false,
+            (kind, value) => {
+                let value = match value {
+                    Value::String(s) => s.clone(),
+                    value => value.to_string(),
+                };
+
+                match kind {
+                    ParamKind::Address => is_address(&value),
+                    ParamKind::Denom => !value.contains(char::is_whitespace),
+                    ParamKind::Amount => value.parse::<u128>().is_ok(),
+                    ParamKind::Decimal => value.split_once('.').map_or(
+                        value.parse::<u128>().is_ok(),
+                        |(int, fraction)| {
+                            int.parse::<u128>().is_ok()
+                                && fraction.chars().all(|c| c.is_ascii_digit())
+                        },
+                    ),
+                    ParamKind::Seconds => value.parse::<u64>().is_ok(),
+                    ParamKind::Text | ParamKind::AddressList => true,
+                }
+            }
+        };
+
+        match valid {
+            true => Ok(()),
+            false => Err(format!("{} is not a valid {}", value, self.kind)),
+        }
+    }
+}
+
+/// Bech32 shaped, Ex: `neutron1...`
+pub(crate) fn is_address(addr: &str) -> bool {
+    addr.split_once('1').is_some_and(|(hrp, data)| {
+        !hrp.is_empty()
+            && !data.is_empty()
+            && addr
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+    })
+}
+
+/// Funds going in or out of the program
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgramIo {
+    /// Account name, or the param holding the address for an external account
+    pub account: String,
+    /// Param holding the denom
+    pub denom_param: String,
+    pub description: String,
+}
+
+impl ProgramIo {
+    pub fn new(account: &str, denom_param: &str, description: &str) -> Self {
+        ProgramIo {
+            account: account.to_string(),
+            denom_param: denom_param.to_string(),
+            description: description.to_string(),
+        }
+    }
+}
+
+/// Everything a program says about itself, as printed by `info`
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgramInfo {
+    pub name: String,
+    /// Cargo package name of the program
+    pub package: Option<String>,
+    pub description: String,
+    pub path: PathBuf,
+    pub envs: Vec<String>,
+    pub params: Vec<ParamSpec>,
+    pub inputs: Vec<ProgramIo>,
+    pub outputs: Vec<ProgramIo>,
+}
+
+impl ProgramInfo {
+    pub fn new(program: &dyn ProgramDefinition) -> Self {
+        ProgramInfo {
+            name: program.name(),
+            package: get_package_name(&program.path()),
+            description: program.description(),
+            path: program.path(),
+            envs: program.envs(),
+            params: program.params(),
+            inputs: program.inputs(),
+            outputs: program.outputs(),
+        }
+    }
+}
+
+impl Display for ProgramInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.package {
+            Some(package) => writeln!(f, "{} ({})", self.name, package)?,
+            None => writeln!(f, "{}", self.name)?,
+        }
+        if !self.description.is_empty() {
+            writeln!(f, "{}", self.description)?;
+        }
+        writeln!(f, "envs: {}", self.envs.join(", "))?;
+
+        if !self.params.is_empty() {
+            writeln!(f, "params:")?;
+            for param in &self.params {
+                let optional = if param.optional { ", optional" } else { "" };
+                writeln!(
+                    f,
+                    "    {} ({}{}): {}",
+                    param.name, param.kind, optional, param.description
+                )?;
+            }
+        }
+
+        for (title, ios) in [("inputs", &self.inputs), ("outputs", &self.outputs)] {
+            if !ios.is_empty() {
+                writeln!(f, "{}:", title)?;
+                for io in ios {
+                    writeln!(
+                        f,
+                        "    {} ({}): {}",
+                        io.account, io.denom_param, io.description
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Check the params of an environment against the params the program declares
+pub fn validate_params(
+    program: &dyn ProgramDefinition,
+    params: &ProgramParams,
+) -> Result<(), DeployerError> {
+    let values = params.to_json()?;
+
+    let errors = program
+        .params()
+        .iter()
+        .filter_map(|spec| {
+            spec.validate(values.get(&spec.name))
+                .err()
+                .map(|e| format!("{} {}", spec.name, e))
+        })
+        .collect::<Vec<_>>();
+
+    if !errors.is_empty() {
+        return Err(DeployerError::Params(format!(
+            "Invalid program params: {}",
+            errors.join(", ")
+        )));
+    }
+
+    Ok(())
+}
+
+/// Read the params of the environment, resolving the amounts in whole tokens of the params
+/// the program declares as amounts, and validate them
+pub fn get_valid_params(
+    program: &dyn ProgramDefinition,
+    program_path: &Path,
+    env: &str,
+) -> Result<ProgramParams, DeployerError> {
+    let specs = program.params();
+    let amounts = specs
+        .iter()
+        .filter(|spec| spec.kind == ParamKind::Amount)
+        .map(|spec| spec.name.as_str())
+        .collect::<Vec<_>>();
+
+    let params = get_resolved_params(program_path, env, &amounts)?;
+    validate_params(program, &params)?;
+
+    Ok(params)
+}
+
+/// Read and validate the params of the environment and build the program with them
+pub fn build_program(
+    program: &dyn ProgramDefinition,
+    env: &str,
+) -> Result<ProgramConfig, DeployerError> {
+    let params = get_valid_params(program, &program.path(), env)?;
+
+    // A missing param keeps its kind when the builder returns it
+    program
+        .build(params)
+        .map_err(|e| DeployerError::categorize(e, DeployerError::Build))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error::Error, path::PathBuf};
+
+    use serde_json::json;
+    use tempfile::{tempdir, TempDir};
+    use valence_program_manager::program_config::ProgramConfig;
+
+    use super::{build_program, ParamKind, ParamSpec, ProgramDefinition};
+    use crate::program_params::ProgramParams;
+
+    struct Forwarder(PathBuf);
+
+    impl ProgramDefinition for Forwarder {
+        fn path(&self) -> PathBuf {
+            self.0.clone()
+        }
+
+        fn params(&self) -> Vec<ParamSpec> {
+            vec![ParamSpec::new(
+                "max_amount",
+                ParamKind::Amount,
+                "Max amount to forward",
+            )]
+        }
+
+        fn build(&self, _: ProgramParams) -> Result<ProgramConfig, Box<dyn Error>> {
+            Err("The params are checked before building".into())
+        }
+    }
+
+    /// Program with a mainnet params file in a temporary directory
+    fn forwarder(dir: &TempDir, params: &str) -> Forwarder {
+        let path = dir.path().to_path_buf();
+        std::fs::create_dir_all(path.join("program_params")).unwrap();
+        std::fs::write(path.join("program_params").join("mainnet.toml"), params).unwrap();
+
+        Forwarder(path)
+    }
+
+    #[test]
+    fn unparsable_amount_exits_with_the_params_code() {
+        let dir = tempdir().unwrap();
+        let program = forwarder(&dir, "max_amount = \"lots\"");
+
+        let e = build_program(&program, "mainnet").unwrap_err();
+
+        assert_eq!(e.exit_code(), 4);
+        assert_eq!(
+            serde_json::to_value(e.report()).unwrap(),
+            json!({
+                "kind": "params",
+                "message": "Invalid program params: max_amount \"lots\" is not a valid amount",
+                "exit_code": 4,
+            })
+        );
+    }
+
+    #[test]
+    fn missing_amount_exits_with_the_params_code() {
+        let dir = tempdir().unwrap();
+        let program = forwarder(&dir, "");
+
+        let e = build_program(&program, "mainnet").unwrap_err();
+
+        assert_eq!(e.report().kind, "params");
+        assert_eq!(e.exit_code(), 4);
+    }
+}
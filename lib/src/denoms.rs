@@ -0,0 +1,285 @@
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+const DENOMS_DIR: &str = "denoms";
+
+/// Suffixes of human amounts, Ex: `6.5M NTRN`
+const SUFFIXES: [(&str, u32); 4] = [("K", 3), ("k", 3), ("M", 6), ("B", 9)];
+
+/// Symbol and decimals of a denom, so amounts can be written and shown in whole tokens
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DenomInfo {
+    /// Case insensitive, Ex: `NTRN`
+    pub symbol: String,
+    pub denom: String,
+    pub decimals: u32,
+}
+
+/// Denoms of an environment, read from `denoms/<env>.toml` at the root of the repo
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DenomRegistry {
+    #[serde(default)]
+    pub denoms: Vec<DenomInfo>,
+}
+
+impl DenomRegistry {
+    /// Load the registry of the environment, looking for the `denoms` directory from the program up,
+    /// an environment without a registry has no denoms
+    pub fn load(program_path: &Path, env: &str) -> Result<Self, Box<dyn Error>> {
+        let Some(path) = get_registry_path(program_path, env) else {
+            return Ok(DenomRegistry::default());
+        };
+
+        let content = std::fs::read_to_string(&path)?;
+        toml::from_str(&content)
+            .map_err(|e| format!("Invalid denom registry {}: {}", path.display(), e).into())
+    }
+
+    fn by_symbol(&self, symbol: &str) -> Option<&DenomInfo> {
+        self.denoms
+            .iter()
+            .find(|d| d.symbol.eq_ignore_ascii_case(symbol))
+    }
+
+    fn by_denom(&self, denom: &str) -> Option<&DenomInfo> {
+        self.denoms.iter().find(|d| d.denom == denom)
+    }
+
+    /// Resolve a human amount like `6.5M NTRN` or `6500000 ntrn` to the integer amount of the denom,
+    /// `None` if the value isn't a human amount of a known symbol
+    pub fn parse_amount(&self, value: &str) -> Option<Result<u128, String>> {
+        let (number, symbol) = value.trim().split_once(char::is_whitespace)?;
+        if !number.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        let info = self.by_symbol(symbol.trim())?;
+
+        let (number, exponent) = SUFFIXES
+            .iter()
+            .find_map(|(suffix, exponent)| {
+                number
+                    .strip_suffix(suffix)
+                    .map(|number| (number, *exponent))
+            })
+            .unwrap_or((number, 0));
+
+        Some(to_atomics(number, exponent + info.decimals).ok_or_else(|| {
+            format!(
+                "Invalid amount {:?}, it must be a number of {} with at most {} decimals",
+                value, info.symbol, info.decimals
+            )
+        }))
+    }
+
+    /// Show an integer amount of a known denom in whole tokens, Ex: `6.5M NTRN`
+    pub fn format_amount(&self, amount: u128, denom: &str) -> Option<String> {
+        let info = self.by_denom(denom)?;
+        let (suffix, exponent) = [("B", 9), ("M", 6), ("K", 3)]
+            .into_iter()
+            .find(|(_, exponent)| amount >= 10u128.pow(exponent + info.decimals))
+            .unwrap_or(("", 0));
+
+        Some(format!(
+            "{}{} {}",
+            from_atomics(amount, exponent + info.decimals),
+            suffix,
+            info.symbol
+        ))
+    }
+
+    /// Integer amount with its human amount if the denom is known, Ex: `6500000000000untrn (6.5M NTRN)`
+    pub fn display_amount(&self, amount: u128, denom: &str) -> String {
+        match self.format_amount(amount, denom) {
+            Some(human) => format!("{}{} ({})", amount, denom, human),
+            None => format!("{}{}", amount, denom),
+        }
+    }
+
+    /// Replace the human amounts of the given keys in the params with the integer amount,
+    /// other values are kept as they are written
+    pub(crate) fn resolve_amounts(
+        &self,
+        value: Value,
+        keys: &[&str],
+    ) -> Result<Value, Box<dyn Error>> {
+        Ok(match value {
+            Value::Array(values) => Value::Array(
+                values
+                    .into_iter()
+                    .map(|v| self.resolve_amounts(v, keys))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(k, v)| match v {
+                        Value::String(s) if keys.contains(&k.as_str()) => {
+                            match self.parse_amount(&s) {
+                                Some(amount) => Ok((k, Value::String(amount?.to_string()))),
+                                None => Ok((k, Value::String(s))),
+                            }
+                        }
+                        v => Ok((k, self.resolve_amounts(v, keys)?)),
+                    })
+                    .collect::<Result<_, Box<dyn Error>>>()?,
+            ),
+            v => v,
+        })
+    }
+
+    /// Add the human amount to the integer amounts next to a known denom in a program config,
+    /// Ex: `{"denom": {"native": "untrn"}, "max_amount": "6500000000000 (6.5M NTRN)"}`
+    pub(crate) fn annotate_amounts(&self, value: Value) -> Value {
+        match value {
+            Value::Object(map) => {
+                let denom = map.get("denom").and_then(|denom| match denom {
+                    Value::String(denom) => Some(denom.clone()),
+                    Value::Object(denom) => denom.values().next()?.as_str().map(|d| d.to_string()),
+                    _ => None,
+                });
+
+                Value::Object(
+                    map.into_iter()
+                        .map(|(k, v)| match &denom {
+                            Some(denom) if k != "denom" => (k, self.annotate_with(v, denom)),
+                            _ => (k, self.annotate_amounts(v)),
+                        })
+                        .collect(),
+                )
+            }
+            Value::Array(values) => Value::Array(
+                values
+                    .into_iter()
+                    .map(|v| self.annotate_amounts(v))
+                    .collect(),
+            ),
+            v => v,
+        }
+    }
+
+    fn annotate_with(&self, value: Value, denom: &str) -> Value {
+        match value {
+            Value::String(s) => match s
+                .parse::<u128>()
+                .ok()
+                .and_then(|amount| self.format_amount(amount, denom))
+            {
+                Some(human) => Value::String(format!("{} ({})", s, human)),
+                None => Value::String(s),
+            },
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(k, v)| (k, self.annotate_with(v, denom)))
+                    .collect(),
+            ),
+            v => self.annotate_amounts(v),
+        }
+    }
+}
+
+/// The program path is taken as given, the deployer passes it joined to its workspace
+fn get_registry_path(program_path: &Path, env: &str) -> Option<PathBuf> {
+    program_path
+        .ancestors()
+        .map(|dir| dir.join(DENOMS_DIR).join(format!("{}.toml", env)))
+        .find(|path| path.exists())
+}
+
+/// `number * 10^exponent` as an integer, `None` if it has more decimals than the exponent
+fn to_atomics(number: &str, exponent: u32) -> Option<u128> {
+    let (int, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if int.is_empty()
+        || fraction.len() > exponent as usize
+        || !int
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let digits = format!("{}{:0<width$}", int, fraction, width = exponent as usize);
+    digits.parse().ok()
+}
+
+/// `amount / 10^exponent` without trailing zeros
+fn from_atomics(amount: u128, exponent: u32) -> String {
+    let unit = 10u128.pow(exponent);
+    let fraction = format!("{:0width$}", amount % unit, width = exponent as usize);
+    let fraction = fraction.trim_end_matches('0');
+
+    if fraction.is_empty() {
+        (amount / unit).to_string()
+    } else {
+        format!("{}.{}", amount / unit, fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{DenomInfo, DenomRegistry};
+
+    fn registry() -> DenomRegistry {
+        DenomRegistry {
+            denoms: vec![DenomInfo {
+                symbol: "NTRN".to_string(),
+                denom: "untrn".to_string(),
+                decimals: 6,
+            }],
+        }
+    }
+
+    #[test]
+    fn amounts_are_parsed() {
+        let registry = registry();
+
+        assert_eq!(
+            registry.parse_amount("6.5M NTRN"),
+            Some(Ok(6_500_000_000_000))
+        );
+        assert_eq!(registry.parse_amount("25 ntrn"), Some(Ok(25_000_000)));
+        assert!(matches!(
+            registry.parse_amount("0.0000001 NTRN"),
+            Some(Err(_))
+        ));
+        assert_eq!(registry.parse_amount("25 ATOM"), None);
+        assert_eq!(registry.parse_amount("6500000000000"), None);
+    }
+
+    #[test]
+    fn only_declared_amounts_are_resolved() {
+        let params = json!({
+            "max_amount": "6.5M NTRN",
+            "label": "25M NTRN",
+            "splits": [{ "amount": "1K NTRN" }],
+        });
+
+        let resolved = registry()
+            .resolve_amounts(params, &["max_amount", "amount"])
+            .unwrap();
+
+        assert_eq!(
+            resolved,
+            json!({
+                "max_amount": "6500000000000",
+                "label": "25M NTRN",
+                "splits": [{ "amount": "1000000000" }],
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_declared_amounts_fail() {
+        let params = json!({ "max_amount": "6.5000001 NTRN" });
+
+        assert!(registry().resolve_amounts(params, &["max_amount"]).is_err());
+    }
+}
@@ -0,0 +1,783 @@
+use std::{
+    fmt::Display,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use k256::ecdsa::SigningKey;
+use log::{info, warn};
+use serde::Serialize;
+use serde_json::Value;
+use valence_program_manager::program_config::ProgramConfig;
+
+use crate::{
+    amount_checks::{check_amounts, get_amount_checks, AmountIssue},
+    artifacts::{verify_artifacts, write_checksums},
+    audit::{append_audit_entry, AuditAction, AuditRecord},
+    definition::{get_valid_params, ProgramDefinition},
+    denoms::DenomRegistry,
+    deployments::{get_latest_success_output, parse_output_dir_name, OUTPUT_TIMESTAMP_FORMAT},
+    diff::{diff_json, Change},
+    error::DeployerError,
+    fees::{estimate_fees, get_registry_chain, read_gas_history, FeeEstimate, FeeMeter},
+    helpers::{canonical_hash, canonicalize, derive_wallet_key, verify_path},
+    manager_config::{get_manager_config_snapshot, set_manager_config, ManagerConfigSource},
+    manifest::{get_deployer_addresses, get_git_info, restore_endpoints, Manifest, ParamsSnapshot},
+    preflight::{preflight, PreflightReport},
+    production::{DeploymentSummary, ProductionGate, Review},
+    program_config::read_program_config_from_json,
+    program_params::get_program_params_path,
+    signer::Signer,
+    summary::ProgramSummary,
+    verify::{get_deployment_queriers, verify_output_dir, Mismatch},
+    workspace_config::WorkspaceConfig,
+};
+
+#[derive(Debug, PartialEq)]
+enum Status {
+    Process,
+    Success,
+    Fail,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Status::Process => write!(f, "process"),
+            Status::Success => write!(f, "success"),
+            Status::Fail => write!(f, "fail"),
+        }
+    }
+}
+
+/// Where the program configs and the manifest of a deployment are written
+#[derive(Debug, Clone, Default)]
+pub enum OutputSink {
+    /// `output/<env>-<timestamp>` in the program directory
+    #[default]
+    ProgramDir,
+    /// `<env>-<timestamp>` in this directory
+    Dir(PathBuf),
+    /// Nothing is written
+    Discard,
+}
+
+/// Builds, plans, deploys and verifies programs of a workspace,
+/// the `deployer` binary is a thin wrapper around it
+#[derive(Debug, Clone)]
+pub struct Deployer {
+    workspace: PathBuf,
+    env: String,
+    /// Production envs can't be deployed on by default
+    explicit_env: bool,
+    signer: Signer,
+    output: OutputSink,
+    manager_config: ManagerConfigSource,
+    review: Review,
+    /// Append deployments to the audit log of the workspace
+    audit_log: bool,
+    /// Sign the checksums of the output files with the deployer key
+    sign_output: bool,
+    /// Run the preflight checks before deploying
+    preflight: bool,
+}
+
+/// What a deployment would do, without touching any chain
+#[derive(Debug, Clone, Serialize)]
+pub struct DeploymentPlan {
+    /// Name of the program directory
+    pub program: String,
+    pub program_path: PathBuf,
+    pub env: String,
+    pub program_config: ProgramConfig,
+    /// sha256 of the canonical json of the program config, what reviewers approve
+    pub config_hash: String,
+    pub summary: DeploymentSummary,
+    /// Either `builder` or the path to the program config json
+    pub program_config_source: String,
+    /// Params the program was built with, if built from the builder
+    pub params: Option<ParamsSnapshot>,
+    /// Amounts and ratios that look wrong, the plan can't be deployed if any is not a warning
+    pub amount_issues: Vec<AmountIssue>,
+    /// Output directory of the last successful deployment on the env and the changes from its raw program config
+    pub deployed: Option<(String, Vec<Change>)>,
+    /// Fees of the deployment on every chain, empty if the manager config couldn't be read
+    pub fees: Vec<FeeEstimate>,
+}
+
+impl DeploymentPlan {
+    pub fn is_ok(&self) -> bool {
+        self.amount_issues.iter().all(|issue| issue.warning)
+    }
+
+    /// Save the plan to review and approve it before deploying on a production env
+    pub fn write(&self, path: &Path) -> Result<(), DeployerError> {
+        let content =
+            serde_json::to_string_pretty(self).map_err(|e| DeployerError::Output(e.to_string()))?;
+
+        std::fs::write(path, content)
+            .map_err(|e| DeployerError::Output(format!("{}: {}", path.display(), e)))
+    }
+}
+
+impl Display for DeploymentPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Deploy {} on {} from {}",
+            self.program_config.name, self.env, self.program_config_source
+        )?;
+        writeln!(
+            f,
+            "    {} accounts, {} libraries, {} authorizations",
+            self.program_config.accounts.len(),
+            self.program_config.libraries.len(),
+            self.program_config.authorizations.len()
+        )?;
+        writeln!(f, "    Program config hash: {}", self.config_hash)?;
+
+        match &self.deployed {
+            Some((output_dir, changes)) if changes.is_empty() => {
+                writeln!(f, "    Same as the last deployment {}", output_dir)?
+            }
+            Some((output_dir, changes)) => {
+                writeln!(f, "    Changes from the last deployment {}:", output_dir)?;
+                for change in changes {
+                    writeln!(f, "        {}", change)?;
+                }
+            }
+            None => writeln!(f, "    First deployment on {}", self.env)?,
+        }
+
+        for issue in &self.amount_issues {
+            writeln!(f, "    {}", issue)?;
+        }
+
+        if !self.fees.is_empty() {
+            writeln!(f, "    Estimated fees:")?;
+            for estimate in &self.fees {
+                writeln!(f, "        {}", estimate)?;
+            }
+        }
+
+        write!(f, "{}", self.summary)
+    }
+}
+
+/// A successful deployment
+#[derive(Debug, Clone, Serialize)]
+pub struct DeploymentResult {
+    /// Instantiated program config, with all the contract addresses
+    pub program_config: ProgramConfig,
+    pub manifest: Manifest,
+    /// Not set if the output is discarded
+    pub output_dir: Option<PathBuf>,
+    /// Program id, chains, UI link and key addresses, also written to the output directory
+    pub summary: ProgramSummary,
+}
+
+impl Display for DeploymentResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Program deployed successfully!")?;
+        write!(f, "{}", self.summary)
+    }
+}
+
+impl Deployer {
+    /// Deployer of the workspace at the given root, on mainnet by default
+    pub fn new(workspace: impl Into<PathBuf>) -> Self {
+        Deployer {
+            workspace: workspace.into(),
+            env: "mainnet".to_string(),
+            explicit_env: false,
+            signer: Signer::default(),
+            output: OutputSink::default(),
+            manager_config: ManagerConfigSource::default(),
+            review: Review::default(),
+            audit_log: true,
+            sign_output: false,
+            preflight: true,
+        }
+    }
+
+    pub fn with_env(mut self, env: &str) -> Self {
+        self.env = env.to_string();
+        self.explicit_env = true;
+        self
+    }
+
+    pub fn with_signer(mut self, signer: Signer) -> Self {
+        self.signer = signer;
+        self
+    }
+
+    /// Deployments are appended to `audit/deployments.jsonl` of the workspace by default
+    pub fn with_audit_log(mut self, enabled: bool) -> Self {
+        self.audit_log = enabled;
+        self
+    }
+
+    /// Sign the checksums of the output files, and so the manifest, with the deployer key
+    pub fn with_signed_output(mut self, enabled: bool) -> Self {
+        self.sign_output = enabled;
+        self
+    }
+
+    /// The preflight checks run before every deployment by default
+    pub fn with_preflight(mut self, enabled: bool) -> Self {
+        self.preflight = enabled;
+        self
+    }
+
+    pub fn with_output(mut self, output: OutputSink) -> Self {
+        self.output = output;
+        self
+    }
+
+    pub fn with_manager_config(mut self, manager_config: ManagerConfigSource) -> Self {
+        self.manager_config = manager_config;
+        self
+    }
+
+    /// Reviewed plan, approvals and confirmation, required to deploy on a production env
+    pub fn with_review(mut self, review: Review) -> Self {
+        self.review = review;
+        self
+    }
+
+    /// Whether the env is in the production envs of `deployer.toml`
+    pub fn is_production(&self) -> Result<bool, DeployerError> {
+        Ok(WorkspaceConfig::load(&self.workspace)?
+            .production
+            .is_production(&self.env))
+    }
+
+    pub fn workspace(&self) -> &Path {
+        &self.workspace
+    }
+
+    pub fn env(&self) -> &str {
+        &self.env
+    }
+
+    fn program_path(&self, program: &dyn ProgramDefinition) -> Result<PathBuf, DeployerError> {
+        info!("Verifying program path");
+        verify_path(&self.workspace, &program.path())?;
+
+        Ok(self.workspace.join(program.path()))
+    }
+
+    /// Build the program config of the program for the environment
+    pub async fn build(
+        &self,
+        program: &dyn ProgramDefinition,
+    ) -> Result<ProgramConfig, DeployerError> {
+        let program_path = self.program_path(program)?;
+        let params = get_valid_params(program, &program_path, &self.env)?;
+
+        program
+            .build(params)
+            .map_err(|e| DeployerError::categorize(e, DeployerError::Build))
+    }
+
+    /// Build the program and check what deploying it would do
+    pub async fn plan(
+        &self,
+        program: &dyn ProgramDefinition,
+    ) -> Result<DeploymentPlan, DeployerError> {
+        let program_path = self.program_path(program)?;
+
+        info!("Building program config from builder");
+        let program_params = get_valid_params(program, &program_path, &self.env)?;
+        let params_path = get_program_params_path(&program_path, &self.env);
+        let params_snapshot = ParamsSnapshot {
+            path: params_path
+                .strip_prefix(&self.workspace)
+                .unwrap_or(&params_path)
+                .display()
+                .to_string(),
+            content: std::fs::read_to_string(&params_path)
+                .map_err(|e| DeployerError::Path(format!("{}: {}", params_path.display(), e)))?,
+            effective: program_params.to_json()?,
+        };
+
+        let program_config = program
+            .build(program_params)
+            .map_err(|e| DeployerError::categorize(e, DeployerError::Build))?;
+
+        self.get_plan(
+            program_path,
+            program_config,
+            "builder".to_string(),
+            Some(params_snapshot),
+        )
+    }
+
+    /// Check what deploying an already built program config json would do
+    pub async fn plan_from_file(
+        &self,
+        program: &dyn ProgramDefinition,
+        program_config_path: &Path,
+    ) -> Result<DeploymentPlan, DeployerError> {
+        let program_path = self.program_path(program)?;
+
+        info!("Reading program config from file");
+        let program_config = read_program_config_from_json(program_config_path)?;
+
+        self.get_plan(
+            program_path,
+            program_config,
+            program_config_path.display().to_string(),
+            None,
+        )
+    }
+
+    fn get_plan(
+        &self,
+        program_path: PathBuf,
+        program_config: ProgramConfig,
+        program_config_source: String,
+        params: Option<ParamsSnapshot>,
+    ) -> Result<DeploymentPlan, DeployerError> {
+        let built = serde_json::to_value(&program_config)
+            .map_err(|e| DeployerError::Validation(e.to_string()))?;
+
+        // Catch wrong amounts before anything is instantiated
+        info!("Checking amounts and ratios");
+        let checks = get_amount_checks(&program_path, &self.env)
+            .map_err(|e| DeployerError::categorize(e, DeployerError::Params))?;
+        let amount_issues = check_amounts(&built, &checks)
+            .map_err(|e| DeployerError::categorize(e, DeployerError::Validation))?;
+
+        let registry = DenomRegistry::load(&program_path, &self.env)
+            .map_err(|e| DeployerError::categorize(e, DeployerError::Params))?;
+        let summary = DeploymentSummary::new(&built, &registry);
+        let config_hash = canonical_hash(&built)
+            .map_err(|e| DeployerError::categorize(e, DeployerError::Validation))?;
+
+        // Fees need the gas prices of the manager config, the plan is still useful without them
+        let fees = match self.get_manager_config() {
+            Ok(manager_config) => {
+                estimate_fees(&built, &manager_config, &read_gas_history(&self.workspace))
+            }
+            Err(e) => {
+                warn!("Failed to estimate the fees: {}", e);
+                vec![]
+            }
+        };
+
+        // The raw program config of a deployment is the builder output at the time
+        let built = canonicalize(built);
+        let deployed =
+            get_latest_success_output(&program_path, &self.env).and_then(|output_path| {
+                let content =
+                    std::fs::read_to_string(output_path.join("raw-program-config.json")).ok()?;
+                let deployed = canonicalize(serde_json::from_str(&content).ok()?);
+
+                Some((
+                    output_path
+                        .strip_prefix(&program_path)
+                        .unwrap_or(&output_path)
+                        .display()
+                        .to_string(),
+                    diff_json(&deployed, &built),
+                ))
+            });
+
+        Ok(DeploymentPlan {
+            program: program_path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            program_path,
+            env: self.env.clone(),
+            program_config,
+            config_hash,
+            summary,
+            program_config_source,
+            params,
+            amount_issues,
+            deployed,
+            fees,
+        })
+    }
+
+    /// Instantiate the program of the plan on chain
+    pub async fn deploy(&self, plan: DeploymentPlan) -> Result<DeploymentResult, DeployerError> {
+        if plan.env != self.env {
+            return Err(DeployerError::Validation(format!(
+                "Plan is for {} but the deployer is on {}",
+                plan.env, self.env
+            )));
+        }
+        if !plan.is_ok() {
+            return Err(DeployerError::Validation(
+                "Some amounts or ratios look wrong, see `check-amounts`".to_string(),
+            ));
+        }
+
+        let mnemonic = self.signer.get_mnemonic(&self.workspace)?;
+
+        let workspace_config = WorkspaceConfig::load(&self.workspace)?;
+        let production = workspace_config.production;
+        if production.is_production(&self.env) {
+            info!("Checking the production gate");
+            ProductionGate {
+                config: &production,
+                program: &plan.program,
+                env: &self.env,
+                explicit_env: self.explicit_env,
+                dirty_tree: get_git_info(&plan.program_path).dirty,
+                config_hash: &plan.config_hash,
+                review: &self.review,
+                mnemonic: &mnemonic,
+            }
+            .check()?;
+        }
+
+        // Fail before touching the chain if the output can't be signed
+        let output_key = match self.sign_output {
+            true => Some(derive_wallet_key(&mnemonic).map_err(|e| {
+                DeployerError::Signer(format!(
+                    "Failed to derive the key to sign the output: {}",
+                    e
+                ))
+            })?),
+            false => None,
+        };
+
+        if self.preflight {
+            info!("Running the preflight checks");
+            let report = self.run_preflight(&plan, &mnemonic).await?;
+            print!("{report}");
+
+            if !report.is_ok() {
+                return Err(DeployerError::Validation(format!(
+                    "{} preflight checks failed",
+                    report.issues.len()
+                )));
+            }
+        }
+
+        let started_at = Utc::now();
+        let timestamp = started_at.format(OUTPUT_TIMESTAMP_FORMAT).to_string();
+        let output_name = format!("{}-{}", self.env, timestamp);
+        let output_path = match &self.output {
+            OutputSink::ProgramDir => Some(plan.program_path.join("output").join(output_name)),
+            OutputSink::Dir(dir) => Some(dir.join(output_name)),
+            OutputSink::Discard => None,
+        };
+
+        // Set manager config for the chosen environment
+        info!("Setting manager config for the chosen environment");
+        let manager_config_path = self.manager_config.get_dir(&self.workspace, &self.env)?;
+        let _manager_mnemonic = set_manager_config(&manager_config_path, &mnemonic).await?;
+
+        let mut program_config = plan.program_config;
+        let mut manifest = Manifest::new(
+            &plan.program_path,
+            &self.env,
+            &program_config,
+            plan.program_config_source,
+            plan.params,
+            get_manager_config_snapshot(&manager_config_path)?,
+            started_at,
+        )
+        .map_err(|e| DeployerError::categorize(e, DeployerError::Output))?
+        .with_signer(&self.signer, &mnemonic);
+
+        // Write the raw program config to file
+        info!("Writing raw program config to file");
+        let output_path = write_to_output(
+            &program_config,
+            output_path,
+            Status::Process,
+            "raw",
+            &mut manifest,
+            None,
+            None,
+        )?;
+
+        // Fees are measured to estimate the next deployments, the deployment doesn't depend on it
+        let raw = serde_json::to_value(&program_config)
+            .map_err(|e| DeployerError::Output(e.to_string()))?;
+        let fee_meter =
+            match FeeMeter::start(&raw, &manifest.manager_config, &manifest.deployer_addresses)
+                .await
+            {
+                Ok(fee_meter) => Some(fee_meter),
+                Err(e) => {
+                    warn!("The fees of the deployment won't be measured: {}", e);
+                    None
+                }
+            };
+
+        // Use program manager to deploy the program
+        println!("Instantiating program...");
+        match valence_program_manager::init_program(&mut program_config).await {
+            Ok(_) => (),
+            Err(e) => {
+                let output = write_to_output(
+                    &program_config,
+                    output_path,
+                    Status::Fail,
+                    "debug",
+                    &mut manifest,
+                    output_key.as_ref(),
+                    None,
+                );
+                let audited = self.audit(
+                    AuditAction::Failure,
+                    &plan.config_hash,
+                    &manifest,
+                    output.as_ref().ok().cloned().flatten(),
+                    &mnemonic,
+                );
+                output?;
+                audited?;
+
+                return Err(DeployerError::Instantiation(e));
+            }
+        };
+
+        manifest.program_id = Some(program_config.id);
+        if let Some(fee_meter) = fee_meter {
+            match fee_meter.stop().await {
+                Ok(gas_used) => manifest.gas_used = gas_used,
+                Err(e) => warn!("Failed to measure the fees of the deployment: {}", e),
+            }
+        }
+        let instantiated = serde_json::to_value(&program_config)
+            .map_err(|e| DeployerError::Output(e.to_string()))?;
+        let summary = ProgramSummary::new(
+            &instantiated,
+            &manifest.manager_config,
+            &self.env,
+            &workspace_config.summary,
+        )
+        .await;
+
+        // Write instantiated program to file, the program is on chain so it is audited
+        // even if its output can't be written
+        let output = write_to_output(
+            &program_config,
+            output_path,
+            Status::Success,
+            "instantiated",
+            &mut manifest,
+            output_key.as_ref(),
+            Some(&summary),
+        );
+        let audited = self.audit(
+            AuditAction::Deploy,
+            &plan.config_hash,
+            &manifest,
+            output.as_ref().ok().cloned().flatten(),
+            &mnemonic,
+        );
+        let output_dir = output?;
+        audited?;
+
+        Ok(DeploymentResult {
+            summary,
+            program_config,
+            manifest,
+            output_dir,
+        })
+    }
+
+    /// Check the deployer balance, the code ids, the registry and the external contracts
+    /// of the plan on chain, without instantiating anything
+    pub async fn preflight(&self, plan: &DeploymentPlan) -> Result<PreflightReport, DeployerError> {
+        let mnemonic = self.signer.get_mnemonic(&self.workspace)?;
+
+        self.run_preflight(plan, &mnemonic).await
+    }
+
+    /// Verify the output files of a deployment match their checksums and signature
+    pub fn verify_artifacts(&self, output_dir: &Path) -> Result<Vec<Mismatch>, DeployerError> {
+        verify_artifacts(output_dir, &self.workspace)
+            .map_err(|e| DeployerError::Path(format!("{}: {}", output_dir.display(), e)))
+    }
+
+    /// Verify the output files of a successful deployment, and that the contracts on chain
+    /// match its instantiated program config
+    pub async fn verify(&self, output_dir: &Path) -> Result<Vec<Mismatch>, DeployerError> {
+        let mut mismatches = self.verify_artifacts(output_dir)?;
+
+        let manifest = Manifest::read(output_dir).ok();
+        let env = match &manifest {
+            Some(manifest) => manifest.env.clone(),
+            None => output_dir
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(parse_output_dir_name)
+                .map(|(env, _, _)| env)
+                .ok_or_else(|| {
+                    DeployerError::Path(format!(
+                        "Not a deployment output directory: {}",
+                        output_dir.display()
+                    ))
+                })?,
+        };
+        let current =
+            get_manager_config_snapshot(&self.manager_config.get_dir(&self.workspace, &env)?)?;
+
+        // Verify against the manager config the program was deployed with if we have it,
+        // its endpoints are redacted so they are taken from the current one
+        let manager_config = match manifest {
+            Some(manifest) => restore_endpoints(manifest.manager_config, &current),
+            None => current,
+        };
+
+        mismatches.extend(
+            verify_output_dir(output_dir, &manager_config)
+                .await
+                .map_err(|e| DeployerError::categorize(e, DeployerError::Fetch))?,
+        );
+
+        Ok(mismatches)
+    }
+}
+
+impl Deployer {
+    /// Manager config of the env as plain json
+    fn get_manager_config(&self) -> Result<Value, DeployerError> {
+        get_manager_config_snapshot(&self.manager_config.get_dir(&self.workspace, &self.env)?)
+    }
+
+    async fn run_preflight(
+        &self,
+        plan: &DeploymentPlan,
+        mnemonic: &str,
+    ) -> Result<PreflightReport, DeployerError> {
+        let manager_config = self.get_manager_config()?;
+        let program_config = serde_json::to_value(&plan.program_config)
+            .map_err(|e| DeployerError::Validation(e.to_string()))?;
+        let queriers = get_deployment_queriers(&program_config, &manager_config)
+            .await
+            .map_err(|e| DeployerError::categorize(e, DeployerError::Fetch))?;
+        // The plan may have been built without the manager config
+        let fees = estimate_fees(
+            &program_config,
+            &manager_config,
+            &read_gas_history(&self.workspace),
+        );
+
+        Ok(preflight(
+            &program_config,
+            &manager_config,
+            fees,
+            &get_deployer_addresses(&manager_config, mnemonic),
+            &queriers,
+        )
+        .await)
+    }
+
+    fn audit(
+        &self,
+        action: AuditAction,
+        config_hash: &str,
+        manifest: &Manifest,
+        output_dir: Option<PathBuf>,
+        mnemonic: &str,
+    ) -> Result<(), DeployerError> {
+        if !self.audit_log {
+            return Ok(());
+        }
+
+        // The deployer of the program is the one on the chain of the authorization contract
+        let deployer_address = manifest
+            .deployer_addresses
+            .get(&get_registry_chain(&manifest.manager_config))
+            .cloned();
+
+        append_audit_entry(
+            &self.workspace,
+            AuditRecord {
+                timestamp: manifest.started_at,
+                action,
+                program: manifest.program.clone(),
+                env: self.env.clone(),
+                deployer_address,
+                config_hash: config_hash.to_string(),
+                program_id: manifest.program_id,
+                status: manifest.status.clone(),
+                output_dir,
+                output_signed: manifest.signed_output,
+            },
+            mnemonic,
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Write the program config, the manifest and the summary if any to the output directory, and the checksums
+/// once the deployment is done, returns the path of the output directory as it might be
+/// renamed with the final status
+fn write_to_output(
+    program_config: &ProgramConfig,
+    path: Option<PathBuf>,
+    status: Status,
+    prefix: &str,
+    manifest: &mut Manifest,
+    signing_key: Option<&SigningKey>,
+    summary: Option<&ProgramSummary>,
+) -> Result<Option<PathBuf>, DeployerError> {
+    // Update the manifest with the latest status
+    manifest.status = status.to_string();
+    if status != Status::Process {
+        manifest.finished_at = Some(Utc::now());
+        manifest.checksums = true;
+        manifest.signed_output = signing_key.is_some();
+    }
+
+    let Some(mut path) = path else {
+        return Ok(None);
+    };
+    let output_error = |path: &Path, e: &dyn std::error::Error| {
+        DeployerError::Output(format!("{}: {}", path.display(), e))
+    };
+
+    if !path.exists() {
+        std::fs::create_dir_all(&path).map_err(|e| output_error(&path, &e))?;
+    } else if status != Status::Process {
+        let mut new_path = path.clone().into_os_string();
+        new_path.push(format!("-{}", status));
+        let new_path = PathBuf::from(new_path);
+        std::fs::rename(&path, &new_path).map_err(|e| output_error(&path, &e))?;
+        path = new_path;
+    }
+
+    // Construct the full file path
+    let file_name = format!("{}-program-config.json", prefix);
+    let file_path = path.join(file_name);
+
+    // Serialize the data to a string
+    let content =
+        serde_json::to_string(program_config).map_err(|e| output_error(&file_path, &e))?;
+
+    // Create and write to the file
+    std::fs::File::create(&file_path)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .map_err(|e| output_error(&file_path, &e))?;
+
+    manifest
+        .write(&path)
+        .map_err(|e| DeployerError::categorize(e, DeployerError::Output))?;
+
+    if let Some(summary) = summary {
+        summary
+            .write(&path)
+            .map_err(|e| DeployerError::Output(format!("{}: {}", path.display(), e)))?;
+    }
+
+    if status != Status::Process {
+        write_checksums(&path, signing_key)
+            .map_err(|e| DeployerError::Output(format!("{}: {}", path.display(), e)))?;
+    }
+
+    Ok(Some(path))
+}
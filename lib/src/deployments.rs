@@ -0,0 +1,640 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{deployer::Deployer, error::DeployerError, manifest::Manifest};
+
+pub(crate) const OUTPUT_TIMESTAMP_FORMAT: &str = "%Y-%m-%d_%H:%M:%S";
+const INDEX_FILE_NAME: &str = "deployments.json";
+
+/// A single run of the deployer, as found in `programs/<program>/output/<env>-<timestamp>-<status>`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Deployment {
+    /// Program directory name
+    pub program: String,
+    /// Cargo package name of the program
+    pub package: Option<String>,
+    pub env: String,
+    pub timestamp: DateTime<Utc>,
+    pub status: String,
+    pub program_id: Option<u64>,
+    pub owner: Option<String>,
+    /// Git commit the deployment was made from, only known for deployments that have a manifest
+    pub commit: Option<String>,
+    /// Output directory path relative to the workspace
+    pub output_dir: String,
+    /// Addresses of the program contracts, by account/library name, `authorization` and `processor:<domain>`
+    pub addresses: BTreeMap<String, String>,
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum DeploymentsCommand {
+    /// List all deployments
+    List {
+        #[command(flatten)]
+        filter: DeploymentFilter,
+        /// Print the deployments as json
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print the latest deployment that matches the filter as json
+    Latest {
+        #[command(flatten)]
+        filter: DeploymentFilter,
+    },
+    /// Write all deployments to `deployments.json` in the workspace root
+    Index,
+    /// Verify the output files of a deployment match their checksums and signature,
+    /// and the contracts on chain and the registry entry match its instantiated program config
+    Verify {
+        /// Output directory of the deployment
+        output_dir: PathBuf,
+        /// Only check the output files, without querying the chain
+        #[arg(long)]
+        artifacts_only: bool,
+        /// Print the mismatches as json
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(clap::Args, Debug, Default, Clone)]
+pub struct DeploymentFilter {
+    /// Program directory or package name
+    #[arg(short, long)]
+    pub program: Option<String>,
+    /// Enviroment of the deployment Ex: mainnet, testnet
+    #[arg(short, long)]
+    pub env: Option<String>,
+    /// Status of the deployment Ex: success, fail, process
+    #[arg(short, long)]
+    pub status: Option<String>,
+}
+
+impl DeploymentFilter {
+    pub fn matches(&self, deployment: &Deployment) -> bool {
+        let program_matches = self.program.as_ref().is_none_or(|program| {
+            &deployment.program == program || deployment.package.as_ref() == Some(program)
+        });
+        let env_matches = self
+            .env
+            .as_ref()
+            .is_none_or(|env| deployment.env.eq_ignore_ascii_case(env));
+        let status_matches = self
+            .status
+            .as_ref()
+            .is_none_or(|status| deployment.status.eq_ignore_ascii_case(status));
+
+        program_matches && env_matches && status_matches
+    }
+}
+
+/// Run a `deployments` command of the deployer, mismatches found by `verify` are validation errors
+pub(crate) async fn run_deployments_command(
+    workspace_path: &Path,
+    command: DeploymentsCommand,
+) -> Result<(), DeployerError> {
+    let to_json = |e: serde_json::Error| DeployerError::Output(e.to_string());
+
+    let deployments = get_deployments(workspace_path)?;
+
+    match command {
+        DeploymentsCommand::List { filter, json } => {
+            let deployments = deployments
+                .into_iter()
+                .filter(|d| filter.matches(d))
+                .collect::<Vec<_>>();
+
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&deployments).map_err(to_json)?
+                );
+            } else {
+                print_deployments(&deployments);
+            }
+        }
+        DeploymentsCommand::Latest { mut filter } => {
+            // Latest is mostly used to find what is live, so default to successful deployments
+            filter.status.get_or_insert_with(|| "success".to_string());
+
+            let latest = get_latest_deployment(&deployments, &filter).ok_or_else(|| {
+                DeployerError::Path("No deployment matches the given filter".to_string())
+            })?;
+
+            println!("{}", serde_json::to_string_pretty(latest).map_err(to_json)?);
+        }
+        DeploymentsCommand::Index => {
+            let index_path = workspace_path.join(INDEX_FILE_NAME);
+            std::fs::write(
+                &index_path,
+                serde_json::to_string_pretty(&deployments).map_err(to_json)?,
+            )
+            .map_err(|e| DeployerError::Output(format!("{}: {}", index_path.display(), e)))?;
+
+            println!(
+                "Wrote {} deployments to {}",
+                deployments.len(),
+                index_path.display()
+            );
+        }
+        DeploymentsCommand::Verify {
+            output_dir,
+            artifacts_only,
+            json,
+        } => {
+            let deployer = Deployer::new(workspace_path);
+            let mismatches = match artifacts_only {
+                true => deployer.verify_artifacts(&output_dir)?,
+                false => deployer.verify(&output_dir).await?,
+            };
+
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&mismatches).map_err(to_json)?
+                );
+            } else if mismatches.is_empty() {
+                println!("No mismatches found");
+            } else {
+                mismatches
+                    .iter()
+                    .for_each(|mismatch| println!("{mismatch}"));
+            }
+
+            if !mismatches.is_empty() {
+                return Err(DeployerError::Validation(format!(
+                    "{} mismatches found",
+                    mismatches.len()
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the latest deployment that matches the filter
+pub fn get_latest_deployment<'a>(
+    deployments: &'a [Deployment],
+    filter: &DeploymentFilter,
+) -> Option<&'a Deployment> {
+    deployments
+        .iter()
+        .filter(|d| filter.matches(d))
+        .max_by_key(|d| d.timestamp)
+}
+
+/// Scan the output directory of every program in the workspace,
+/// deployments are sorted by program and timestamp
+pub fn get_deployments(workspace_path: &Path) -> Result<Vec<Deployment>, Box<dyn Error>> {
+    let programs_path = workspace_path.join("programs");
+
+    if !programs_path.exists() {
+        return Err("Programs path doesn't exists, make sure you ran the script from the workplace directory".into());
+    }
+
+    let mut deployments = vec![];
+
+    for program_path in read_dirs(&programs_path)? {
+        let output_path = program_path.join("output");
+
+        if !output_path.exists() {
+            continue;
+        }
+
+        let package = get_package_name(&program_path);
+
+        for deployment_path in read_dirs(&output_path)? {
+            if let Some(deployment) = read_deployment(
+                workspace_path,
+                &program_path,
+                package.clone(),
+                &deployment_path,
+            )? {
+                deployments.push(deployment);
+            }
+        }
+    }
+
+    deployments.sort_by(|a, b| {
+        a.program
+            .cmp(&b.program)
+            .then_with(|| a.timestamp.cmp(&b.timestamp))
+    });
+
+    Ok(deployments)
+}
+
+/// Get the output directory of the latest successful deployment of a program on an env
+pub(crate) fn get_latest_success_output(program_path: &Path, env: &str) -> Option<PathBuf> {
+    read_dirs(&program_path.join("output"))
+        .ok()?
+        .iter()
+        .filter_map(|path| read_deployment(program_path, program_path, None, path).ok()?)
+        .filter(|d| d.env == env && d.status == "success")
+        .max_by_key(|d| d.timestamp)
+        .map(|d| program_path.join(d.output_dir))
+}
+
+fn read_dirs(path: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut dirs = std::fs::read_dir(path)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_dir())
+        .collect::<Vec<_>>();
+    dirs.sort();
+
+    Ok(dirs)
+}
+
+pub(crate) fn get_package_name(program_path: &Path) -> Option<String> {
+    let manifest: toml::Table = std::fs::read_to_string(program_path.join("Cargo.toml"))
+        .ok()?
+        .parse()
+        .ok()?;
+
+    manifest
+        .get("package")?
+        .get("name")?
+        .as_str()
+        .map(|s| s.to_string())
+}
+
+/// Split an output directory name into env, timestamp and status,
+/// Ex: `ntrn-devnet-2-2025-03-12_03:09:08` or `mainnet-2025-03-31_18:30:33-success`
+pub(crate) fn parse_output_dir_name(name: &str) -> Option<(String, DateTime<Utc>, Option<String>)> {
+    // The timestamp has a fixed length, so find the first dash that is followed by a valid timestamp
+    const TIMESTAMP_LEN: usize = "2025-03-31_18:30:33".len();
+
+    name.match_indices('-').find_map(|(i, _)| {
+        let rest = &name[i + 1..];
+        let timestamp = rest.get(..TIMESTAMP_LEN)?;
+        let timestamp = NaiveDateTime::parse_from_str(timestamp, OUTPUT_TIMESTAMP_FORMAT).ok()?;
+        let status = rest[TIMESTAMP_LEN..]
+            .strip_prefix('-')
+            .map(|s| s.to_string());
+
+        Some((name[..i].to_string(), timestamp.and_utc(), status))
+    })
+}
+
+fn read_deployment(
+    workspace_path: &Path,
+    program_path: &Path,
+    package: Option<String>,
+    deployment_path: &Path,
+) -> Result<Option<Deployment>, Box<dyn Error>> {
+    let Some(dir_name) = deployment_path.file_name().and_then(|n| n.to_str()) else {
+        return Ok(None);
+    };
+    let Some((env, timestamp, status)) = parse_output_dir_name(dir_name) else {
+        return Ok(None);
+    };
+
+    let instantiated = read_json(&deployment_path.join("instantiated-program-config.json"));
+    let debug = read_json(&deployment_path.join("debug-program-config.json"));
+    let raw = read_json(&deployment_path.join("raw-program-config.json"));
+
+    // Older deployments were not renamed with their status, so we infer it from the files
+    let status = status.unwrap_or_else(|| {
+        if instantiated.is_some() {
+            "success".to_string()
+        } else if debug.is_some() {
+            "fail".to_string()
+        } else {
+            "process".to_string()
+        }
+    });
+
+    let manifest = Manifest::read(deployment_path).ok();
+    let config = instantiated.or(debug).or(raw).unwrap_or_default();
+
+    let program_id = config
+        .get("id")
+        .and_then(|id| id.as_u64())
+        // Program id is 0 until the program is registered
+        .filter(|id| *id != 0);
+
+    Ok(Some(Deployment {
+        program: program_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default()
+            .to_string(),
+        package,
+        env,
+        timestamp,
+        status,
+        program_id,
+        owner: config
+            .get("owner")
+            .and_then(|o| o.as_str())
+            .map(|o| o.to_string()),
+        commit: manifest.and_then(|m| m.git.commit),
+        output_dir: deployment_path
+            .strip_prefix(workspace_path)
+            .unwrap_or(deployment_path)
+            .display()
+            .to_string(),
+        addresses: get_addresses(&config),
+    }))
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+/// Collect the contract addresses of a serialized program config
+pub(crate) fn get_addresses(config: &Value) -> BTreeMap<String, String> {
+    let mut addresses = BTreeMap::new();
+
+    for key in ["accounts", "libraries"] {
+        let Some(entries) = config.get(key).and_then(|e| e.as_object()) else {
+            continue;
+        };
+
+        for entry in entries.values() {
+            if let (Some(name), Some(addr)) = (
+                entry.get("name").and_then(|n| n.as_str()),
+                entry.get("addr").and_then(|a| a.as_str()),
+            ) {
+                addresses.insert(name.to_string(), addr.to_string());
+            }
+        }
+    }
+
+    if let Some(authorization_data) = config.get("authorization_data") {
+        if let Some(addr) = authorization_data
+            .get("authorization_addr")
+            .and_then(|a| a.as_str())
+            .filter(|a| !a.is_empty())
+        {
+            addresses.insert("authorization".to_string(), addr.to_string());
+        }
+
+        if let Some(processors) = authorization_data
+            .get("processor_addrs")
+            .and_then(|p| p.as_object())
+        {
+            for (domain, addr) in processors {
+                if let Some(addr) = addr.as_str() {
+                    addresses.insert(format!("processor:{}", domain), addr.to_string());
+                }
+            }
+        }
+    }
+
+    addresses
+}
+
+fn print_deployments(deployments: &[Deployment]) {
+    println!(
+        "{:<50} {:<15} {:<20} {:<8} {:<6} OWNER",
+        "PROGRAM", "ENV", "TIMESTAMP", "STATUS", "ID"
+    );
+
+    for d in deployments {
+        println!(
+            "{:<50} {:<15} {:<20} {:<8} {:<6} {}",
+            d.program,
+            d.env,
+            d.timestamp.format(OUTPUT_TIMESTAMP_FORMAT),
+            d.status,
+            d.program_id.map(|id| id.to_string()).unwrap_or_default(),
+            d.owner.as_deref().unwrap_or_default(),
+        );
+
+        if let Some(authorization) = d.addresses.get("authorization") {
+            println!("    authorization: {}", authorization);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use chrono::{DateTime, Utc};
+    use serde_json::json;
+    use tempfile::{tempdir, TempDir};
+
+    use super::{
+        get_deployments, get_latest_success_output, parse_output_dir_name, run_deployments_command,
+        DeploymentFilter, DeploymentsCommand,
+    };
+    use crate::artifacts::write_checksums;
+
+    const PROGRAM: &str = "2025-03-23-prod-dICS-gradual-ls";
+
+    /// Workspace with a program and its output directories, with the given program config files
+    fn workspace(outputs: &[(&str, &[&str])]) -> TempDir {
+        let workspace = tempdir().unwrap();
+        let program_path = workspace.path().join("programs").join(PROGRAM);
+        std::fs::create_dir_all(&program_path).unwrap();
+        std::fs::write(
+            program_path.join("Cargo.toml"),
+            "[package]\nname = \"prod-dics-gradual-ls\"\n",
+        )
+        .unwrap();
+
+        for (dir, files) in outputs {
+            let output_path = program_path.join("output").join(dir);
+            std::fs::create_dir_all(&output_path).unwrap();
+
+            for prefix in *files {
+                let config = match *prefix {
+                    "instantiated" => json!({ "id": 2, "owner": "neutron1owner" }),
+                    _ => json!({ "id": 0, "owner": "neutron1owner" }),
+                };
+                std::fs::write(
+                    output_path.join(format!("{}-program-config.json", prefix)),
+                    config.to_string(),
+                )
+                .unwrap();
+            }
+        }
+
+        workspace
+    }
+
+    fn timestamp(timestamp: &str) -> DateTime<Utc> {
+        timestamp.parse().unwrap()
+    }
+
+    #[test]
+    fn output_dir_names_are_split_on_the_timestamp() {
+        assert_eq!(
+            parse_output_dir_name("ntrn-devnet-2-2025-03-12_03:09:08"),
+            Some((
+                "ntrn-devnet-2".to_string(),
+                timestamp("2025-03-12T03:09:08Z"),
+                None
+            ))
+        );
+        assert_eq!(
+            parse_output_dir_name("mainnet-2025-03-31_18:30:33-success"),
+            Some((
+                "mainnet".to_string(),
+                timestamp("2025-03-31T18:30:33Z"),
+                Some("success".to_string())
+            ))
+        );
+    }
+
+    #[test]
+    fn malformed_output_dir_names_are_not_deployments() {
+        for name in [
+            "",
+            "mainnet",
+            "mainnet-2025-03-31",
+            "mainnet-2025-13-31_18:30:33",
+            "mainnet_2025-03-31_18:30:33",
+            "2025-03-31_18:30:33",
+        ] {
+            assert_eq!(parse_output_dir_name(name), None, "{}", name);
+        }
+    }
+
+    #[test]
+    fn status_is_inferred_from_the_files_without_a_suffix() {
+        let workspace = workspace(&[
+            (
+                "ntrn-devnet-2-2025-03-12_03:09:08",
+                &["raw", "instantiated"],
+            ),
+            ("ntrn-devnet-2-2025-03-12_04:09:08", &["raw", "debug"]),
+            ("ntrn-devnet-2-2025-03-12_05:09:08", &["raw"]),
+            (
+                "ntrn-devnet-2-2025-03-12_06:09:08-fail",
+                &["raw", "instantiated"],
+            ),
+            ("not-a-deployment", &["raw"]),
+        ]);
+
+        let deployments = get_deployments(workspace.path()).unwrap();
+
+        assert_eq!(
+            deployments
+                .iter()
+                .map(|d| (d.status.as_str(), d.program_id))
+                .collect::<Vec<_>>(),
+            vec![
+                ("success", Some(2)),
+                ("fail", None),
+                ("process", None),
+                ("fail", Some(2)),
+            ]
+        );
+        assert!(deployments.iter().all(|d| d.env == "ntrn-devnet-2"
+            && d.package.as_deref() == Some("prod-dics-gradual-ls")
+            && d.owner.as_deref() == Some("neutron1owner")));
+        assert_eq!(
+            deployments[0].output_dir,
+            format!(
+                "programs/{}/output/ntrn-devnet-2-2025-03-12_03:09:08",
+                PROGRAM
+            )
+        );
+    }
+
+    #[test]
+    fn latest_success_output_is_of_the_env() {
+        let workspace = workspace(&[
+            (
+                "mainnet-2025-03-30_10:00:00-success",
+                &["raw", "instantiated"],
+            ),
+            ("mainnet-2025-03-31_10:00:00", &["raw", "instantiated"]),
+            ("mainnet-2025-04-01_10:00:00-fail", &["raw", "debug"]),
+            (
+                "testnet-2025-04-02_10:00:00-success",
+                &["raw", "instantiated"],
+            ),
+        ]);
+        let program_path = workspace.path().join("programs").join(PROGRAM);
+
+        assert_eq!(
+            get_latest_success_output(&program_path, "mainnet"),
+            Some(program_path.join("output/mainnet-2025-03-31_10:00:00"))
+        );
+        assert_eq!(get_latest_success_output(&program_path, "rehearsal"), None);
+        assert_eq!(
+            get_latest_success_output(Path::new("programs/missing"), "mainnet"),
+            None
+        );
+    }
+
+    #[test]
+    fn filter_matches_program_directory_or_package_env_and_status() {
+        let workspace = workspace(&[("Mainnet-2025-03-31_10:00:00-success", &["instantiated"])]);
+        let deployment = &get_deployments(workspace.path()).unwrap()[0];
+        let filter = |program: Option<&str>, env: Option<&str>, status: Option<&str>| {
+            DeploymentFilter {
+                program: program.map(|p| p.to_string()),
+                env: env.map(|e| e.to_string()),
+                status: status.map(|s| s.to_string()),
+            }
+            .matches(deployment)
+        };
+
+        assert!(filter(None, None, None));
+        assert!(filter(Some(PROGRAM), Some("mainnet"), Some("SUCCESS")));
+        assert!(filter(Some("prod-dics-gradual-ls"), None, None));
+        assert!(!filter(Some("prod-dics"), None, None));
+        assert!(!filter(None, Some("testnet"), None));
+        assert!(!filter(None, None, Some("fail")));
+    }
+
+    #[tokio::test]
+    async fn missing_deployment_exits_with_the_path_code() {
+        let workspace = workspace(&[("mainnet-2025-04-01_10:00:00-fail", &["raw", "debug"])]);
+
+        let e = run_deployments_command(
+            workspace.path(),
+            DeploymentsCommand::Latest {
+                filter: DeploymentFilter::default(),
+            },
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(e.exit_code(), 3);
+        assert_eq!(e.report().message, "No deployment matches the given filter");
+    }
+
+    #[tokio::test]
+    async fn mismatches_exit_with_the_validation_code() {
+        let workspace = workspace(&[(
+            "mainnet-2025-03-31_10:00:00-success",
+            &["raw", "instantiated"],
+        )]);
+        let output_dir = workspace
+            .path()
+            .join("programs")
+            .join(PROGRAM)
+            .join("output/mainnet-2025-03-31_10:00:00-success");
+        write_checksums(&output_dir, None).unwrap();
+        std::fs::write(output_dir.join("raw-program-config.json"), "{}").unwrap();
+
+        let e = run_deployments_command(
+            workspace.path(),
+            DeploymentsCommand::Verify {
+                output_dir,
+                artifacts_only: true,
+                json: false,
+            },
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(e.exit_code(), 8);
+        assert_eq!(e.report().message, "1 mismatches found");
+    }
+}
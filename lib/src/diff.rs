@@ -0,0 +1,462 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+};
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Program config with all ids and contract addresses replaced by names,
+/// so 2 configs of the same program can be compared regardless of the deployment
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct NormalizedProgram {
+    pub name: Value,
+    pub owner: Value,
+    /// Account name to account info
+    pub accounts: BTreeMap<String, Value>,
+    /// Library name to library info, including the names of the linked accounts
+    pub libraries: BTreeMap<String, Value>,
+    /// Authorization label to authorization
+    pub authorizations: BTreeMap<String, Value>,
+}
+
+/// Fields that are expected to differ between deployments of the same program
+const IGNORED_FIELDS: [&str; 3] = ["addr", "ty", "id"];
+
+impl NormalizedProgram {
+    pub(crate) fn new(config: &Value) -> Self {
+        let accounts = get_named_entries(config, "accounts");
+        let libraries = get_named_entries(config, "libraries");
+
+        // Map every id and address we know of to a reference by name
+        let mut refs: BTreeMap<String, String> = BTreeMap::new();
+        for (id, entry) in &accounts {
+            refs.insert(
+                format!("account_id:{}", id),
+                format!("@account:{}", entry.0),
+            );
+            if let Some(addr) = entry.1.get("addr").and_then(|a| a.as_str()) {
+                refs.insert(format!("addr:{}", addr), format!("@account:{}", entry.0));
+            }
+        }
+        for (id, entry) in &libraries {
+            refs.insert(
+                format!("library_id:{}", id),
+                format!("@library:{}", entry.0),
+            );
+            if let Some(addr) = entry.1.get("addr").and_then(|a| a.as_str()) {
+                refs.insert(format!("addr:{}", addr), format!("@library:{}", entry.0));
+            }
+        }
+
+        let resolve_ids = |ids: Option<&Value>, refs: &BTreeMap<String, String>| {
+            Value::Array(
+                ids.and_then(|ids| ids.as_array())
+                    .cloned()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|id| {
+                        let key = format!("account_id:{}", id);
+                        Value::String(refs.get(&key).cloned().unwrap_or(key))
+                    })
+                    .collect(),
+            )
+        };
+
+        // Links are stored by id, we attach them to the library they belong to
+        let mut links: BTreeMap<String, (Value, Value)> = BTreeMap::new();
+        if let Some(config_links) = config.get("links").and_then(|l| l.as_object()) {
+            for link in config_links.values() {
+                let library_id = link.get("library_id").cloned().unwrap_or_default();
+                links.insert(
+                    library_id.to_string(),
+                    (
+                        resolve_ids(link.get("input_accounts_id"), &refs),
+                        resolve_ids(link.get("output_accounts_id"), &refs),
+                    ),
+                );
+            }
+        }
+
+        let accounts = accounts
+            .into_values()
+            .map(|(name, account)| (name, strip_ignored(resolve_refs(account, &refs))))
+            .collect();
+
+        let libraries = libraries
+            .into_iter()
+            .map(|(id, (name, library))| {
+                let mut library = strip_ignored(resolve_refs(library, &refs));
+
+                if let (Some((inputs, outputs)), Value::Object(map)) =
+                    (links.remove(&id), &mut library)
+                {
+                    map.insert("input_accounts".to_string(), inputs);
+                    map.insert("output_accounts".to_string(), outputs);
+                }
+
+                (name, library)
+            })
+            .collect();
+
+        let authorizations = config
+            .get("authorizations")
+            .and_then(|a| a.as_array())
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|authorization| {
+                let label = authorization
+                    .get("label")
+                    .and_then(|l| l.as_str())
+                    .unwrap_or_default()
+                    .to_string();
+
+                (label, resolve_refs(authorization, &refs))
+            })
+            .collect();
+
+        NormalizedProgram {
+            name: config.get("name").cloned().unwrap_or_default(),
+            owner: config.get("owner").cloned().unwrap_or_default(),
+            accounts,
+            libraries,
+            authorizations,
+        }
+    }
+}
+
+/// Get the entries of an id keyed map (accounts or libraries) as id to (name, entry)
+fn get_named_entries(config: &Value, key: &str) -> BTreeMap<String, (String, Value)> {
+    config
+        .get(key)
+        .and_then(|e| e.as_object())
+        .map(|entries| {
+            entries
+                .iter()
+                .map(|(id, entry)| {
+                    let name = entry
+                        .get("name")
+                        .and_then(|n| n.as_str())
+                        .map(|n| n.to_string())
+                        .unwrap_or_else(|| format!("{}#{}", key, id));
+
+                    (id.clone(), (name, entry.clone()))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Replace `|account_id|`, `|library_id|` and `|library_account_addr|` references with names
+fn resolve_refs(value: Value, refs: &BTreeMap<String, String>) -> Value {
+    match value {
+        Value::Object(map) if map.len() == 1 => {
+            let (key, inner) = map.iter().next().unwrap();
+
+            let lookup = match key.as_str() {
+                "|account_id|" => Some(format!("account_id:{}", inner)),
+                "|library_id|" => Some(format!("library_id:{}", inner)),
+                "|library_account_addr|" => inner.as_str().map(|addr| format!("addr:{}", addr)),
+                _ => None,
+            };
+
+            match lookup {
+                // Addresses we don't know of are external, so we keep them as is
+                Some(lookup) => Value::String(
+                    refs.get(&lookup)
+                        .cloned()
+                        .unwrap_or_else(|| lookup.trim_start_matches("addr:").to_string()),
+                ),
+                None => Value::Object(
+                    map.into_iter()
+                        .map(|(k, v)| (k, resolve_refs(v, refs)))
+                        .collect(),
+                ),
+            }
+        }
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| (k, resolve_refs(v, refs)))
+                .collect(),
+        ),
+        Value::Array(values) => {
+            Value::Array(values.into_iter().map(|v| resolve_refs(v, refs)).collect())
+        }
+        v => v,
+    }
+}
+
+fn strip_ignored(mut value: Value) -> Value {
+    if let Value::Object(map) = &mut value {
+        for field in IGNORED_FIELDS {
+            map.remove(field);
+        }
+    }
+
+    value
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum Change {
+    Added {
+        path: String,
+        value: Value,
+    },
+    Removed {
+        path: String,
+        value: Value,
+    },
+    Changed {
+        path: String,
+        from: Value,
+        to: Value,
+    },
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Added { path, value } => write!(f, "+ {}: {}", path, value),
+            Change::Removed { path, value } => write!(f, "- {}: {}", path, value),
+            Change::Changed { path, from, to } => write!(f, "~ {}: {} -> {}", path, from, to),
+        }
+    }
+}
+
+/// Structural diff of 2 serialized program configs,
+/// accounts and libraries are aligned by name and authorizations by label
+pub fn diff_program_configs(left: &Value, right: &Value) -> Vec<Change> {
+    diff_normalized(
+        &NormalizedProgram::new(left),
+        &NormalizedProgram::new(right),
+    )
+}
+
+pub(crate) fn diff_normalized(left: &NormalizedProgram, right: &NormalizedProgram) -> Vec<Change> {
+    let mut changes = vec![];
+
+    diff_values("name", &left.name, &right.name, &mut changes);
+    diff_values("owner", &left.owner, &right.owner, &mut changes);
+    diff_entries("accounts", &left.accounts, &right.accounts, &mut changes);
+    diff_entries("libraries", &left.libraries, &right.libraries, &mut changes);
+    diff_entries(
+        "authorizations",
+        &left.authorizations,
+        &right.authorizations,
+        &mut changes,
+    );
+
+    changes
+}
+
+/// Diff of 2 json values as they are, without normalizing ids and addresses
+pub(crate) fn diff_json(left: &Value, right: &Value) -> Vec<Change> {
+    let mut changes = vec![];
+    diff_values("$", left, right, &mut changes);
+
+    changes
+}
+
+fn diff_entries(
+    prefix: &str,
+    left: &BTreeMap<String, Value>,
+    right: &BTreeMap<String, Value>,
+    changes: &mut Vec<Change>,
+) {
+    for (name, value) in left {
+        let path = format!("{}.{}", prefix, name);
+
+        match right.get(name) {
+            Some(other) => diff_values(&path, value, other, changes),
+            None => changes.push(Change::Removed {
+                path,
+                value: value.clone(),
+            }),
+        }
+    }
+
+    for (name, value) in right {
+        if !left.contains_key(name) {
+            changes.push(Change::Added {
+                path: format!("{}.{}", prefix, name),
+                value: value.clone(),
+            });
+        }
+    }
+}
+
+fn diff_values(path: &str, left: &Value, right: &Value, changes: &mut Vec<Change>) {
+    if left == right {
+        return;
+    }
+
+    match (left, right) {
+        (Value::Object(l), Value::Object(r)) => {
+            let keys = l.keys().chain(r.keys()).collect::<BTreeSet<_>>();
+
+            for key in keys {
+                let path = format!("{}.{}", path, key);
+
+                match (l.get(key), r.get(key)) {
+                    (Some(lv), Some(rv)) => diff_values(&path, lv, rv, changes),
+                    (Some(lv), None) => changes.push(Change::Removed {
+                        path,
+                        value: lv.clone(),
+                    }),
+                    (None, Some(rv)) => changes.push(Change::Added {
+                        path,
+                        value: rv.clone(),
+                    }),
+                    (None, None) => (),
+                }
+            }
+        }
+        // Operator lists of authorization modes are compared as sets
+        (Value::Array(l), Value::Array(r)) if path.contains(".mode.") => {
+            for value in l.iter().filter(|v| !r.contains(v)) {
+                changes.push(Change::Removed {
+                    path: path.to_string(),
+                    value: value.clone(),
+                });
+            }
+            for value in r.iter().filter(|v| !l.contains(v)) {
+                changes.push(Change::Added {
+                    path: path.to_string(),
+                    value: value.clone(),
+                });
+            }
+        }
+        (Value::Array(l), Value::Array(r)) => {
+            for i in 0..l.len().max(r.len()) {
+                let path = format!("{}[{}]", path, i);
+
+                match (l.get(i), r.get(i)) {
+                    (Some(lv), Some(rv)) => diff_values(&path, lv, rv, changes),
+                    (Some(lv), None) => changes.push(Change::Removed {
+                        path,
+                        value: lv.clone(),
+                    }),
+                    (None, Some(rv)) => changes.push(Change::Added {
+                        path,
+                        value: rv.clone(),
+                    }),
+                    (None, None) => (),
+                }
+            }
+        }
+        _ => changes.push(Change::Changed {
+            path: path.to_string(),
+            from: left.clone(),
+            to: right.clone(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::{diff_json, diff_program_configs, Change};
+
+    /// Config of a splitter from an account to an external address, ids and addresses are
+    /// those of a given deployment
+    fn program_config(account_id: u64, account_addr: &str, operators: Value) -> Value {
+        json!({
+            "name": "Program",
+            "owner": "neutron1owner",
+            "accounts": {
+                account_id.to_string(): { "name": "input", "ty": { "Base": {} }, "addr": account_addr },
+            },
+            "libraries": {
+                "1": {
+                    "name": "splitter",
+                    "addr": "neutron1splitter",
+                    "config": { "input_addr": { "|account_id|": account_id }, "amount": "10" },
+                },
+            },
+            "links": {
+                "1": { "input_accounts_id": [account_id], "output_accounts_id": [], "library_id": 1 },
+            },
+            "authorizations": [{
+                "label": "split",
+                "mode": { "permissioned": { "without_call_limit": operators } },
+                "contract": { "|library_account_addr|": account_addr },
+            }],
+        })
+    }
+
+    #[test]
+    fn ids_and_addresses_of_deployments_are_ignored() {
+        let left = program_config(1, "neutron1a", json!(["neutron1op1", "neutron1op2"]));
+        let right = program_config(7, "neutron1b", json!(["neutron1op2", "neutron1op1"]));
+
+        assert_eq!(diff_program_configs(&left, &right), vec![]);
+    }
+
+    #[test]
+    fn changes_are_classified() {
+        let left = program_config(1, "neutron1a", json!(["neutron1op1"]));
+        let mut right = program_config(1, "neutron1a", json!(["neutron1op2"]));
+        right["libraries"]["1"]["config"]["amount"] = json!("20");
+        right["libraries"]["2"] = json!({ "name": "forwarder", "config": {} });
+        right["owner"] = json!("neutron1other");
+
+        let changes = diff_program_configs(&left, &right);
+
+        assert_eq!(
+            changes,
+            vec![
+                Change::Changed {
+                    path: "owner".to_string(),
+                    from: json!("neutron1owner"),
+                    to: json!("neutron1other"),
+                },
+                Change::Changed {
+                    path: "libraries.splitter.config.amount".to_string(),
+                    from: json!("10"),
+                    to: json!("20"),
+                },
+                Change::Added {
+                    path: "libraries.forwarder".to_string(),
+                    value: json!({ "name": "forwarder", "config": {} }),
+                },
+                Change::Removed {
+                    path: "authorizations.split.mode.permissioned.without_call_limit".to_string(),
+                    value: json!("neutron1op1"),
+                },
+                Change::Added {
+                    path: "authorizations.split.mode.permissioned.without_call_limit".to_string(),
+                    value: json!("neutron1op2"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn json_is_diffed_as_is() {
+        let changes = diff_json(
+            &json!({ "a": [1, 2], "b": 1 }),
+            &json!({ "a": [1], "c": 1 }),
+        );
+
+        assert_eq!(
+            changes,
+            vec![
+                Change::Removed {
+                    path: "$.a[1]".to_string(),
+                    value: json!(2),
+                },
+                Change::Removed {
+                    path: "$.b".to_string(),
+                    value: json!(1),
+                },
+                Change::Added {
+                    path: "$.c".to_string(),
+                    value: json!(1),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use std::{collections::BTreeMap, error::Error, path::Path};
+
+use serde_json::Value;
+
+use crate::{
+    definition::{build_program, ProgramDefinition},
+    diff::{diff_normalized, Change, NormalizedProgram},
+};
+
+/// Keys whose values define the structure of the program and are kept as is
+const STRUCTURAL_KEYS: [&str; 2] = ["domain", "label"];
+
+/// Result of comparing the structure of a program across all its environments
+#[derive(Debug)]
+pub struct EquivalenceReport {
+    /// Environment all others are compared to
+    pub reference_env: String,
+    /// Structural differences of every other environment from the reference one
+    pub divergences: BTreeMap<String, Vec<Change>>,
+}
+
+impl EquivalenceReport {
+    pub fn is_equivalent(&self) -> bool {
+        self.divergences.values().all(|changes| changes.is_empty())
+    }
+}
+
+/// Build the program for every environment that has a params file, and compare the structure
+/// (accounts, links, authorization labels, message names and restrictions) of all of them,
+/// ignoring values that come from params like addresses, amounts and denoms
+pub(crate) fn check_env_equivalence(
+    program: &dyn ProgramDefinition,
+) -> Result<EquivalenceReport, Box<dyn Error>> {
+    let envs = program.envs();
+
+    // Mainnet is what matters, so we compare to it if we have it
+    let reference_env = envs
+        .iter()
+        .find(|env| env.as_str() == "mainnet")
+        .or(envs.first())
+        .cloned()
+        .ok_or("Program has no params files")?;
+
+    let mut structures = BTreeMap::new();
+    for env in &envs {
+        let program_config = serde_json::to_value(build_program(program, env)?)?;
+
+        structures.insert(env.clone(), get_structure(&program_config));
+    }
+
+    let reference = &structures[&reference_env];
+    let divergences = structures
+        .iter()
+        .filter(|(env, _)| **env != reference_env)
+        .map(|(env, structure)| (env.clone(), diff_normalized(reference, structure)))
+        .collect();
+
+    Ok(EquivalenceReport {
+        reference_env,
+        divergences,
+    })
+}
+
+/// Get the names of all environments the program has a params file for
+pub(crate) fn get_program_envs(program_path: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut envs = std::fs::read_dir(program_path.join("program_params"))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .filter_map(|path| {
+            path.file_stem()
+                .and_then(|stem| stem.to_str())
+                .map(|s| s.to_string())
+        })
+        .collect::<Vec<_>>();
+    envs.sort();
+
+    Ok(envs)
+}
+
+/// Normalize the program and replace all param driven values with placeholders of their type
+fn get_structure(program_config: &Value) -> NormalizedProgram {
+    let normalized = NormalizedProgram::new(program_config);
+
+    let skeletons = |entries: BTreeMap<String, Value>| {
+        entries
+            .into_iter()
+            .map(|(name, value)| (name, skeleton(value)))
+            .collect()
+    };
+
+    NormalizedProgram {
+        name: skeleton(normalized.name),
+        owner: skeleton(normalized.owner),
+        accounts: skeletons(normalized.accounts),
+        libraries: skeletons(normalized.libraries),
+        authorizations: skeletons(normalized.authorizations),
+    }
+}
+
+fn skeleton(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| match k.as_str() {
+                    "message_details" => (k, skeleton_message_details(v)),
+                    key if STRUCTURAL_KEYS.contains(&key) => (k, v),
+                    _ => (k, skeleton(v)),
+                })
+                .collect(),
+        ),
+        // Lists of plain values (Ex: operators) can have any length
+        Value::Array(values) if values.iter().all(|v| !v.is_object() && !is_ref(v)) => {
+            Value::String("<list>".to_string())
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(skeleton).collect()),
+        // References to program accounts and libraries are part of the structure
+        Value::String(s) if s.starts_with('@') => Value::String(s),
+        Value::String(_) => Value::String("<string>".to_string()),
+        Value::Number(_) => Value::String("<number>".to_string()),
+        Value::Bool(_) => Value::String("<bool>".to_string()),
+        // Null is kept as the builder might branch on a param to set it
+        Value::Null => Value::Null,
+    }
+}
+
+/// Keep the message names and restriction paths, the values of `must_be_value` restrictions
+/// are base64 encoded params (Ex: the expected pool ratio range)
+fn skeleton_message_details(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| match (k.as_str(), v) {
+                    ("must_be_value", Value::Array(mut restriction)) => {
+                        if let Some(value) = restriction.get_mut(1) {
+                            *value = Value::String("<value>".to_string());
+                        }
+                        (k, Value::Array(restriction))
+                    }
+                    (_, v) => (k, skeleton_message_details(v)),
+                })
+                .collect(),
+        ),
+        Value::Array(values) => {
+            Value::Array(values.into_iter().map(skeleton_message_details).collect())
+        }
+        v => v,
+    }
+}
+
+fn is_ref(value: &Value) -> bool {
+    value.as_str().is_some_and(|s| s.starts_with('@'))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::get_structure;
+    use crate::diff::diff_normalized;
+
+    /// Config of a forwarder with a pool ratio restriction, as built from the params of an env
+    fn program_config(owner: &str, amount: &str, denom: &str, ratio_range: &str) -> Value {
+        json!({
+            "name": "Program",
+            "owner": owner,
+            "accounts": {
+                "1": { "name": "input", "ty": { "Base": { "admin": owner } }, "domain": { "CosmosCosmwasm": "neutron" } },
+                "2": { "name": "output", "ty": { "Addr": "neutron1dao" }, "domain": { "CosmosCosmwasm": "neutron" } },
+            },
+            "libraries": {
+                "1": {
+                    "name": "forwarder",
+                    "domain": { "CosmosCosmwasm": "neutron" },
+                    "config": {
+                        "ValenceForwarderLibrary": {
+                            "input_addr": { "|account_id|": 1 },
+                            "output_addr": { "|account_id|": 2 },
+                            "forwarding_configs": [{ "denom": denom, "max_amount": amount }],
+                        }
+                    },
+                },
+            },
+            "links": {
+                "1": { "input_accounts_id": [1], "output_accounts_id": [2], "library_id": 1 },
+            },
+            "authorizations": [{
+                "label": "forward",
+                "mode": { "permissioned": { "without_call_limit": [owner] } },
+                "subroutine": { "atomic": { "functions": [{
+                    "domain": "main",
+                    "message_details": {
+                        "message_type": "cosmwasm_execute_msg",
+                        "message": {
+                            "name": "process_function",
+                            "params_restrictions": [{
+                                "must_be_value": [
+                                    ["process_function", "forward", "expected_pool_ratio_range"],
+                                    ratio_range,
+                                ]
+                            }],
+                        },
+                    },
+                    "contract_address": { "|library_account_addr|": "neutron1forwarder" },
+                }] } },
+            }],
+        })
+    }
+
+    #[test]
+    fn envs_differing_in_params_are_equivalent() {
+        // {"min":"0.98","max":"1.02"} and {"min":"0.5","max":"2"}
+        let mainnet = program_config(
+            "neutron1mainnetdao",
+            "1000000",
+            "untrn",
+            "eyJtaW4iOiIwLjk4IiwibWF4IjoiMS4wMiJ9",
+        );
+        let fork = program_config(
+            "neutron1forkowner",
+            "5",
+            "ibc/FORK",
+            "eyJtaW4iOiIwLjUiLCJtYXgiOiIyIn0=",
+        );
+
+        let changes = diff_normalized(&get_structure(&mainnet), &get_structure(&fork));
+        assert_eq!(changes, vec![]);
+    }
+
+    #[test]
+    fn envs_differing_in_messages_are_not_equivalent() {
+        let mainnet = program_config("neutron1dao", "1", "untrn", "e30=");
+        let mut fork = mainnet.clone();
+        fork["authorizations"][0]["subroutine"]["atomic"]["functions"][0]["message_details"]
+            ["message"]["params_restrictions"][0]["must_be_value"][0][1] = json!("withdraw");
+
+        let changes = diff_normalized(&get_structure(&mainnet), &get_structure(&fork));
+        assert_eq!(changes.len(), 1);
+    }
+}
@@ -0,0 +1,106 @@
+use std::{error::Error, process::ExitCode};
+
+use serde::Serialize;
+use valence_program_manager::error::ManagerError;
+
+/// Why the deployer failed, every kind exits with its own code so CI can tell them apart
+#[derive(Debug, thiserror::Error)]
+pub enum DeployerError {
+    /// Workspace, program or file that doesn't exist
+    #[error("{0}")]
+    Path(String),
+    /// Missing or invalid program params
+    #[error("{0}")]
+    Params(String),
+    #[error("{0}")]
+    ManagerConfig(String),
+    /// No wallet to deploy with
+    #[error("{0}")]
+    Signer(String),
+    /// Querying a chain failed
+    #[error("{0}")]
+    Fetch(String),
+    /// The program config or its deployment doesn't look right
+    #[error("{0}")]
+    Validation(String),
+    /// The program builder failed
+    #[error("Failed to build the program: {0}")]
+    Build(String),
+    /// The program manager failed to instantiate the program
+    #[error("Failed to instantiate the program: {0}")]
+    Instantiation(#[source] ManagerError),
+    /// Writing the deployment output failed
+    #[error("{0}")]
+    Output(String),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl DeployerError {
+    /// Keep the kind of a boxed `DeployerError`, or give any other error the kind of `into`
+    pub(crate) fn categorize(e: Box<dyn Error>, into: fn(String) -> DeployerError) -> Self {
+        match e.downcast::<DeployerError>() {
+            Ok(e) => *e,
+            Err(e) => into(e.to_string()),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DeployerError::Path(_) => "path",
+            DeployerError::Params(_) => "params",
+            DeployerError::ManagerConfig(_) => "manager_config",
+            DeployerError::Signer(_) => "signer",
+            DeployerError::Fetch(_) => "fetch",
+            DeployerError::Validation(_) => "validation",
+            DeployerError::Build(_) => "build",
+            DeployerError::Instantiation(_) => "instantiation",
+            DeployerError::Output(_) => "output",
+            DeployerError::Other(_) => "other",
+        }
+    }
+
+    /// 2 is left to clap for invalid arguments
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            DeployerError::Other(_) => 1,
+            DeployerError::Path(_) => 3,
+            DeployerError::Params(_) => 4,
+            DeployerError::ManagerConfig(_) => 5,
+            DeployerError::Signer(_) => 6,
+            DeployerError::Fetch(_) => 7,
+            DeployerError::Validation(_) => 8,
+            DeployerError::Build(_) => 9,
+            DeployerError::Instantiation(_) => 10,
+            DeployerError::Output(_) => 11,
+        }
+    }
+
+    pub fn report(&self) -> ErrorReport {
+        ErrorReport {
+            kind: self.kind(),
+            message: self.to_string(),
+            exit_code: self.exit_code(),
+        }
+    }
+}
+
+impl From<Box<dyn Error>> for DeployerError {
+    fn from(e: Box<dyn Error>) -> Self {
+        DeployerError::categorize(e, DeployerError::Other)
+    }
+}
+
+impl From<DeployerError> for ExitCode {
+    fn from(e: DeployerError) -> Self {
+        ExitCode::from(e.exit_code())
+    }
+}
+
+/// An error as printed with `--error-format json`
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorReport {
+    pub kind: &'static str,
+    pub message: String,
+    pub exit_code: u8,
+}
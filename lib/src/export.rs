@@ -0,0 +1,227 @@
+use std::{collections::BTreeMap, path::Path};
+
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{deployments::get_addresses, error::DeployerError, verify::get_domain_chain};
+
+/// Format of the exported addresses
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    /// `RECEIVE_NTRN_ACC=neutron1...` lines, for `.env` files
+    Env,
+    Toml,
+    /// A single json object
+    JsonFlat,
+    /// TypeScript constants
+    Ts,
+}
+
+/// Addresses of a deployed program without any valence type,
+/// keys are account/library names, `authorization` and `processor_<chain>`
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportedAddresses {
+    pub program_id: u64,
+    /// Labels of the authorizations, in the order of the program config
+    pub authorization_labels: Vec<String>,
+    #[serde(flatten)]
+    pub addresses: BTreeMap<String, String>,
+}
+
+impl ExportedAddresses {
+    /// Read the addresses from an instantiated program config, or the output directory that has it
+    pub fn read(path: &Path) -> Result<Self, DeployerError> {
+        let path = match path.is_dir() {
+            true => path.join("instantiated-program-config.json"),
+            false => path.to_path_buf(),
+        };
+
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            DeployerError::Path(format!(
+                "Only successful deployments can be exported, {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let program_config: Value = serde_json::from_str(&content).map_err(|e| {
+            DeployerError::Validation(format!("Invalid program config {}: {}", path.display(), e))
+        })?;
+
+        Ok(ExportedAddresses::new(&program_config))
+    }
+
+    pub fn new(program_config: &Value) -> Self {
+        let addresses = get_addresses(program_config)
+            .into_iter()
+            .map(|(name, addr)| match name.strip_prefix("processor:") {
+                Some(domain) => {
+                    let chain = get_domain_chain(&Value::String(domain.to_string()))
+                        .unwrap_or(domain.to_string());
+                    (format!("processor_{}", chain), addr)
+                }
+                None => (name, addr),
+            })
+            .collect();
+
+        let authorization_labels = program_config
+            .get("authorizations")
+            .and_then(|a| a.as_array())
+            .map(|authorizations| {
+                authorizations
+                    .iter()
+                    .filter_map(|a| a.get("label").and_then(|l| l.as_str()))
+                    .map(|label| label.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        ExportedAddresses {
+            program_id: program_config
+                .get("id")
+                .and_then(|id| id.as_u64())
+                .unwrap_or_default(),
+            authorization_labels,
+            addresses,
+        }
+    }
+
+    /// Render the addresses in the format, `prefix` is prepended to the names of env variables
+    pub fn render(&self, format: ExportFormat, prefix: &str) -> Result<String, DeployerError> {
+        let output_error = |e: &dyn std::error::Error| DeployerError::Output(e.to_string());
+
+        match format {
+            ExportFormat::Env => {
+                let mut lines = vec![
+                    format!("{}PROGRAM_ID={}", prefix, self.program_id),
+                    format!(
+                        "{}AUTHORIZATION_LABELS={}",
+                        prefix,
+                        to_env_value(&self.authorization_labels.join(","))
+                    ),
+                ];
+                lines.extend(self.addresses.iter().map(|(name, addr)| {
+                    format!("{}{}={}", prefix, to_env_name(name), to_env_value(addr))
+                }));
+
+                Ok(lines.join("\n") + "\n")
+            }
+            ExportFormat::Toml => toml::to_string(self).map_err(|e| output_error(&e)),
+            ExportFormat::JsonFlat => {
+                serde_json::to_string_pretty(self).map_err(|e| output_error(&e))
+            }
+            ExportFormat::Ts => {
+                let labels = serde_json::to_string_pretty(&self.authorization_labels)
+                    .map_err(|e| output_error(&e))?;
+                let addresses =
+                    serde_json::to_string_pretty(&self.addresses).map_err(|e| output_error(&e))?;
+
+                Ok(format!(
+                    "// Generated by `deployer export`, do not edit\n\
+                     export const PROGRAM_ID = {};\n\
+                     export const AUTHORIZATION_LABELS = {} as const;\n\
+                     export const ADDRESSES = {} as const;\n",
+                    self.program_id, labels, addresses,
+                ))
+            }
+        }
+    }
+}
+
+/// Upper snake case, Ex: `receive_ntrn_acc` to `RECEIVE_NTRN_ACC`
+fn to_env_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c.to_ascii_uppercase(),
+            false => '_',
+        })
+        .collect()
+}
+
+/// Values with anything but letters, digits and `_,.:/-` are double quoted
+fn to_env_value(value: &str) -> String {
+    match value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "_,.:/-".contains(c))
+    {
+        true => value.to_string(),
+        false => format!(
+            "\"{}\"",
+            value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('$', "\\$")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::{ExportFormat, ExportedAddresses};
+
+    fn addresses() -> ExportedAddresses {
+        ExportedAddresses::new(&json!({
+            "id": 2,
+            "accounts": {
+                "1": { "name": "receive_ntrn_acc", "addr": "neutron1receive" },
+            },
+            "libraries": {
+                "2": { "name": "lp.holder", "addr": "neutron1lp" },
+            },
+            "authorizations": [{ "label": "forward ntrn" }, { "label": "say \"hi\" $HOME" }],
+            "authorization_data": {
+                "authorization_addr": "neutron1authorization",
+                "processor_addrs": { "CosmosCosmwasm:neutron": "neutron1processor" },
+            },
+        }))
+    }
+
+    #[test]
+    fn env_names_and_values_are_escaped() {
+        let env = addresses().render(ExportFormat::Env, "NTRN_").unwrap();
+
+        assert_eq!(
+            env,
+            "NTRN_PROGRAM_ID=2\n\
+             NTRN_AUTHORIZATION_LABELS=\"forward ntrn,say \\\"hi\\\" \\$HOME\"\n\
+             NTRN_AUTHORIZATION=neutron1authorization\n\
+             NTRN_LP_HOLDER=neutron1lp\n\
+             NTRN_PROCESSOR_NEUTRON=neutron1processor\n\
+             NTRN_RECEIVE_NTRN_ACC=neutron1receive\n"
+        );
+    }
+
+    #[test]
+    fn toml_and_json_keep_the_names() {
+        let expected = json!({
+            "program_id": 2,
+            "authorization_labels": ["forward ntrn", "say \"hi\" $HOME"],
+            "authorization": "neutron1authorization",
+            "lp.holder": "neutron1lp",
+            "processor_neutron": "neutron1processor",
+            "receive_ntrn_acc": "neutron1receive",
+        });
+
+        let toml = addresses().render(ExportFormat::Toml, "").unwrap();
+        assert!(toml.contains("\"lp.holder\" = \"neutron1lp\""));
+        let toml: toml::Table = toml.parse().unwrap();
+        assert_eq!(serde_json::to_value(toml).unwrap(), expected);
+
+        let json = addresses().render(ExportFormat::JsonFlat, "").unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&json).unwrap(), expected);
+    }
+
+    #[test]
+    fn ts_strings_are_json_escaped() {
+        let ts = addresses().render(ExportFormat::Ts, "").unwrap();
+
+        assert!(ts.starts_with("// Generated by `deployer export`, do not edit\n"));
+        assert!(ts.contains("export const PROGRAM_ID = 2;\n"));
+        assert!(ts.contains(
+            "export const AUTHORIZATION_LABELS = [\n  \"forward ntrn\",\n  \"say \\\"hi\\\" $HOME\"\n] as const;\n"
+        ));
+        assert!(ts.contains("  \"lp.holder\": \"neutron1lp\",\n"));
+    }
+}
@@ -0,0 +1,460 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fmt::Display,
+    path::Path,
+};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    deployments::get_deployments,
+    manifest::Manifest,
+    verify::{get_address_chain, get_deployment_queriers, get_domain_chain, Queriers},
+};
+
+/// Gas per message when no prior deployment paid for the chain
+const DEFAULT_GAS_PER_MESSAGE: (u64, u64) = (250_000, 1_000_000);
+/// Added to the highest gas per message of prior deployments, gas varies with the size of the configs
+const GAS_MARGIN: f64 = 1.2;
+
+/// Messages the program manager sends on a chain to instantiate a program
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MessageCounts {
+    /// Accounts instantiated, existing accounts are not
+    pub accounts: u64,
+    pub libraries: u64,
+    /// Instantiating the authorization contract and adding the authorizations
+    pub authorization: u64,
+    pub processors: u64,
+    /// Reserving the program id and saving the program config
+    pub registry: u64,
+    /// Approving every library on its input accounts
+    pub approvals: u64,
+}
+
+impl MessageCounts {
+    pub fn total(&self) -> u64 {
+        self.accounts
+            + self.libraries
+            + self.authorization
+            + self.processors
+            + self.registry
+            + self.approvals
+    }
+}
+
+impl Display for MessageCounts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let counts = [
+            (self.accounts, "accounts"),
+            (self.libraries, "libraries"),
+            (self.authorization, "authorization"),
+            (self.processors, "processors"),
+            (self.registry, "registry"),
+            (self.approvals, "approvals"),
+        ];
+
+        write!(
+            f,
+            "{}",
+            counts
+                .iter()
+                .filter(|(count, _)| *count > 0)
+                .map(|(count, name)| format!("{} {}", count, name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+/// Fees the deployer pays on a chain to instantiate a program
+#[derive(Debug, Clone, Serialize)]
+pub struct FeeEstimate {
+    pub chain: String,
+    pub messages: MessageCounts,
+    pub min_gas: u64,
+    pub max_gas: u64,
+    pub denom: String,
+    /// Not set if the manager config has no gas price for the chain
+    pub min_fee: Option<u128>,
+    pub max_fee: Option<u128>,
+    /// Prior successful deployments on the chain the gas is estimated from, defaults are used if none
+    pub deployments: usize,
+}
+
+impl Display for FeeEstimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} messages ({}), {}-{} gas",
+            self.chain,
+            self.messages.total(),
+            self.messages,
+            self.min_gas,
+            self.max_gas
+        )?;
+
+        match (self.min_fee, self.max_fee) {
+            (Some(min_fee), Some(max_fee)) => write!(f, ", {}-{}{}", min_fee, max_fee, self.denom)?,
+            _ => write!(f, ", no gas price in the manager config")?,
+        }
+
+        match self.deployments {
+            0 => write!(f, ", no prior deployments"),
+            deployments => write!(f, ", from {} prior deployments", deployments),
+        }
+    }
+}
+
+/// Gas the deployer paid for a deployment on a chain, recorded in the manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasUsage {
+    pub messages: u64,
+    /// In the gas denom of the chain
+    pub fee: u128,
+    /// Fee divided by the gas price
+    pub gas: u64,
+}
+
+/// Gas per message paid by prior successful deployments, by chain
+pub(crate) type GasHistory = BTreeMap<String, Vec<u64>>;
+
+/// Read the gas paid from the manifests of the successful deployments of the workspace
+pub(crate) fn read_gas_history(workspace: &Path) -> GasHistory {
+    let mut history = GasHistory::new();
+
+    let deployments = match get_deployments(workspace) {
+        Ok(deployments) => deployments,
+        Err(e) => {
+            warn!("Failed to read the gas of prior deployments: {}", e);
+            return history;
+        }
+    };
+
+    for deployment in deployments.iter().filter(|d| d.status == "success") {
+        let Ok(manifest) = Manifest::read(&workspace.join(&deployment.output_dir)) else {
+            continue;
+        };
+
+        for (chain, usage) in manifest.gas_used {
+            if let Some(gas_per_message) = usage.gas.checked_div(usage.messages) {
+                history.entry(chain).or_default().push(gas_per_message);
+            }
+        }
+    }
+
+    history
+}
+
+/// Count the messages of instantiating a raw program config on every chain it uses
+pub(crate) fn count_messages(
+    program_config: &Value,
+    manager_config: &Value,
+) -> BTreeMap<String, MessageCounts> {
+    let mut counts = BTreeMap::<String, MessageCounts>::new();
+
+    // The authorization and the registry live on the chain of the registry,
+    // the authorization needs a processor there too
+    let main_chain = get_registry_chain(manager_config);
+    let main = counts.entry(main_chain.clone()).or_default();
+    main.registry = 2;
+    main.authorization = 1 + program_config
+        .get("authorizations")
+        .and_then(|a| a.as_array())
+        .is_some_and(|a| !a.is_empty()) as u64;
+
+    let entries = |key: &str| {
+        program_config
+            .get(key)
+            .and_then(|e| e.as_object())
+            .into_iter()
+            .flat_map(|e| e.values())
+    };
+
+    for account in entries("accounts") {
+        let instantiated = account["ty"]
+            .as_object()
+            .and_then(|ty| ty.keys().next())
+            .is_some_and(|ty| ty == "Base" || ty == "Storage");
+        if let (true, Some(chain)) = (instantiated, get_domain_chain(&account["domain"])) {
+            counts.entry(chain).or_default().accounts += 1;
+        }
+    }
+
+    let mut processor_chains = BTreeSet::from([main_chain]);
+    for library in entries("libraries") {
+        if let Some(chain) = get_domain_chain(&library["domain"]) {
+            counts.entry(chain.clone()).or_default().libraries += 1;
+            processor_chains.insert(chain);
+        }
+    }
+    for chain in processor_chains {
+        counts.entry(chain).or_default().processors += 1;
+    }
+
+    for link in entries("links") {
+        let library_chain = program_config
+            .pointer(&format!("/libraries/{}/domain", link["library_id"]))
+            .and_then(get_domain_chain);
+        if let Some(chain) = library_chain {
+            counts.entry(chain).or_default().approvals += link["input_accounts_id"]
+                .as_array()
+                .map_or(0, |ids| ids.len())
+                as u64;
+        }
+    }
+
+    counts
+}
+
+/// Estimate the fees of instantiating a raw program config on every chain it uses, the gas
+/// ranges from the lowest to the highest gas per message of prior deployments on the chain
+pub(crate) fn estimate_fees(
+    program_config: &Value,
+    manager_config: &Value,
+    history: &GasHistory,
+) -> Vec<FeeEstimate> {
+    count_messages(program_config, manager_config)
+        .into_iter()
+        .map(|(chain, messages)| {
+            let samples = history.get(&chain).cloned().unwrap_or_default();
+            let (min_per_message, max_per_message) =
+                match (samples.iter().min(), samples.iter().max()) {
+                    (Some(min), Some(max)) => (*min, (*max as f64 * GAS_MARGIN).ceil() as u64),
+                    _ => DEFAULT_GAS_PER_MESSAGE,
+                };
+            let min_gas = messages.total() * min_per_message;
+            let max_gas = messages.total() * max_per_message;
+
+            let gas_price = get_chain_field(manager_config, &chain, "gas_price")
+                .and_then(|price| price.parse::<f64>().ok());
+            let fee = |gas: u64| gas_price.map(|price| (gas as f64 * price).ceil() as u128);
+
+            FeeEstimate {
+                denom: get_chain_field(manager_config, &chain, "gas_denom").unwrap_or_default(),
+                chain,
+                messages,
+                min_gas,
+                max_gas,
+                min_fee: fee(min_gas),
+                max_fee: fee(max_gas),
+                deployments: samples.len(),
+            }
+        })
+        .collect()
+}
+
+/// Measures the fees of a deployment from the balances of the deployer before and after it
+pub(crate) struct FeeMeter {
+    queriers: Queriers,
+    manager_config: Value,
+    deployer_addresses: BTreeMap<String, String>,
+    messages: BTreeMap<String, MessageCounts>,
+    before: BTreeMap<String, u128>,
+}
+
+impl FeeMeter {
+    pub(crate) async fn start(
+        program_config: &Value,
+        manager_config: &Value,
+        deployer_addresses: &BTreeMap<String, String>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut meter = FeeMeter {
+            queriers: get_deployment_queriers(program_config, manager_config).await?,
+            manager_config: manager_config.clone(),
+            deployer_addresses: deployer_addresses.clone(),
+            messages: count_messages(program_config, manager_config),
+            before: BTreeMap::new(),
+        };
+        meter.before = meter.get_balances().await?;
+
+        Ok(meter)
+    }
+
+    /// Gas paid on every chain since the meter started
+    pub(crate) async fn stop(&self) -> Result<BTreeMap<String, GasUsage>, Box<dyn Error>> {
+        let after = self.get_balances().await?;
+        let mut gas_used = BTreeMap::new();
+
+        for (chain, before) in &self.before {
+            let (Some(after), Some(messages)) = (after.get(chain), self.messages.get(chain)) else {
+                continue;
+            };
+            let gas_price = get_chain_field(&self.manager_config, chain, "gas_price")
+                .and_then(|price| price.parse::<f64>().ok())
+                .filter(|price| *price > 0.0)
+                .ok_or_else(|| format!("No gas price for {} in manager config", chain))?;
+            let fee = before.saturating_sub(*after);
+
+            gas_used.insert(
+                chain.clone(),
+                GasUsage {
+                    messages: messages.total(),
+                    fee,
+                    gas: (fee as f64 / gas_price) as u64,
+                },
+            );
+        }
+
+        Ok(gas_used)
+    }
+
+    /// Balances of the deployer in the gas denom of every chain the program is deployed on
+    async fn get_balances(&self) -> Result<BTreeMap<String, u128>, Box<dyn Error>> {
+        let mut balances = BTreeMap::new();
+
+        for chain in self.messages.keys() {
+            let address = self
+                .deployer_addresses
+                .get(chain)
+                .ok_or_else(|| format!("No deployer address on {}", chain))?;
+            let denom = get_chain_field(&self.manager_config, chain, "gas_denom")
+                .ok_or_else(|| format!("No gas denom for {} in manager config", chain))?;
+            let querier = self
+                .queriers
+                .get(chain)
+                .ok_or_else(|| format!("No querier for chain {}", chain))?;
+
+            balances.insert(chain.clone(), querier.balance(address, &denom).await?);
+        }
+
+        Ok(balances)
+    }
+}
+
+/// Chain of the registry, where the authorization contract is instantiated
+pub(crate) fn get_registry_chain(manager_config: &Value) -> String {
+    manager_config
+        .pointer("/general/registry_addr")
+        .and_then(|r| r.as_str())
+        .and_then(|r| get_address_chain(r, manager_config))
+        .unwrap_or_else(|| "neutron".to_string())
+}
+
+/// A field of a chain of the manager config, numbers are read as strings
+fn get_chain_field(manager_config: &Value, chain: &str, name: &str) -> Option<String> {
+    match manager_config.pointer(&format!("/chains/{}/{}", chain, name)) {
+        Some(Value::String(s)) => Some(s.clone()),
+        Some(Value::Number(n)) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::{count_messages, estimate_fees, GasHistory};
+
+    fn program_config() -> Value {
+        let neutron = json!({"CosmosCosmwasm": "neutron"});
+        let juno = json!({"CosmosCosmwasm": "juno"});
+
+        json!({
+            "accounts": {
+                "1": {"name": "input_account", "ty": {"Base": {"admin": null}}, "domain": neutron},
+                "2": {"name": "storage_account", "ty": {"Storage": {"admin": null}}, "domain": neutron},
+                "3": {"name": "existing_account", "ty": {"Addr": "neutron1existing"}, "domain": neutron},
+                "4": {"name": "juno_account", "ty": {"Base": {"admin": null}}, "domain": juno},
+            },
+            "libraries": {
+                "1": {"name": "forwarder", "domain": neutron},
+                "2": {"name": "juno_forwarder", "domain": juno},
+            },
+            "links": {
+                "1": {"input_accounts_id": [1, 2], "output_account_id": 3, "library_id": 1},
+                "2": {"input_accounts_id": [4], "output_account_id": 1, "library_id": 2},
+            },
+            "authorizations": [{"label": "forward"}],
+        })
+    }
+
+    fn manager_config(registry_addr: &str) -> Value {
+        json!({
+            "general": {"registry_addr": registry_addr},
+            "chains": {
+                "neutron": {"prefix": "neutron", "gas_price": "0.0053", "gas_denom": "untrn"},
+                "juno": {"prefix": "juno", "gas_denom": "ujuno"},
+            },
+        })
+    }
+
+    #[test]
+    fn messages_are_counted_by_chain() {
+        let counts = count_messages(&program_config(), &manager_config("neutron1registry"));
+
+        let neutron = &counts["neutron"];
+        // The existing account isn't instantiated
+        assert_eq!(neutron.accounts, 2);
+        assert_eq!(neutron.libraries, 1);
+        assert_eq!(neutron.authorization, 2);
+        assert_eq!(neutron.processors, 1);
+        assert_eq!(neutron.registry, 2);
+        assert_eq!(neutron.approvals, 2);
+        assert_eq!(neutron.total(), 10);
+
+        let juno = &counts["juno"];
+        assert_eq!((juno.registry, juno.authorization), (0, 0));
+        assert_eq!(juno.total(), 4);
+    }
+
+    #[test]
+    fn authorization_and_registry_are_on_the_registry_chain() {
+        let counts = count_messages(&program_config(), &manager_config("juno1registry"));
+
+        assert_eq!(counts["neutron"].total(), 6);
+        assert_eq!(
+            (counts["juno"].registry, counts["juno"].authorization),
+            (2, 2)
+        );
+        assert_eq!(counts["juno"].total(), 8);
+    }
+
+    #[test]
+    fn defaults_are_used_without_prior_deployments() {
+        let fees = estimate_fees(
+            &program_config(),
+            &manager_config("neutron1registry"),
+            &GasHistory::new(),
+        );
+
+        // Sorted by chain
+        let (juno, neutron) = (&fees[0], &fees[1]);
+        assert_eq!((neutron.min_gas, neutron.max_gas), (2_500_000, 10_000_000));
+        assert_eq!(
+            (neutron.min_fee, neutron.max_fee),
+            (Some(13_250), Some(53_000))
+        );
+        assert_eq!(neutron.denom, "untrn");
+        assert_eq!(neutron.deployments, 0);
+
+        assert_eq!((juno.min_gas, juno.max_gas), (1_000_000, 4_000_000));
+        // No gas price for juno
+        assert_eq!((juno.min_fee, juno.max_fee), (None, None));
+    }
+
+    #[test]
+    fn prior_deployments_bound_the_gas() {
+        let history = GasHistory::from([("neutron".to_string(), vec![600_000, 400_000])]);
+        let fees = estimate_fees(
+            &program_config(),
+            &manager_config("neutron1registry"),
+            &history,
+        );
+
+        let neutron = &fees[1];
+        // Lowest gas per message, and the highest with a margin
+        assert_eq!((neutron.min_gas, neutron.max_gas), (4_000_000, 7_200_000));
+        assert_eq!(
+            (neutron.min_fee, neutron.max_fee),
+            (Some(21_200), Some(38_160))
+        );
+        assert_eq!(neutron.deployments, 2);
+
+        // Other chains keep the defaults
+        assert_eq!((fees[0].min_gas, fees[0].deployments), (1_000_000, 0));
+    }
+}
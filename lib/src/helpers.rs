@@ -1,26 +1,120 @@
-use std::{error::Error, path::PathBuf};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::{error::Error, path::Path};
 use valence_library_utils::LibraryAccountType;
 
+use crate::error::DeployerError;
+
 /// empty vec that implements GetId trait for links
 pub const EMPTY_VEC: Vec<&LibraryAccountType> = vec![];
 
 // Verify the program path exists and everything was called from the right place
-pub(crate) fn verify_path(path: PathBuf) -> Result<(), Box<dyn Error>> {
-    let curr_dir = std::env::current_dir()?;
-
+pub(crate) fn verify_path(workspace: &Path, program_path: &Path) -> Result<(), DeployerError> {
     // Verify we have a "programs" directory
-    let programs_dir = curr_dir.join("programs");
+    let programs_dir = workspace.join("programs");
 
     if !programs_dir.exists() {
-        return Err("Programs path doesn't exists, make sure you ran the script from the workplace directory".into());
+        return Err(DeployerError::Path("Programs path doesn't exists, make sure you ran the script from the workplace directory".to_string()));
     }
 
     // Verify program directory exists
-    let program_path = curr_dir.join(path);
+    let program_path = workspace.join(program_path);
 
     if !program_path.exists() {
-        return Err("Program does not exist".into());
+        return Err(DeployerError::Path(format!(
+            "Program does not exist: {}",
+            program_path.display()
+        )));
     }
 
     Ok(())
 }
+
+/// Sort all object keys recursively so the serialized json doesn't depend on insertion order
+pub(crate) fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries = map.into_iter().collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k, canonicalize(v)))
+                    .collect(),
+            )
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(canonicalize).collect()),
+        v => v,
+    }
+}
+
+/// sha256 hex digest of the canonical json of the value
+pub(crate) fn canonical_hash<T: Serialize>(value: &T) -> Result<String, Box<dyn Error>> {
+    let canonical = canonicalize(serde_json::to_value(value)?);
+    let digest = Sha256::digest(serde_json::to_string(&canonical)?.as_bytes());
+
+    Ok(hex::encode(digest))
+}
+
+/// Derive the address of the first account of a mnemonic for the given chain prefix and coin type
+pub(crate) fn derive_address(
+    mnemonic: &str,
+    prefix: &str,
+    coin_type: u64,
+) -> Result<String, Box<dyn Error>> {
+    let account_id = derive_account_key(mnemonic, coin_type)?
+        .public_key()
+        .account_id(prefix)
+        .map_err(|e| e.to_string())?;
+
+    Ok(account_id.to_string())
+}
+
+/// Derive the key of the first account of a mnemonic for the given coin type, to sign transactions with
+pub(crate) fn derive_account_key(
+    mnemonic: &str,
+    coin_type: u64,
+) -> Result<cosmrs::crypto::secp256k1::SigningKey, Box<dyn Error>> {
+    let seed = cosmrs::bip32::Mnemonic::new(mnemonic.trim(), cosmrs::bip32::Language::English)?
+        .to_seed("");
+    let path: cosmrs::bip32::DerivationPath = format!("m/44'/{}'/0'/0/0", coin_type).parse()?;
+
+    Ok(
+        cosmrs::crypto::secp256k1::SigningKey::derive_from_path(seed, &path)
+            .map_err(|e| e.to_string())?,
+    )
+}
+
+/// Derive the secp256k1 key of the first cosmos account of a mnemonic,
+/// used to sign approvals and audit log entries
+pub(crate) fn derive_wallet_key(mnemonic: &str) -> Result<k256::ecdsa::SigningKey, Box<dyn Error>> {
+    let seed = cosmrs::bip32::Mnemonic::new(mnemonic.trim(), cosmrs::bip32::Language::English)?
+        .to_seed("");
+    let path: cosmrs::bip32::DerivationPath = "m/44'/118'/0'/0/0".parse()?;
+
+    Ok(cosmrs::bip32::XPrv::derive_from_path(seed, &path)?
+        .private_key()
+        .clone())
+}
+
+/// hex of the compressed public key
+pub(crate) fn get_public_key(key: &k256::ecdsa::SigningKey) -> String {
+    hex::encode(key.verifying_key().to_encoded_point(true).as_bytes())
+}
+
+/// Whether the hex of a compressed public key is the key of the address, on the chain of its prefix
+pub(crate) fn is_key_of_address(public_key: &str, address: &str) -> bool {
+    let Some(public_key) = hex::decode(public_key)
+        .ok()
+        .and_then(|bytes| cosmrs::crypto::PublicKey::from_raw_secp256k1(&bytes))
+    else {
+        return false;
+    };
+
+    address
+        .rsplit_once('1')
+        .and_then(|(prefix, _)| public_key.account_id(prefix).ok())
+        .is_some_and(|account_id| account_id.to_string() == address)
+}
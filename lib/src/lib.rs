@@ -1,169 +1,1009 @@
+mod amount_checks;
+mod artifacts;
+mod audit;
+mod definition;
+mod denoms;
+mod deployer;
+mod deployments;
+mod diff;
+mod equivalence;
+mod error;
+mod export;
+mod fees;
 mod helpers;
 mod manager_config;
+mod manifest;
+mod operator;
+mod preflight;
+mod production;
 mod program_config;
 mod program_params;
+mod querier;
+mod scenarios;
+mod schedule;
+mod signer;
+mod snapshots;
+mod status;
+mod summary;
+mod tx;
+mod verify;
+mod workspace_config;
 
-use std::{error::Error, fmt::Display, io::Write, path::PathBuf};
+use std::{
+    io::{IsTerminal, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
 
-use chrono::Utc;
-use clap::{command, Parser};
-use dotenvy::dotenv;
+use amount_checks::{check_amounts, get_amount_checks};
+use audit::{run_audit_command, AuditCommand};
+use chrono::TimeDelta;
+use clap::{command, Parser, Subcommand, ValueEnum};
+use deployments::{
+    get_latest_success_output, get_package_name, run_deployments_command, DeploymentsCommand,
+};
+use equivalence::check_env_equivalence;
 use helpers::verify_path;
 use log::info;
-use manager_config::set_manager_config;
-use program_config::read_program_config_from_json;
-use program_params::get_program_params;
-use valence_program_manager::program_config::ProgramConfig;
+use manager_config::get_manager_config_snapshot;
+use operator::{
+    get_template_params, ChainClient, Operator, OperatorClient, SimulationClient, OPERATOR_DIR,
+};
+use program_config::read_program_config_source;
+use scenarios::{get_scenario_simulation, run_scenarios};
+use schedule::{get_schedule, parse_duration, InputAmount};
+use serde::Serialize;
+use snapshots::check_snapshots;
+use zeroize::Zeroizing;
 
+pub use amount_checks::AmountIssue;
+pub use audit::{read_audit_log, verify_audit_log, AuditConfig, AuditEntry, AuditIssue};
+pub use definition::{
+    build_program, get_valid_params, ParamKind, ParamSpec, ProgramDefinition, ProgramInfo,
+    ProgramIo,
+};
+pub use denoms::{DenomInfo, DenomRegistry};
+pub use deployer::{Deployer, DeploymentPlan, DeploymentResult, OutputSink};
+pub use deployments::{get_deployments, get_latest_deployment, Deployment, DeploymentFilter};
+pub use diff::{diff_program_configs, Change};
+pub use equivalence::EquivalenceReport;
+pub use error::{DeployerError, ErrorReport};
+pub use export::{ExportFormat, ExportedAddresses};
+pub use fees::{FeeEstimate, GasUsage, MessageCounts};
 // Re-export params to programs
 pub use helpers::EMPTY_VEC;
-pub use program_params::ProgramParams;
+pub use manager_config::ManagerConfigSource;
+pub use manifest::{GitInfo, Manifest, ParamsSnapshot};
+pub use operator::{
+    OperatorSchedule, OperatorState, OperatorTask, Precondition, TaskOutcome, TaskRun, TaskState,
+};
+pub use preflight::{preflight, DeployerBalance, PreflightIssue, PreflightReport};
+pub use production::{Approval, DeploymentSummary, ProductionConfig, Review, Reviewer, SavedPlan};
+pub use program_params::{get_program_params, ProgramParams};
+pub use querier::{ChainQuerier, CodeInfo, ContractInfo, GrpcQuerier, MockQuerier};
+pub use scenarios::{assert_scenarios, ScenarioReport};
+pub use schedule::{BatchInterval, ForwarderSchedule};
+pub use signer::{Keystore, Signer, KEYSTORE_PASSPHRASE_VAR};
+pub use snapshots::{assert_snapshots, SnapshotResult, SnapshotStatus};
+pub use status::{
+    AccountStatus, AuthorizationStatus, Execution, ProcessorStatus, ProgramStatus, QueuedBatch,
+};
+pub use summary::{EnvSummaryConfig, ProgramSummary, SummaryConfig, SummaryDomain};
+pub use verify::{get_grpc_queriers, verify_deployment, Mismatch, Queriers};
+pub use workspace_config::WorkspaceConfig;
 
-#[derive(Debug, PartialEq)]
-enum Status {
-    Process,
-    Success,
-    Fail,
+/// Env of the commands when `--env` isn't given
+const DEFAULT_ENV: &str = "mainnet";
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Build, check and deploy the programs of the workspace", long_about = None)]
+struct Args {
+    #[command(subcommand)]
+    command: DeployerCommand,
+    /// How errors are printed to stderr, the exit code tells the kind of error either way
+    #[arg(long, global = true, value_enum, default_value_t = ErrorFormat::Text)]
+    error_format: ErrorFormat,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ErrorFormat {
+    Text,
+    /// `{"kind": .., "message": .., "exit_code": ..}`, for CI
+    Json,
+}
+
+#[derive(Subcommand, Debug)]
+enum DeployerCommand {
+    /// List the programs registered in the deployer
+    List {
+        /// Print the programs as json
+        #[arg(long)]
+        json: bool,
+    },
+    /// Query the deployments of all programs in the workspace
+    Deployments {
+        #[command(subcommand)]
+        command: DeploymentsCommand,
+    },
+    /// Inspect and verify the audit log of deployments in `audit/deployments.jsonl`
+    Audit {
+        #[command(subcommand)]
+        command: AuditCommand,
+    },
+    /// Sign a plan saved with `plan --out` as a reviewer, the approval is written next to the plan
+    Approve {
+        /// Path of the saved plan
+        plan: PathBuf,
+        /// Name of the reviewer, as listed in `deployer.toml`
+        #[arg(short, long)]
+        reviewer: String,
+        /// Where the mnemonic of the reviewer comes from: env, keystore:<path>,
+        /// command:<command> or socket:<path>
+        #[arg(short, long, default_value = "env")]
+        signer: Signer,
+    },
+    /// Export the addresses of a successful deployment as a flat name to address map
+    /// other repos can consume without the valence types
+    Export {
+        /// Output directory of the deployment or an instantiated program config json
+        path: PathBuf,
+        #[arg(short, long, value_enum, default_value = "json-flat")]
+        format: ExportFormat,
+        /// Prepended to the variable names of the env format, Ex: `NTRN_`
+        #[arg(long, default_value = "")]
+        prefix: String,
+        /// File to write the addresses to instead of stdout
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+    /// Encrypt a mnemonic into a keystore file to deploy with `--signer keystore:<path>`,
+    /// the mnemonic is prompted for or read from stdin, the passphrase is prompted for
+    /// or read from `KEYSTORE_PASSPHRASE`
+    NewKeystore {
+        /// Path of the keystore file to create
+        path: PathBuf,
+    },
+    #[command(flatten)]
+    Program(Command),
+}
+
+/// Program and environment a command runs on
+#[derive(clap::Args, Debug)]
+struct Target {
+    /// Program directory or package name, see `list`
+    program: String,
+    /// Enviroment config to use Ex: mainnet, testnet, local. Defaults to mainnet,
+    /// but production envs must be given explicitly to deploy
+    #[arg(short, long, alias = "target-env")]
+    env: Option<String>,
+}
+
+impl Target {
+    fn env(&self) -> &str {
+        self.env.as_deref().unwrap_or(DEFAULT_ENV)
+    }
+
+    /// Deployer on the env, only set if given so production envs are never deployed on by default
+    fn deployer(&self, workspace: PathBuf) -> Deployer {
+        match &self.env {
+            Some(env) => Deployer::new(workspace).with_env(env),
+            None => Deployer::new(workspace),
+        }
+    }
 }
 
-impl Display for Status {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print what the program declares about itself: description, envs, params, inputs and outputs
+    Info {
+        /// Program directory or package name, see `list`
+        program: String,
+        /// Print the info as json
+        #[arg(long)]
+        json: bool,
+    },
+    /// Build the program config for the environment and print it as json
+    Build {
+        #[command(flatten)]
+        target: Target,
+    },
+    /// Build the program and print what deploying it would do, without touching any chain
+    Plan {
+        #[command(flatten)]
+        target: Target,
+        /// Path to a program config json file to deploy instead of building the program
+        #[arg(short, long)]
+        program_config_path: Option<PathBuf>,
+        /// Save the plan to this file, to review and approve it before deploying on a production env
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+    /// Deploy the program on the environment
+    Deploy {
+        #[command(flatten)]
+        target: Target,
+        /// Path to a program config json file to deploy instead of building the program
+        #[arg(short, long)]
+        program_config_path: Option<PathBuf>,
+        /// Where the mnemonic to deploy with comes from: env, keystore:<path>,
+        /// command:<command> or socket:<path>
+        #[arg(short, long, default_value = "env")]
+        signer: Signer,
+        /// Plan saved with `plan --out`, required on production envs
+        #[arg(long)]
+        plan: Option<PathBuf>,
+        /// Approval of the plan, can be repeated. Defaults to the `<plan>.<reviewer>.approval.json`
+        /// files next to the plan
+        #[arg(long)]
+        approval: Vec<PathBuf>,
+        /// Confirm the deployment on a production env without being prompted
+        #[arg(long)]
+        yes_i_mean_mainnet: bool,
+        /// Sign the checksums of the output files, and so the manifest, with the deployer key
+        #[arg(long)]
+        sign_output: bool,
+        /// Deploy without checking the deployer balance, code ids, registry and external contracts first
+        #[arg(long)]
+        skip_preflight: bool,
+    },
+    /// Check the deployer balance against the estimated fees, that every code id and the registry
+    /// exist and that the external contracts of the libraries answer as expected, without deploying
+    Preflight {
+        #[command(flatten)]
+        target: Target,
+        /// Path to a program config json file to check instead of building the program
+        #[arg(short, long)]
+        program_config_path: Option<PathBuf>,
+        /// Where the mnemonic of the deployer comes from: env, keystore:<path>,
+        /// command:<command> or socket:<path>
+        #[arg(short, long, default_value = "env")]
+        signer: Signer,
+        /// Print the report as json
+        #[arg(long)]
+        json: bool,
+    },
+    /// Structural diff between 2 program configs, ignoring addresses and ids.
+    /// A program config is either a path to a program config json, an output directory
+    /// or `builder:<env>` to build it from the program builder
+    Diff {
+        #[command(flatten)]
+        target: Target,
+        left: String,
+        right: String,
+        /// Print the changes as json
+        #[arg(long)]
+        json: bool,
+    },
+    /// Build the program for every environment that has a params file and check they all
+    /// have the same structure, only values from params (addresses, amounts, denoms) may differ
+    CheckEnvs {
+        /// Program directory or package name, see `list`
+        program: String,
+    },
+    /// Build the program for every environment and compare it to the golden snapshots in `snapshots/`,
+    /// and to the raw program config of the last successful deployment on the environment
+    Snapshots {
+        /// Program directory or package name, see `list`
+        program: String,
+        /// Write the snapshots that are missing or differ with the built program config
+        #[arg(long)]
+        accept: bool,
+    },
+    /// Check splitter ratios and amounts, forwarder max amounts and decimal ranges of the program
+    /// built for the environment, against the `[checks]` declared in its params file
+    CheckAmounts {
+        #[command(flatten)]
+        target: Target,
+        /// Print the issues as json
+        #[arg(long)]
+        json: bool,
+    },
+    /// Run the operator scenarios in `scenarios/` against an offline simulation of the program
+    Scenarios {
+        /// Program directory or package name, see `list`
+        program: String,
+        /// Only run the scenario with this file name, without extension
+        name: Option<String>,
+    },
+    /// Execute the authorizations of a deployed program on the cadence of its operator schedule,
+    /// as one of their operators, and tick the processor
+    Operator {
+        #[command(flatten)]
+        target: Target,
+        /// Output directory of the deployment, defaults to the latest successful one on the env
+        #[arg(long)]
+        output_dir: Option<PathBuf>,
+        /// Schedule of the tasks, defaults to `operator/<env>.toml` of the program
+        #[arg(long)]
+        schedule: Option<PathBuf>,
+        /// Where the state of the tasks is kept between runs, defaults to `<schedule>.state.json`.
+        /// Only kept in memory when simulating if not given
+        #[arg(long)]
+        state: Option<PathBuf>,
+        /// Where the mnemonic of the operator comes from: env, keystore:<path>,
+        /// command:<command> or socket:<path>
+        #[arg(short, long, default_value = "env")]
+        signer: Signer,
+        /// Print the messages of the due tasks without sending them
+        #[arg(long)]
+        dry_run: bool,
+        /// Run the due tasks once and exit
+        #[arg(long)]
+        once: bool,
+        /// Run against a simulation set up by this scenario of `scenarios/` instead of the chain,
+        /// on the env of the scenario. Time moves forward by the poll interval instead of waiting
+        #[arg(long)]
+        simulate: Option<String>,
+        /// How long to simulate, Ex: 4w, 3mo (30 days)
+        #[arg(long, value_parser = parse_duration, default_value = "4w")]
+        duration: u64,
+    },
+    /// Query the balances of every account of a deployed program, the processor queues and the
+    /// executions of the authorizations, and flag accounts holding funds that nothing is moving
+    Status {
+        #[command(flatten)]
+        target: Target,
+        /// Output directory of the deployment, defaults to the latest successful one on the env
+        #[arg(long)]
+        output_dir: Option<PathBuf>,
+        /// Print the status as json
+        #[arg(long, conflicts_with = "prometheus")]
+        json: bool,
+        /// Print the status as Prometheus metrics, never fails on idle accounts
+        #[arg(long)]
+        prometheus: bool,
+        /// File to write the status to instead of stdout
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+    /// Project how many batches and how long every forwarder takes to drain its input account
+    Schedule {
+        #[command(flatten)]
+        target: Target,
+        /// Expected amount in an input account, `<account>[:<denom>]=<amount>`, can be repeated
+        #[arg(short, long)]
+        input: Vec<InputAmount>,
+        /// Intended duration to drain the inputs, Ex: 90d, 12w, 3mo (30 days)
+        #[arg(short, long, value_parser = parse_duration)]
+        duration: Option<u64>,
+        /// Print the schedule as json
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+impl Command {
+    fn program(&self) -> &str {
         match self {
-            Status::Process => write!(f, "process"),
-            Status::Success => write!(f, "success"),
-            Status::Fail => write!(f, "fail"),
+            Command::Info { program, .. }
+            | Command::CheckEnvs { program }
+            | Command::Snapshots { program, .. }
+            | Command::Scenarios { program, .. } => program,
+            Command::Build { target }
+            | Command::Plan { target, .. }
+            | Command::Deploy { target, .. }
+            | Command::Preflight { target, .. }
+            | Command::Diff { target, .. }
+            | Command::CheckAmounts { target, .. }
+            | Command::Operator { target, .. }
+            | Command::Status { target, .. }
+            | Command::Schedule { target, .. } => &target.program,
         }
     }
 }
 
-#[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
-struct Args {
-    /// Enviroment config to use Ex: mainnet, testnet, local
-    #[arg(short, long, default_value = "mainnet")]
-    target_env: String,
-    /// Absolute path to the program config json file
-    #[arg(short, long)]
-    program_config_path: Option<String>,
-}
-
-pub async fn main<F>(program_path: &str, builder: F) -> Result<(), Box<dyn Error>>
-where
-    F: Fn(ProgramParams) -> ProgramConfig,
-{
+/// Entry point of the `deployer` binary, runs the command given on the command line
+/// with the programs registered in the binary and prints the error if it fails
+pub async fn deployer_main(programs: &[&dyn ProgramDefinition]) -> ExitCode {
     // Enable logs
     env_logger::init();
 
-    println!("Starting program deployment...");
+    let args = Args::parse();
+    let error_format = args.error_format;
 
-    // Load .env file environment variables
-    info!("Loading environment variables from .env file");
-    dotenv().expect(".env file not found");
+    match run_command(programs, args.command).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            match error_format {
+                ErrorFormat::Text => eprintln!("Error: {e}"),
+                ErrorFormat::Json => eprintln!(
+                    "{}",
+                    serde_json::to_string(&e.report()).unwrap_or_else(|_| e.to_string())
+                ),
+            }
 
-    let args = Args::parse();
-    let timestamp = Utc::now().format("%Y-%m-%d_%H:%M:%S").to_string();
+            e.into()
+        }
+    }
+}
+
+async fn run_command(
+    programs: &[&dyn ProgramDefinition],
+    command: DeployerCommand,
+) -> Result<(), DeployerError> {
+    match command {
+        DeployerCommand::List { json } => {
+            let infos = programs
+                .iter()
+                .map(|program| ProgramInfo::new(*program))
+                .collect::<Vec<_>>();
+
+            if json {
+                print_json(&infos)?;
+            } else {
+                for info in infos {
+                    match info.package {
+                        Some(package) => println!("{} ({})", info.name, package),
+                        None => println!("{}", info.name),
+                    }
+                    if !info.description.is_empty() {
+                        println!("    {}", info.description);
+                    }
+                }
+            }
+
+            Ok(())
+        }
+        DeployerCommand::Deployments { command } => {
+            let workspace =
+                std::env::current_dir().map_err(|e| DeployerError::Path(e.to_string()))?;
+
+            run_deployments_command(&workspace, command).await
+        }
+        DeployerCommand::Audit { command } => {
+            let workspace =
+                std::env::current_dir().map_err(|e| DeployerError::Path(e.to_string()))?;
+
+            run_audit_command(&workspace, command)
+        }
+        DeployerCommand::Approve {
+            plan,
+            reviewer,
+            signer,
+        } => {
+            let workspace =
+                std::env::current_dir().map_err(|e| DeployerError::Path(e.to_string()))?;
+            let saved_plan = SavedPlan::read(&plan)?;
+            let mnemonic = signer.get_mnemonic(&workspace)?;
+            let approval = Approval::sign(&saved_plan, &reviewer, &mnemonic)?;
+
+            let path = get_approval_path(&plan, &reviewer);
+            approval.write(&path)?;
+            println!(
+                "Approved {} on {} with program config {}",
+                saved_plan.program, saved_plan.env, saved_plan.config_hash
+            );
+            println!("Public key: {}", approval.public_key);
+            println!("Approval written to {}", path.display());
+
+            Ok(())
+        }
+        DeployerCommand::Export {
+            path,
+            format,
+            prefix,
+            out,
+        } => {
+            let exported = ExportedAddresses::read(&path)?.render(format, &prefix)?;
+
+            match out {
+                Some(out) => std::fs::write(&out, exported)
+                    .map_err(|e| DeployerError::Output(format!("{}: {}", out.display(), e)))?,
+                None => print!("{exported}"),
+            }
+
+            Ok(())
+        }
+        DeployerCommand::NewKeystore { path } => {
+            if path.exists() {
+                return Err(DeployerError::Path(format!(
+                    "{} already exists",
+                    path.display()
+                )));
+            }
+
+            // Piped in when not run from a terminal
+            let mnemonic = Zeroizing::new(
+                match std::io::stdin().is_terminal() {
+                    true => rpassword::prompt_password("Mnemonic: "),
+                    false => std::io::read_to_string(std::io::stdin()),
+                }
+                .map_err(|e| {
+                    DeployerError::Signer(format!("Failed to read the mnemonic: {}", e))
+                })?,
+            );
+            let passphrase = signer::get_passphrase("Passphrase: ")?;
+            if std::env::var(KEYSTORE_PASSPHRASE_VAR).is_err()
+                && *signer::get_passphrase("Repeat passphrase: ")? != *passphrase
+            {
+                return Err(DeployerError::Signer("Passphrases don't match".to_string()));
+            }
+
+            Keystore::encrypt(&mnemonic, &passphrase)?.write(&path)?;
+            println!("Keystore written to {}", path.display());
+
+            Ok(())
+        }
+        DeployerCommand::Program(command) => {
+            let program = find_program(programs, command.program())?;
+
+            run_program_command(program, command).await
+        }
+    }
+}
+
+/// Find a program by its directory or package name
+fn find_program<'a>(
+    programs: &[&'a dyn ProgramDefinition],
+    name: &str,
+) -> Result<&'a dyn ProgramDefinition, DeployerError> {
+    programs
+        .iter()
+        .find(|program| {
+            program.name() == name || get_package_name(&program.path()).as_deref() == Some(name)
+        })
+        .copied()
+        .ok_or_else(|| DeployerError::Path(format!("Program {} not found, see `list`", name)))
+}
 
+async fn run_program_command(
+    program: &dyn ProgramDefinition,
+    command: Command,
+) -> Result<(), DeployerError> {
     // Get and verify paths
-    let curr_dir = std::env::current_dir()?;
-    let program_path = curr_dir.join(
-        PathBuf::from(program_path)
-            .parent()
-            .unwrap()
-            .parent()
-            .unwrap(),
-    );
+    let workspace = std::env::current_dir().map_err(|e| DeployerError::Path(e.to_string()))?;
+    let program_path = workspace.join(program.path());
 
     info!("Verifying program path");
-    verify_path(program_path.clone())?;
-
-    let output_path = program_path
-        .join("output")
-        .join(format!("{}-{}", args.target_env, timestamp));
-
-    // Set manager config for the chosen environment
-    info!("Setting manager config for the chosen environment");
-    set_manager_config(&args.target_env).await?;
-
-    // If a path to program_config.json was passed, use it
-    let mut program_config = if let Some(program_config_path) = args.program_config_path {
-        info!("Reading program config from file");
-        read_program_config_from_json(&program_config_path)
-    } else {
-        // Else build the program config from the builder
-        info!("Building program config from builder");
-        let program_params = get_program_params(&program_path, &args.target_env)?;
-
-        builder(program_params)
-    };
-
-    // Write the raw program config to file
-    info!("Writing raw program config to file");
-    write_to_output(&program_config, output_path.clone(), Status::Process, "raw")?;
-
-    // Use program manager to deploy the program
-    println!("Instantiating program...");
-    match valence_program_manager::init_program(&mut program_config).await {
-        Ok(_) => (),
-        Err(e) => {
-            write_to_output(&program_config, output_path.clone(), Status::Fail, "debug")?;
+    verify_path(&workspace, &program_path)?;
+
+    match command {
+        Command::Info { json, .. } => {
+            let info = ProgramInfo::new(program);
 
-            return Err(Box::new(e));
+            if json {
+                print_json(&info)?;
+            } else {
+                print!("{info}");
+            }
+
+            Ok(())
         }
-    };
+        Command::Build { target } => {
+            let deployer = target.deployer(workspace);
+            let program_config = deployer.build(program).await?;
 
-    // Write instantiated program to file
-    write_to_output(
-        &program_config,
-        output_path,
-        Status::Success,
-        "instantiated",
-    )?;
+            print_json(&program_config)?;
 
-    print_success_msg(&program_config).await;
+            Ok(())
+        }
+        Command::Plan {
+            target,
+            program_config_path,
+            out,
+        } => {
+            let deployer = target.deployer(workspace);
+            let plan = get_plan(&deployer, program, program_config_path).await?;
 
-    Ok(())
+            print!("{plan}");
+
+            if let Some(out) = out {
+                plan.write(&out)?;
+                println!("Plan written to {}", out.display());
+            }
+
+            if !plan.is_ok() {
+                return Err(DeployerError::Validation(
+                    "Some amounts or ratios look wrong, see `check-amounts`".to_string(),
+                ));
+            }
+
+            Ok(())
+        }
+        Command::Deploy {
+            target,
+            program_config_path,
+            signer,
+            plan: plan_path,
+            approval,
+            yes_i_mean_mainnet,
+            sign_output,
+            skip_preflight,
+        } => {
+            println!("Starting program deployment...");
+
+            let deployer = target
+                .deployer(workspace)
+                .with_signer(signer)
+                .with_signed_output(sign_output)
+                .with_preflight(!skip_preflight);
+            let plan = get_plan(&deployer, program, program_config_path).await?;
+
+            print!("{plan}");
+
+            let approvals = match (&plan_path, approval.is_empty()) {
+                (Some(plan_path), true) => find_approvals(plan_path),
+                _ => approval,
+            };
+            let confirmed = !deployer.is_production()?
+                || yes_i_mean_mainnet
+                || confirm_deployment(deployer.env())?;
+            let deployer = deployer.with_review(Review {
+                plan: plan_path,
+                approvals,
+                confirmed,
+            });
+
+            let result = deployer.deploy(plan).await?;
+
+            println!("{result}");
+
+            Ok(())
+        }
+        Command::Preflight {
+            target,
+            program_config_path,
+            signer,
+            json,
+        } => {
+            let deployer = target.deployer(workspace).with_signer(signer);
+            let plan = get_plan(&deployer, program, program_config_path).await?;
+            let report = deployer.preflight(&plan).await?;
+
+            if json {
+                print_json(&report)?;
+            } else {
+                print!("{report}");
+            }
+
+            if !report.is_ok() {
+                return Err(DeployerError::Validation(format!(
+                    "{} preflight checks failed",
+                    report.issues.len()
+                )));
+            }
+
+            Ok(())
+        }
+        Command::Diff {
+            target,
+            left,
+            right,
+            json,
+        } => {
+            // Amounts are shown with the denoms of the target env
+            let registry = DenomRegistry::load(&program_path, target.env())?;
+            let left = read_program_config_source(&left, program)?;
+            let right = read_program_config_source(&right, program)?;
+            let changes = diff_program_configs(
+                &registry.annotate_amounts(left),
+                &registry.annotate_amounts(right),
+            );
+
+            if json {
+                print_json(&changes)?;
+            } else if changes.is_empty() {
+                println!("No structural differences");
+            } else {
+                changes.iter().for_each(|change| println!("{change}"));
+            }
+
+            Ok(())
+        }
+        Command::CheckEnvs { .. } => {
+            let report = check_env_equivalence(program)?;
+
+            println!("Reference environment: {}", report.reference_env);
+            for (env, changes) in &report.divergences {
+                if changes.is_empty() {
+                    println!("{env}: equivalent");
+                } else {
+                    println!("{env}: {} structural differences", changes.len());
+                    changes.iter().for_each(|change| println!("    {change}"));
+                }
+            }
+
+            if !report.is_equivalent() {
+                return Err(DeployerError::Validation(
+                    "Program structure differs between environments".to_string(),
+                ));
+            }
+
+            Ok(())
+        }
+        Command::Snapshots { accept, .. } => {
+            let results = check_snapshots(program, accept)?;
+
+            results.iter().for_each(|result| print!("{result}"));
+
+            if results.iter().any(|result| !result.is_ok()) {
+                return Err(DeployerError::Validation(
+                    "Program config differs from snapshots or from the last deployment, use --accept to update the snapshots"
+                        .to_string(),
+                ));
+            }
+
+            Ok(())
+        }
+        Command::CheckAmounts { target, json } => {
+            let program_config = to_json(&build_program(program, target.env())?)?;
+            let checks = get_amount_checks(&program_path, target.env())?;
+            let issues = check_amounts(&program_config, &checks)?;
+
+            if json {
+                print_json(&issues)?;
+            } else if issues.is_empty() {
+                println!("All amounts and ratios look right");
+            } else {
+                issues.iter().for_each(|issue| println!("{issue}"));
+            }
+
+            if issues.iter().any(|issue| !issue.warning) {
+                return Err(DeployerError::Validation(
+                    "Some amounts or ratios look wrong".to_string(),
+                ));
+            }
+
+            Ok(())
+        }
+        Command::Scenarios { name, .. } => {
+            let reports = run_scenarios(program, name.as_deref())?;
+
+            reports.iter().for_each(|report| print!("{report}"));
+
+            if reports.iter().any(|report| !report.is_ok()) {
+                return Err(DeployerError::Validation(
+                    "Some scenarios failed".to_string(),
+                ));
+            }
+
+            Ok(())
+        }
+        Command::Operator {
+            target,
+            output_dir,
+            schedule,
+            state,
+            signer,
+            dry_run,
+            once,
+            simulate,
+            duration,
+        } => {
+            let schedule_path = |env: &str| {
+                schedule.unwrap_or_else(|| {
+                    program_path
+                        .join(OPERATOR_DIR)
+                        .join(format!("{}.toml", env))
+                })
+            };
+            let invalid_schedule = |e| DeployerError::categorize(e, DeployerError::Validation);
+
+            match simulate {
+                Some(scenario) => {
+                    let simulation = get_scenario_simulation(program, &scenario)
+                        .map_err(|e| DeployerError::categorize(e, DeployerError::Build))?;
+                    let client = SimulationClient::new(simulation.sim, &simulation.program_config);
+                    let params = get_template_params(simulation.params, &client.addresses());
+                    let schedule = OperatorSchedule::read(&schedule_path(&simulation.env), &params)
+                        .map_err(invalid_schedule)?;
+                    let client = client.with_operator_of(&schedule, &simulation.program_config);
+                    let until = client.now() + TimeDelta::seconds(duration as i64);
+
+                    Operator::new(schedule, simulation.program_config, client, state)
+                        .map_err(invalid_schedule)?
+                        .with_dry_run(dry_run)
+                        .run(once, Some(until))
+                        .await?;
+                }
+                None => {
+                    let output_dir =
+                        get_output_dir(program, &program_path, output_dir, target.env())?;
+                    let program_config = read_instantiated_program_config(&output_dir)?;
+                    let manager_config = get_env_manager_config(&workspace, target.env())?;
+                    let mnemonic = signer.get_mnemonic(&workspace)?;
+                    let client = ChainClient::connect(&program_config, &manager_config, &mnemonic)
+                        .await
+                        .map_err(|e| DeployerError::categorize(e, DeployerError::Fetch))?;
+
+                    // Messages are built with the params the program was deployed with
+                    let params = match Manifest::read(&output_dir).ok().and_then(|m| m.params) {
+                        Some(params) => params.effective,
+                        None => {
+                            get_valid_params(program, &program_path, target.env())?.to_json()?
+                        }
+                    };
+                    let schedule_path = schedule_path(target.env());
+                    let schedule = OperatorSchedule::read(
+                        &schedule_path,
+                        &get_template_params(params, &client.addresses()),
+                    )
+                    .map_err(invalid_schedule)?;
+                    let state = state.unwrap_or_else(|| schedule_path.with_extension("state.json"));
+
+                    Operator::new(schedule, program_config, client, Some(state))
+                        .map_err(invalid_schedule)?
+                        .with_dry_run(dry_run)
+                        .run(once, None)
+                        .await?;
+                }
+            }
+
+            Ok(())
+        }
+        Command::Status {
+            target,
+            output_dir,
+            json,
+            prometheus,
+            out,
+        } => {
+            let output_dir = get_output_dir(program, &program_path, output_dir, target.env())?;
+            let program_config = read_instantiated_program_config(&output_dir)?;
+            let manager_config = get_env_manager_config(&workspace, target.env())?;
+            let queriers = get_grpc_queriers(&program_config, &manager_config)
+                .await
+                .map_err(|e| DeployerError::categorize(e, DeployerError::Fetch))?;
+
+            let status =
+                ProgramStatus::new(&program_config, &manager_config, target.env(), &queriers).await;
+
+            let output = if prometheus {
+                status.to_prometheus()
+            } else if json {
+                serde_json::to_string_pretty(&status)
+                    .map_err(|e| DeployerError::Output(e.to_string()))?
+                    + "\n"
+            } else {
+                status.to_string()
+            };
+            match out {
+                Some(out) => std::fs::write(&out, output)
+                    .map_err(|e| DeployerError::Output(format!("{}: {}", out.display(), e)))?,
+                None => print!("{output}"),
+            }
+
+            if prometheus {
+                return Ok(());
+            }
+            if !status.errors.is_empty() {
+                return Err(DeployerError::Fetch(format!(
+                    "{} queries failed, the status is incomplete",
+                    status.errors.len()
+                )));
+            }
+            if status.idle_accounts().next().is_some() {
+                return Err(DeployerError::Validation(
+                    "Some accounts hold funds that nothing is moving".to_string(),
+                ));
+            }
+
+            Ok(())
+        }
+        Command::Schedule {
+            target,
+            input,
+            duration,
+            json,
+        } => {
+            let program_config = to_json(&build_program(program, target.env())?)?;
+            let registry = DenomRegistry::load(&program_path, target.env())?;
+            let schedules = get_schedule(&program_config, &input, duration, &registry)?;
+
+            if json {
+                print_json(&schedules)?;
+            } else if schedules.is_empty() {
+                println!("Program has no forwarders");
+            } else {
+                schedules.iter().for_each(|schedule| print!("{schedule}"));
+            }
+
+            if schedules.iter().any(|schedule| schedule.warning.is_some()) {
+                return Err(DeployerError::Validation(
+                    "Schedule has warnings".to_string(),
+                ));
+            }
+
+            Ok(())
+        }
+    }
+}
+
+async fn get_plan(
+    deployer: &Deployer,
+    program: &dyn ProgramDefinition,
+    program_config_path: Option<PathBuf>,
+) -> Result<DeploymentPlan, DeployerError> {
+    match program_config_path {
+        Some(path) => deployer.plan_from_file(program, &path).await,
+        None => deployer.plan(program).await,
+    }
 }
 
-fn write_to_output(
-    program_config: &ProgramConfig,
-    mut path: PathBuf,
-    status: Status,
-    prefix: &str,
-) -> Result<(), Box<dyn Error>> {
-    if !path.exists() {
-        std::fs::create_dir_all(path.clone())?;
-    } else if status != Status::Process {
-        let new_path = PathBuf::from(format!("{}-{}", path.to_str().unwrap(), status));
-        std::fs::rename(path, new_path.clone())?;
-        path = new_path;
+/// The given output directory of a deployment, or the latest successful one on the env
+fn get_output_dir(
+    program: &dyn ProgramDefinition,
+    program_path: &Path,
+    output_dir: Option<PathBuf>,
+    env: &str,
+) -> Result<PathBuf, DeployerError> {
+    match output_dir {
+        Some(output_dir) => Ok(output_dir),
+        None => get_latest_success_output(program_path, env).ok_or_else(|| {
+            DeployerError::Path(format!(
+                "No successful deployment of {} on {}",
+                program.name(),
+                env
+            ))
+        }),
     }
+}
+
+fn read_instantiated_program_config(output_dir: &Path) -> Result<serde_json::Value, DeployerError> {
+    let path = output_dir.join("instantiated-program-config.json");
+    let content = std::fs::read_to_string(&path).map_err(|e| {
+        DeployerError::Path(format!(
+            "Only successful deployments have an instantiated program config, {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
 
-    // Construct the full file path
-    let file_name = format!("{}-program-config.json", prefix);
-    let file_path = path.join(file_name.clone());
+    serde_json::from_str(&content).map_err(|e| {
+        DeployerError::Validation(format!("Invalid program config {}: {}", path.display(), e))
+    })
+}
 
-    // Create and write to the file
-    let mut file = std::fs::File::create(file_path.clone())?;
+/// Current manager config of the env, the endpoints may have changed since a deployment
+fn get_env_manager_config(workspace: &Path, env: &str) -> Result<serde_json::Value, DeployerError> {
+    get_manager_config_snapshot(&ManagerConfigSource::Workspace.get_dir(workspace, env)?)
+}
 
-    // Serialize the data to a string
-    let content = serde_json::to_string(program_config)?;
+fn to_json<T: Serialize>(value: &T) -> Result<serde_json::Value, DeployerError> {
+    serde_json::to_value(value).map_err(|e| DeployerError::Output(e.to_string()))
+}
 
-    file.write_all(content.as_bytes())?;
+fn print_json<T: Serialize>(value: &T) -> Result<(), DeployerError> {
+    let json =
+        serde_json::to_string_pretty(value).map_err(|e| DeployerError::Output(e.to_string()))?;
+    println!("{json}");
 
     Ok(())
 }
 
-async fn print_success_msg(program_config: &ProgramConfig) {
-    let gc = valence_program_manager::config::GLOBAL_CONFIG.lock().await;
-    let ui_link_main = format!("https://app.valence.zone/programs/{}", program_config.id);
-    let ui_link = format!("{}?queryConfig={{\"main\":{{\"registryAddress\":\"{}\",\"name\":\"neutron\",\"chainId\":\"neutron-1\",\"rpcUrl\":\"{}\"}},\"external\":[]}}", ui_link_main, gc.general.registry_addr, gc.chains.get("neutron").unwrap().rpc);
+/// `<plan>.<reviewer>.approval.json` next to the plan
+fn get_approval_path(plan: &Path, reviewer: &str) -> PathBuf {
+    let stem = plan
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    plan.with_file_name(format!("{}.{}.approval.json", stem, reviewer))
+}
+
+fn find_approvals(plan: &Path) -> Vec<PathBuf> {
+    let pattern = get_approval_path(plan, "*");
+
+    glob::glob(&pattern.to_string_lossy())
+        .map(|paths| paths.filter_map(|path| path.ok()).collect())
+        .unwrap_or_default()
+}
+
+/// Ask to type the env name after seeing the plan, only from a terminal
+fn confirm_deployment(env: &str) -> Result<bool, DeployerError> {
+    if !std::io::stdin().is_terminal() {
+        return Ok(false);
+    }
+
+    print!("Type {} to deploy the program above: ", env);
+    std::io::stdout()
+        .flush()
+        .map_err(|e| DeployerError::Output(e.to_string()))?;
 
-    let success_msg = format!(
-        "Program deployed successfully! 
-Program id: {} 
-View program on Valence UI: {}",
-        program_config.id, ui_link
-    );
+    let mut answer = String::new();
+    std::io::stdin()
+        .read_line(&mut answer)
+        .map_err(|e| DeployerError::Other(e.to_string()))?;
 
-    println!("{success_msg}");
+    Ok(answer.trim() == env)
 }
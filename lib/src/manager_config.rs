@@ -1,22 +1,90 @@
 use config::Config as ConfigHelper;
-use std::{error::Error, path::Path};
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use tokio::sync::{Mutex, MutexGuard};
+use zeroize::Zeroizing;
+
+use crate::error::DeployerError;
+
+/// Env var the program manager reads the mnemonic it signs with from
+const MANAGER_MNEMONIC_VAR: &str = "MANAGER_MNEMONIC";
+
+/// The config and the mnemonic of the manager are process wide, held for a whole deployment
+static MANAGER_LOCK: Mutex<()> = Mutex::const_new(());
 
 const MANAGER_CONFIGS_REPO_URL: &str =
     "https://github.com/timewave-computer/valence-program-manager-config.git";
 
-pub fn get_manager_config(
-    path: &str,
-) -> Result<valence_program_manager::config::Config, Box<dyn Error>> {
-    // TODO: Get config from our repo if env exists
-    let path = &path.to_lowercase();
-    let config_path = std::env::current_dir()?.join("manager_configs").join(path);
-    let config_path_str = config_path
-        .to_str()
-        .expect("Config path should be a string");
-
-    if !config_path.exists() {
-        clone_config_from_repo(path, &config_path)?;
+/// Where the manager config of an environment is read from
+#[derive(Debug, Clone, Default)]
+pub enum ManagerConfigSource {
+    /// `manager_configs/<env>` of the workspace, cloned from the manager configs repo if missing
+    #[default]
+    Workspace,
+    /// Directory of a manager config, used for every environment
+    Dir(PathBuf),
+}
+
+impl ManagerConfigSource {
+    /// Get the directory of the manager config of the environment
+    pub(crate) fn get_dir(&self, workspace: &Path, env: &str) -> Result<PathBuf, DeployerError> {
+        match self {
+            ManagerConfigSource::Workspace => {
+                // TODO: Get config from our repo if env exists
+                let env = env.to_lowercase();
+                let config_path = workspace.join("manager_configs").join(&env);
+
+                if !config_path.exists() {
+                    clone_config_from_repo(workspace, &env, &config_path)
+                        .map_err(|e| DeployerError::categorize(e, DeployerError::ManagerConfig))?;
+                }
+
+                Ok(config_path)
+            }
+            ManagerConfigSource::Dir(path) if path.exists() => Ok(path.clone()),
+            ManagerConfigSource::Dir(path) => Err(DeployerError::ManagerConfig(format!(
+                "Manager config {} does not exist",
+                path.display()
+            ))),
+        }
     }
+}
+
+pub(crate) fn get_manager_config(
+    config_path: &Path,
+) -> Result<valence_program_manager::config::Config, DeployerError> {
+    load_manager_config(config_path)?
+        .try_deserialize()
+        .map_err(|e| invalid_config(config_path, &e))
+}
+
+/// Get the manager config as plain json, used to snapshot the config into the manifest
+pub(crate) fn get_manager_config_snapshot(
+    config_path: &Path,
+) -> Result<serde_json::Value, DeployerError> {
+    load_manager_config(config_path)?
+        .try_deserialize()
+        .map_err(|e| invalid_config(config_path, &e))
+}
+
+fn invalid_config(config_path: &Path, e: &dyn Error) -> DeployerError {
+    DeployerError::ManagerConfig(format!(
+        "Invalid manager config {}: {}",
+        config_path.display(),
+        e
+    ))
+}
+
+fn load_manager_config(config_path: &Path) -> Result<ConfigHelper, DeployerError> {
+    let config_path_str = config_path.to_str().ok_or_else(|| {
+        DeployerError::ManagerConfig(format!(
+            "Manager config path is not valid utf-8: {}",
+            config_path.display()
+        ))
+    })?;
 
     if let Ok(cfg) = ConfigHelper::builder()
         .add_source(config::File::with_name(&format!(
@@ -25,56 +93,84 @@ pub fn get_manager_config(
         )))
         .build()
     {
-        return cfg.try_deserialize().map_err(|e| e.into());
+        return Ok(cfg);
+    };
+
+    // Every file of the config directory and its subdirectories
+    let get_files = |pattern: String| {
+        glob::glob(&pattern)
+            .map_err(|e| invalid_config(config_path, &e))?
+            .filter_map(|path| match path {
+                Ok(p) if p.is_dir() => None,
+                Ok(p) => Some(Ok(config::File::from(p))),
+                Err(e) => Some(Err(invalid_config(config_path, &e))),
+            })
+            .collect::<Result<Vec<_>, _>>()
     };
 
     ConfigHelper::builder()
-        .add_source(
-            glob::glob(&format!("{}/*", config_path_str))
-                .unwrap()
-                .filter_map(|path| {
-                    let p = path.unwrap();
-
-                    if p.is_dir() {
-                        None
-                    } else {
-                        Some(config::File::from(p))
-                    }
-                })
-                .collect::<Vec<_>>(),
-        )
-        .add_source(
-            glob::glob(&format!("{}/**/*", config_path_str))
-                .unwrap()
-                .filter_map(|path| {
-                    let p = path.unwrap();
-                    if p.is_dir() {
-                        None
-                    } else {
-                        Some(config::File::from(p))
-                    }
-                })
-                .collect::<Vec<_>>(),
-        )
-        .build()?
-        .try_deserialize()
-        .map_err(|e| e.into())
-    // .map_err(|_| "Failed to parse config".into())
+        .add_source(get_files(format!("{}/*", config_path_str))?)
+        .add_source(get_files(format!("{}/**/*", config_path_str))?)
+        .build()
+        .map_err(|e| invalid_config(config_path, &e))
 }
 
-pub(crate) async fn set_manager_config(path: &str) -> Result<(), Box<dyn Error>> {
+/// Set the global config of the manager and the mnemonic it deploys with,
+/// the manager signs with the mnemonic until the returned guard is dropped.
+/// Deployments of the process wait for each other, as the manager only has one config and mnemonic
+pub(crate) async fn set_manager_config(
+    config_path: &Path,
+    mnemonic: &str,
+) -> Result<ManagerMnemonic, DeployerError> {
+    let manager_mnemonic = ManagerMnemonic::set(mnemonic).await;
+
     // Read the config
-    let config = get_manager_config(path)?;
+    let config = get_manager_config(config_path)?;
 
     // Set the global config of the manager with the read config
     let mut gc = valence_program_manager::config::GLOBAL_CONFIG.lock().await;
     *gc = config;
-    Ok(())
+
+    Ok(manager_mnemonic)
+}
+
+/// Mnemonic handed to the program manager, which only reads it from `MANAGER_MNEMONIC` when it
+/// connects to a chain. The variable is only changed while holding the manager lock, and its
+/// previous value is restored before the lock is released on drop
+pub(crate) struct ManagerMnemonic {
+    previous: Option<Zeroizing<String>>,
+    _lock: MutexGuard<'static, ()>,
 }
 
-fn clone_config_from_repo(env_path: &str, config_path: &Path) -> Result<(), Box<dyn Error>> {
+impl ManagerMnemonic {
+    async fn set(mnemonic: &str) -> Self {
+        let lock = MANAGER_LOCK.lock().await;
+        let previous = std::env::var(MANAGER_MNEMONIC_VAR).ok().map(Zeroizing::new);
+        std::env::set_var(MANAGER_MNEMONIC_VAR, mnemonic);
+
+        ManagerMnemonic {
+            previous,
+            _lock: lock,
+        }
+    }
+}
+
+impl Drop for ManagerMnemonic {
+    fn drop(&mut self) {
+        match &self.previous {
+            Some(previous) => std::env::set_var(MANAGER_MNEMONIC_VAR, previous.as_str()),
+            None => std::env::remove_var(MANAGER_MNEMONIC_VAR),
+        }
+    }
+}
+
+fn clone_config_from_repo(
+    workspace: &Path,
+    env_path: &str,
+    config_path: &Path,
+) -> Result<(), Box<dyn Error>> {
     // DO NOT CHANGE THIS
-    let tmp_dir = std::env::current_dir()?.join("tmp");
+    let tmp_dir = workspace.join("tmp");
 
     if !tmp_dir.exists() {
         std::fs::create_dir_all(&tmp_dir)?;
@@ -97,3 +193,36 @@ fn clone_config_from_repo(env_path: &str, config_path: &Path) -> Result<(), Box<
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{ManagerMnemonic, MANAGER_MNEMONIC_VAR};
+
+    fn manager_mnemonic() -> Option<String> {
+        std::env::var(MANAGER_MNEMONIC_VAR).ok()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn deployments_hand_their_mnemonic_one_at_a_time() {
+        let previous = manager_mnemonic();
+        let first = ManagerMnemonic::set("first").await;
+
+        let second = tokio::spawn(async {
+            let second = ManagerMnemonic::set("second").await;
+            let mnemonic = manager_mnemonic();
+            drop(second);
+            mnemonic
+        });
+
+        // The second deployment waits for the first one to be done with the manager
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!second.is_finished());
+        assert_eq!(manager_mnemonic().as_deref(), Some("first"));
+
+        drop(first);
+        assert_eq!(second.await.unwrap().as_deref(), Some("second"));
+        assert_eq!(manager_mnemonic(), previous);
+    }
+}
@@ -0,0 +1,1036 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, TimeDelta, Utc};
+use deployer_simulator::{default_messages, interpolate, Simulation};
+use log::{info, warn};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    deployments::get_addresses,
+    schedule::{format_duration, parse_duration},
+    tx::TxSender,
+    verify::{get_address_chain, get_grpc_queriers, Queriers},
+};
+
+pub(crate) const OPERATOR_DIR: &str = "operator";
+
+/// Authorizations an operator executes on a cadence, read from `operator/<env>.toml` of the program.
+/// Strings can refer to program params and to the accounts and libraries of the deployment with `${name}`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OperatorSchedule {
+    /// How often due tasks are looked for, Ex: 10m
+    #[serde(
+        default = "default_poll_interval",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub poll_interval: u64,
+    /// Consecutive failures retried every `retry_delay`, after that the task waits for its next run
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    #[serde(
+        default = "default_retry_delay",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub retry_delay: u64,
+    #[serde(default)]
+    pub tasks: Vec<OperatorTask>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OperatorTask {
+    /// Label of the authorization to execute
+    pub label: String,
+    /// Time between 2 successful runs, Ex: 7d
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub every: u64,
+    /// Messages to execute the authorization with, one per function,
+    /// built from the function restrictions if not set
+    pub messages: Option<Vec<Value>>,
+    /// Tick the processor after sending the messages, so they are executed right away
+    #[serde(default = "default_tick")]
+    pub tick: bool,
+    #[serde(default)]
+    pub preconditions: Vec<Precondition>,
+}
+
+/// The task is skipped until the account holds at least the minimum balance
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Precondition {
+    /// Account or library name, or an address
+    pub account: String,
+    pub denom: String,
+    #[serde(deserialize_with = "deserialize_amount")]
+    pub min_balance: u128,
+}
+
+fn default_poll_interval() -> u64 {
+    600
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_retry_delay() -> u64 {
+    300
+}
+
+fn default_tick() -> bool {
+    true
+}
+
+fn deserialize_duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    parse_duration(&String::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+}
+
+/// Amounts are strings once params are interpolated
+fn deserialize_amount<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::String(s) => s.parse().map_err(serde::de::Error::custom),
+        Value::Number(n) => n
+            .as_u64()
+            .map(u128::from)
+            .ok_or_else(|| serde::de::Error::custom(format!("Invalid amount {}", n))),
+        v => Err(serde::de::Error::custom(format!("Invalid amount {}", v))),
+    }
+}
+
+impl OperatorSchedule {
+    /// Read a schedule from a toml or json file, resolving `${name}` with the given params
+    pub(crate) fn read(path: &Path, params: &Value) -> Result<Self, Box<dyn Error>> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Schedule {}: {}", path.display(), e))?;
+        let schedule: Value = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => serde_json::from_str(&content)?,
+            _ => toml::from_str(&content)?,
+        };
+
+        Ok(serde_json::from_value(interpolate(schedule, params)?)
+            .map_err(|e| format!("Invalid schedule {}: {}", path.display(), e))?)
+    }
+}
+
+/// What the operator did for every task, persisted between runs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OperatorState {
+    /// Program the state is for, a new deployment starts from a fresh state
+    pub program_id: Option<u64>,
+    #[serde(default)]
+    pub tasks: BTreeMap<String, TaskState>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskState {
+    pub last_success: Option<DateTime<Utc>>,
+    pub last_attempt: Option<DateTime<Utc>>,
+    pub last_tx: Option<String>,
+    pub last_error: Option<String>,
+    /// Consecutive failures since the last success
+    #[serde(default)]
+    pub failures: u32,
+}
+
+impl OperatorState {
+    fn read(path: &Path, program_id: Option<u64>) -> Result<Self, Box<dyn Error>> {
+        if !path.exists() {
+            return Ok(OperatorState {
+                program_id,
+                ..Default::default()
+            });
+        }
+
+        let state: OperatorState = serde_json::from_str(&std::fs::read_to_string(path)?)
+            .map_err(|e| format!("Invalid operator state {}: {}", path.display(), e))?;
+        if state.program_id != program_id {
+            warn!(
+                "{} is for another deployment, starting from a fresh state",
+                path.display()
+            );
+            return Ok(OperatorState {
+                program_id,
+                ..Default::default()
+            });
+        }
+
+        Ok(state)
+    }
+
+    fn write(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        Ok(std::fs::write(path, serde_json::to_string_pretty(self)?)?)
+    }
+}
+
+impl TaskState {
+    /// When the task runs next, right away if it never ran
+    fn next_run(
+        &self,
+        task: &OperatorTask,
+        schedule: &OperatorSchedule,
+    ) -> Result<Option<DateTime<Utc>>, String> {
+        let after = |time: Option<DateTime<Utc>>, seconds: u64| {
+            time.map(|time| add_seconds(time, seconds)).transpose()
+        };
+
+        match self.failures {
+            0 => after(self.last_success, task.every),
+            failures if failures < schedule.max_retries => {
+                after(self.last_attempt, schedule.retry_delay)
+            }
+            _ => after(self.last_attempt, task.every),
+        }
+    }
+}
+
+/// Errors instead of overflowing if the time can't be represented
+fn add_seconds(time: DateTime<Utc>, seconds: u64) -> Result<DateTime<Utc>, String> {
+    i64::try_from(seconds)
+        .ok()
+        .and_then(TimeDelta::try_seconds)
+        .and_then(|delta| time.checked_add_signed(delta))
+        .ok_or_else(|| {
+            format!(
+                "{} after {} is out of range",
+                format_duration(seconds),
+                time
+            )
+        })
+}
+
+/// Outcome of running a due task
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case", tag = "outcome")]
+pub enum TaskOutcome {
+    Sent {
+        tx: String,
+        /// The messages were sent but the processor wasn't ticked, they run on the next tick
+        tick_error: Option<String>,
+    },
+    /// `--dry-run`, nothing was sent
+    DryRun {
+        messages: Vec<Value>,
+    },
+    /// A precondition isn't met, the task is tried again on the next poll
+    Skipped {
+        reason: String,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskRun {
+    pub label: String,
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub outcome: TaskOutcome,
+}
+
+impl Display for TaskRun {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let at = self.at.format("%Y-%m-%d %H:%M:%S");
+
+        match &self.outcome {
+            TaskOutcome::Sent { tx, tick_error } => {
+                write!(f, "{} {}: sent {}", at, self.label, tx)?;
+                if let Some(error) = tick_error {
+                    write!(f, ", tick failed: {}", error)?;
+                }
+                Ok(())
+            }
+            TaskOutcome::DryRun { messages } => write!(
+                f,
+                "{} {}: would send {}",
+                at,
+                self.label,
+                serde_json::to_string(messages).unwrap_or_default()
+            ),
+            TaskOutcome::Skipped { reason } => {
+                write!(f, "{} {}: skipped, {}", at, self.label, reason)
+            }
+            TaskOutcome::Failed { error } => write!(f, "{} {}: FAILED {}", at, self.label, error),
+        }
+    }
+}
+
+/// Where the operator sends its transactions, a chain or a simulation of the program
+#[async_trait(?Send)]
+pub(crate) trait OperatorClient {
+    /// Address the authorizations are executed from
+    fn address(&self) -> String;
+
+    /// Addresses of the deployment by account/library name, `authorization` and `processor:<domain>`
+    fn addresses(&self) -> BTreeMap<String, String>;
+
+    fn now(&self) -> DateTime<Utc>;
+
+    async fn wait(&mut self, seconds: u64);
+
+    async fn balance(&self, addr: &str, denom: &str) -> Result<u128, Box<dyn Error>>;
+
+    /// Execute an authorization with the messages, returns what identifies the execution
+    async fn send_msgs(
+        &mut self,
+        authorization: &Value,
+        messages: Vec<Value>,
+    ) -> Result<String, Box<dyn Error>>;
+
+    /// Tick the processor of the main domain
+    async fn tick(&mut self) -> Result<String, Box<dyn Error>>;
+}
+
+/// Runs the tasks of a schedule when they are due
+pub(crate) struct Operator<C: OperatorClient> {
+    schedule: OperatorSchedule,
+    program_config: Value,
+    client: C,
+    state: OperatorState,
+    /// The state is only kept in memory if not set
+    state_path: Option<PathBuf>,
+    dry_run: bool,
+}
+
+impl<C: OperatorClient> Operator<C> {
+    /// Errors if an authorization of the schedule doesn't exist or the client isn't one of its operators
+    pub(crate) fn new(
+        schedule: OperatorSchedule,
+        program_config: Value,
+        client: C,
+        state_path: Option<PathBuf>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let now = client.now();
+        add_seconds(now, schedule.poll_interval)
+            .and(add_seconds(now, schedule.retry_delay))
+            .map_err(|e| format!("Invalid schedule: {}", e))?;
+
+        let address = client.address();
+        for task in &schedule.tasks {
+            add_seconds(now, task.every)
+                .map_err(|e| format!("Invalid task {}: {}", task.label, e))?;
+            let authorization = get_authorization(&program_config, &task.label)?;
+
+            if !is_permitted(authorization, &address) {
+                return Err(format!(
+                    "{} is not an operator of authorization {}",
+                    address, task.label
+                )
+                .into());
+            }
+        }
+
+        let program_id = program_config.get("id").and_then(|id| id.as_u64());
+        let state = match &state_path {
+            Some(path) => OperatorState::read(path, program_id)?,
+            None => OperatorState {
+                program_id,
+                ..Default::default()
+            },
+        };
+
+        Ok(Operator {
+            schedule,
+            program_config,
+            client,
+            state,
+            state_path,
+            dry_run: false,
+        })
+    }
+
+    /// Print the messages of due tasks without sending them or changing the state
+    pub(crate) fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Run the due tasks every poll interval, until the given time if any
+    pub(crate) async fn run(
+        &mut self,
+        once: bool,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<(), Box<dyn Error>> {
+        info!(
+            "Operating as {}, polling every {}s",
+            self.client.address(),
+            self.schedule.poll_interval
+        );
+
+        loop {
+            for run in self.run_due().await? {
+                println!("{run}");
+            }
+
+            if once || until.is_some_and(|until| self.client.now() >= until) {
+                return Ok(());
+            }
+
+            self.client.wait(self.schedule.poll_interval).await;
+        }
+    }
+
+    /// Run every task that is due now, in the order of the schedule
+    pub(crate) async fn run_due(&mut self) -> Result<Vec<TaskRun>, Box<dyn Error>> {
+        let mut runs = vec![];
+
+        for task in self.schedule.tasks.clone() {
+            let now = self.client.now();
+            let mut state = self
+                .state
+                .tasks
+                .get(&task.label)
+                .cloned()
+                .unwrap_or_default();
+            if state
+                .next_run(&task, &self.schedule)
+                .map_err(|e| format!("Can't schedule task {}: {}", task.label, e))?
+                .is_some_and(|next_run| next_run > now)
+            {
+                continue;
+            }
+
+            let outcome = self.run_task(&task).await;
+            match &outcome {
+                TaskOutcome::Sent { tx, .. } => {
+                    state.last_success = Some(now);
+                    state.last_attempt = Some(now);
+                    state.last_tx = Some(tx.clone());
+                    state.last_error = None;
+                    state.failures = 0;
+                }
+                TaskOutcome::Failed { error } => {
+                    state.last_attempt = Some(now);
+                    state.last_error = Some(error.clone());
+                    state.failures += 1;
+                }
+                TaskOutcome::DryRun { .. } | TaskOutcome::Skipped { .. } => {}
+            }
+
+            self.state.tasks.insert(task.label.clone(), state);
+            if let Some(path) = &self.state_path {
+                self.state.write(path)?;
+            }
+
+            runs.push(TaskRun {
+                label: task.label,
+                at: now,
+                outcome,
+            });
+        }
+
+        Ok(runs)
+    }
+
+    async fn run_task(&mut self, task: &OperatorTask) -> TaskOutcome {
+        let addresses = self.client.addresses();
+
+        for precondition in &task.preconditions {
+            let addr = addresses
+                .get(&precondition.account)
+                .unwrap_or(&precondition.account);
+
+            match self.client.balance(addr, &precondition.denom).await {
+                Ok(balance) if balance < precondition.min_balance => {
+                    return TaskOutcome::Skipped {
+                        reason: format!(
+                            "{} has {}{}, needs {}",
+                            precondition.account,
+                            balance,
+                            precondition.denom,
+                            precondition.min_balance
+                        ),
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    return TaskOutcome::Failed {
+                        error: format!("Failed to query {}: {}", precondition.account, e),
+                    }
+                }
+            }
+        }
+
+        // Checked when creating the operator
+        let Ok(authorization) = get_authorization(&self.program_config, &task.label).cloned()
+        else {
+            return TaskOutcome::Failed {
+                error: format!("Authorization {} not found", task.label),
+            };
+        };
+        let messages = task
+            .messages
+            .clone()
+            .unwrap_or_else(|| default_messages(&authorization));
+
+        if self.dry_run {
+            return TaskOutcome::DryRun { messages };
+        }
+
+        match self.client.send_msgs(&authorization, messages).await {
+            Ok(tx) => TaskOutcome::Sent {
+                tx,
+                tick_error: match task.tick {
+                    true => self.client.tick().await.err().map(|e| e.to_string()),
+                    false => None,
+                },
+            },
+            Err(e) => TaskOutcome::Failed {
+                error: e.to_string(),
+            },
+        }
+    }
+}
+
+/// Program params with the addresses of the deployment, to resolve `${name}` in a schedule
+pub(crate) fn get_template_params(params: Value, addresses: &BTreeMap<String, String>) -> Value {
+    let mut params = match params {
+        Value::Object(map) => map,
+        _ => serde_json::Map::new(),
+    };
+    for (name, addr) in addresses {
+        params.entry(name.clone()).or_insert(json!(addr));
+    }
+
+    Value::Object(params)
+}
+
+fn get_authorization<'a>(program_config: &'a Value, label: &str) -> Result<&'a Value, String> {
+    program_config
+        .get("authorizations")
+        .and_then(|a| a.as_array())
+        .and_then(|authorizations| {
+            authorizations
+                .iter()
+                .find(|a| a.get("label").and_then(|l| l.as_str()) == Some(label))
+        })
+        .ok_or_else(|| format!("Authorization {} not found", label))
+}
+
+/// Addresses allowed to execute a permissioned authorization, with call limit they come with their limit
+fn get_operators(authorization: &Value) -> Option<Vec<String>> {
+    let permissioned = authorization.pointer("/mode/permissioned")?;
+    let operators = permissioned
+        .get("with_call_limit")
+        .or_else(|| permissioned.get("without_call_limit"))?
+        .as_array()?
+        .iter()
+        .filter_map(|operator| match operator {
+            Value::Array(entry) => entry.first()?.as_str(),
+            operator => operator.as_str(),
+        })
+        .map(|operator| operator.to_string())
+        .collect();
+
+    Some(operators)
+}
+
+fn is_permitted(authorization: &Value, address: &str) -> bool {
+    get_operators(authorization).is_none_or(|operators| operators.iter().any(|o| o == address))
+}
+
+/// Sends the transactions of the operator to the chain of the authorization contract
+pub(crate) struct ChainClient {
+    sender: TxSender,
+    queriers: Queriers,
+    manager_config: Value,
+    addresses: BTreeMap<String, String>,
+}
+
+impl ChainClient {
+    pub(crate) async fn connect(
+        program_config: &Value,
+        manager_config: &Value,
+        mnemonic: &str,
+    ) -> Result<Self, Box<dyn Error>> {
+        let addresses = get_addresses(program_config);
+        let authorization = addresses
+            .get("authorization")
+            .ok_or("Program has no authorization contract")?;
+        let chain = get_address_chain(authorization, manager_config)
+            .ok_or_else(|| format!("No chain in the manager config for {}", authorization))?;
+
+        Ok(ChainClient {
+            sender: TxSender::connect(manager_config, &chain, mnemonic).await?,
+            queriers: get_grpc_queriers(program_config, manager_config).await?,
+            manager_config: manager_config.clone(),
+            addresses,
+        })
+    }
+
+    fn get_processor(&self) -> Result<&String, Box<dyn Error>> {
+        let authorization = &self.addresses["authorization"];
+        let (prefix, _) = authorization.rsplit_once('1').unwrap_or_default();
+
+        self.addresses
+            .iter()
+            .find(|(name, addr)| name.starts_with("processor:") && addr.starts_with(prefix))
+            .map(|(_, addr)| addr)
+            .ok_or_else(|| "Program has no processor on the main domain".into())
+    }
+}
+
+#[async_trait(?Send)]
+impl OperatorClient for ChainClient {
+    fn address(&self) -> String {
+        self.sender.address()
+    }
+
+    fn addresses(&self) -> BTreeMap<String, String> {
+        self.addresses.clone()
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    async fn wait(&mut self, seconds: u64) {
+        tokio::time::sleep(std::time::Duration::from_secs(seconds)).await;
+    }
+
+    async fn balance(&self, addr: &str, denom: &str) -> Result<u128, Box<dyn Error>> {
+        let querier = get_address_chain(addr, &self.manager_config)
+            .and_then(|chain| self.queriers.get(&chain))
+            .ok_or_else(|| format!("No chain the program is on has the prefix of {}", addr))?;
+
+        querier.balance(addr, denom).await
+    }
+
+    async fn send_msgs(
+        &mut self,
+        authorization: &Value,
+        messages: Vec<Value>,
+    ) -> Result<String, Box<dyn Error>> {
+        let label = authorization["label"].as_str().unwrap_or_default();
+        let authorization_addr = &self.addresses["authorization"];
+
+        // With call limit, the authorization token is spent on every execution
+        let funds = match authorization.pointer("/mode/permissioned/with_call_limit") {
+            Some(_) => vec![(1, format!("factory/{}/{}", authorization_addr, label))],
+            None => vec![],
+        };
+
+        let messages = messages
+            .iter()
+            .map(|msg| {
+                Ok(json!({
+                    "cosmwasm_execute_msg": {"msg": STANDARD.encode(serde_json::to_vec(msg)?)}
+                }))
+            })
+            .collect::<Result<Vec<_>, serde_json::Error>>()?;
+        let msg = json!({
+            "permissionless_action": {
+                "send_msgs": {
+                    "label": label,
+                    "messages": messages,
+                    "ttl": null,
+                }
+            }
+        });
+
+        self.sender.execute(authorization_addr, &msg, funds).await
+    }
+
+    async fn tick(&mut self) -> Result<String, Box<dyn Error>> {
+        let processor = self.get_processor()?.clone();
+
+        self.sender
+            .execute(
+                &processor,
+                &json!({"permissionless_action": {"tick": {}}}),
+                vec![],
+            )
+            .await
+    }
+}
+
+/// Runs the operator against a simulation of the program, time moves forward instead of waiting
+pub(crate) struct SimulationClient {
+    sim: Simulation,
+    actor: String,
+    addresses: BTreeMap<String, String>,
+    started_at: DateTime<Utc>,
+    elapsed: u64,
+}
+
+impl SimulationClient {
+    pub(crate) fn new(sim: Simulation, program_config: &Value) -> Self {
+        let mut names = vec!["authorization".to_string(), "processor".to_string()];
+        for key in ["accounts", "libraries"] {
+            if let Some(entries) = program_config.get(key).and_then(|e| e.as_object()) {
+                names.extend(
+                    entries
+                        .values()
+                        .filter_map(|entry| entry.get("name").and_then(|n| n.as_str()))
+                        .map(|name| name.to_string()),
+                );
+            }
+        }
+        let addresses = names
+            .into_iter()
+            .map(|name| {
+                let addr = sim.addr(&name).to_string();
+                (name, addr)
+            })
+            .collect();
+
+        SimulationClient {
+            sim,
+            actor: "operator".to_string(),
+            addresses,
+            started_at: Utc::now(),
+            elapsed: 0,
+        }
+    }
+
+    /// Act as the first operator of the authorizations of the schedule
+    pub(crate) fn with_operator_of(
+        mut self,
+        schedule: &OperatorSchedule,
+        program_config: &Value,
+    ) -> Self {
+        if let Some(operator) = schedule
+            .tasks
+            .iter()
+            .filter_map(|task| get_authorization(program_config, &task.label).ok())
+            .find_map(|authorization| get_operators(authorization)?.into_iter().next())
+        {
+            self.actor = operator;
+        }
+
+        self
+    }
+}
+
+#[async_trait(?Send)]
+impl OperatorClient for SimulationClient {
+    fn address(&self) -> String {
+        self.actor.clone()
+    }
+
+    fn addresses(&self) -> BTreeMap<String, String> {
+        self.addresses.clone()
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        add_seconds(self.started_at, self.elapsed).unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+
+    async fn wait(&mut self, seconds: u64) {
+        self.sim.advance_time(seconds);
+        self.elapsed += seconds;
+    }
+
+    async fn balance(&self, addr: &str, denom: &str) -> Result<u128, Box<dyn Error>> {
+        self.sim.balance(addr, denom)
+    }
+
+    async fn send_msgs(
+        &mut self,
+        authorization: &Value,
+        messages: Vec<Value>,
+    ) -> Result<String, Box<dyn Error>> {
+        let label = authorization["label"].as_str().unwrap_or_default();
+        self.sim
+            .execute_with_messages(&self.actor, label, messages)?;
+
+        Ok("simulated".to_string())
+    }
+
+    async fn tick(&mut self) -> Result<String, Box<dyn Error>> {
+        self.sim.tick()?;
+
+        Ok("simulated".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, error::Error};
+
+    use async_trait::async_trait;
+    use chrono::{DateTime, TimeDelta, Utc};
+    use serde_json::{json, Value};
+    use tempfile::tempdir;
+
+    use super::{Operator, OperatorClient, OperatorSchedule, TaskOutcome};
+
+    const OPERATOR: &str = "neutron1operator";
+
+    /// Client whose time is moved by the test, sends fail while `failing` is set
+    struct TestClient {
+        now: DateTime<Utc>,
+        balances: BTreeMap<String, u128>,
+        failing: bool,
+        sent: Vec<(String, Vec<Value>)>,
+        ticks: u32,
+    }
+
+    impl TestClient {
+        fn new() -> Self {
+            TestClient {
+                now: "2026-01-01T00:00:00Z".parse().unwrap(),
+                balances: BTreeMap::new(),
+                failing: false,
+                sent: vec![],
+                ticks: 0,
+            }
+        }
+    }
+
+    #[async_trait(?Send)]
+    impl OperatorClient for TestClient {
+        fn address(&self) -> String {
+            OPERATOR.to_string()
+        }
+
+        fn addresses(&self) -> BTreeMap<String, String> {
+            BTreeMap::from([("input_account".to_string(), "neutron1input".to_string())])
+        }
+
+        fn now(&self) -> DateTime<Utc> {
+            self.now
+        }
+
+        async fn wait(&mut self, seconds: u64) {
+            self.now += TimeDelta::seconds(seconds as i64);
+        }
+
+        async fn balance(&self, addr: &str, _denom: &str) -> Result<u128, Box<dyn Error>> {
+            Ok(self.balances.get(addr).copied().unwrap_or_default())
+        }
+
+        async fn send_msgs(
+            &mut self,
+            authorization: &Value,
+            messages: Vec<Value>,
+        ) -> Result<String, Box<dyn Error>> {
+            if self.failing {
+                return Err("out of gas".into());
+            }
+            let label = authorization["label"].as_str().unwrap().to_string();
+            self.sent.push((label, messages));
+
+            Ok(format!("tx{}", self.sent.len()))
+        }
+
+        async fn tick(&mut self) -> Result<String, Box<dyn Error>> {
+            self.ticks += 1;
+
+            Ok("tick".to_string())
+        }
+    }
+
+    fn program_config(id: u64) -> Value {
+        json!({
+            "id": id,
+            "authorizations": [
+                {"label": "forward", "mode": {"permissioned": {"without_call_limit": [OPERATOR]}}},
+                {"label": "claim", "mode": "permissionless"},
+                {"label": "admin", "mode": {"permissioned": {"with_call_limit": [["neutron1admin", "10"]]}}},
+            ]
+        })
+    }
+
+    fn schedule(tasks: Value) -> OperatorSchedule {
+        serde_json::from_value(json!({
+            "poll_interval": "10m",
+            "max_retries": 2,
+            "retry_delay": "5m",
+            "tasks": tasks,
+        }))
+        .unwrap()
+    }
+
+    fn forward_task() -> Value {
+        json!({"label": "forward", "every": "1d", "messages": [{"forward": {}}]})
+    }
+
+    fn operator(tasks: Value) -> Operator<TestClient> {
+        Operator::new(schedule(tasks), program_config(1), TestClient::new(), None).unwrap()
+    }
+
+    /// Labels of the tasks run after waiting the given seconds
+    async fn run_after(operator: &mut Operator<TestClient>, seconds: u64) -> Vec<String> {
+        operator.client.wait(seconds).await;
+        operator
+            .run_due()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|run| run.label)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn due_tasks_run_until_their_next_run() {
+        let mut operator = operator(json!([
+            forward_task(),
+            {"label": "claim", "every": "7d", "messages": [{"claim": {}}], "tick": false},
+        ]));
+
+        assert_eq!(run_after(&mut operator, 0).await, vec!["forward", "claim"]);
+        assert_eq!(
+            operator.client.sent[0],
+            ("forward".to_string(), vec![json!({"forward": {}})])
+        );
+        assert_eq!(operator.client.ticks, 1);
+
+        assert!(run_after(&mut operator, 86_399).await.is_empty());
+        assert_eq!(run_after(&mut operator, 1).await, vec!["forward"]);
+        assert_eq!(
+            run_after(&mut operator, 6 * 86_400).await,
+            vec!["forward", "claim"]
+        );
+        assert_eq!(operator.client.ticks, 3);
+    }
+
+    #[tokio::test]
+    async fn failed_tasks_are_retried_then_wait_for_their_next_run() {
+        let mut operator = operator(json!([forward_task()]));
+        operator.client.failing = true;
+
+        assert_eq!(run_after(&mut operator, 0).await, vec!["forward"]);
+        assert_eq!(operator.state.tasks["forward"].failures, 1);
+        assert!(run_after(&mut operator, 299).await.is_empty());
+        assert_eq!(run_after(&mut operator, 1).await, vec!["forward"]);
+
+        // Out of retries
+        assert_eq!(operator.state.tasks["forward"].failures, 2);
+        assert!(run_after(&mut operator, 300).await.is_empty());
+
+        operator.client.failing = false;
+        assert_eq!(
+            run_after(&mut operator, 86_400 - 300).await,
+            vec!["forward"]
+        );
+        let state = &operator.state.tasks["forward"];
+        assert_eq!(state.failures, 0);
+        assert_eq!(state.last_error, None);
+        assert_eq!(state.last_tx.as_deref(), Some("tx1"));
+    }
+
+    #[tokio::test]
+    async fn unmet_preconditions_skip_the_task_until_the_next_poll() {
+        let mut operator = operator(json!([{
+            "label": "forward",
+            "every": "1d",
+            "messages": [],
+            "preconditions": [{"account": "input_account", "denom": "untrn", "min_balance": "100"}],
+        }]));
+        operator
+            .client
+            .balances
+            .insert("neutron1input".to_string(), 99);
+
+        let runs = operator.run_due().await.unwrap();
+        let TaskOutcome::Skipped { reason } = &runs[0].outcome else {
+            panic!("{:?}", runs[0].outcome);
+        };
+        assert_eq!(reason, "input_account has 99untrn, needs 100");
+
+        operator
+            .client
+            .balances
+            .insert("neutron1input".to_string(), 100);
+        assert_eq!(run_after(&mut operator, 600).await, vec!["forward"]);
+        assert_eq!(operator.client.sent.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn state_is_persisted_for_the_same_deployment() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("operator-state.json");
+        let new_operator = |id: u64| {
+            Operator::new(
+                schedule(json!([forward_task()])),
+                program_config(id),
+                TestClient::new(),
+                Some(path.clone()),
+            )
+            .unwrap()
+        };
+
+        let mut operator = new_operator(1);
+        assert_eq!(run_after(&mut operator, 0).await, vec!["forward"]);
+
+        // Restarted at the same time, the task already ran
+        let mut operator = new_operator(1);
+        assert_eq!(
+            operator.state.tasks["forward"].last_tx.as_deref(),
+            Some("tx1")
+        );
+        assert!(run_after(&mut operator, 0).await.is_empty());
+
+        // A new deployment starts from a fresh state
+        let mut operator = new_operator(2);
+        assert_eq!(run_after(&mut operator, 0).await, vec!["forward"]);
+    }
+
+    #[tokio::test]
+    async fn dry_run_sends_nothing() {
+        let mut operator = operator(json!([forward_task()])).with_dry_run(true);
+
+        for _ in 0..2 {
+            let runs = operator.run_due().await.unwrap();
+            let TaskOutcome::DryRun { messages } = &runs[0].outcome else {
+                panic!("{:?}", runs[0].outcome);
+            };
+            assert_eq!(messages, &vec![json!({"forward": {}})]);
+        }
+
+        assert!(operator.client.sent.is_empty());
+        assert_eq!(operator.client.ticks, 0);
+        assert_eq!(operator.state.tasks["forward"].last_attempt, None);
+    }
+
+    #[test]
+    fn unrepresentable_durations_are_rejected() {
+        let error = Operator::new(
+            schedule(json!([{"label": "forward", "every": "100000000mo"}])),
+            program_config(1),
+            TestClient::new(),
+            None,
+        )
+        .err()
+        .unwrap();
+
+        assert!(error
+            .to_string()
+            .starts_with("Invalid task forward: 3000000000 days after"));
+    }
+
+    #[test]
+    fn tasks_need_an_authorization_the_client_operates() {
+        let new_operator = |label: &str| {
+            Operator::new(
+                schedule(json!([{"label": label, "every": "1d"}])),
+                program_config(1),
+                TestClient::new(),
+                None,
+            )
+            .err()
+            .map(|e| e.to_string())
+        };
+
+        assert_eq!(new_operator("forward"), None);
+        assert_eq!(new_operator("claim"), None);
+        assert_eq!(
+            new_operator("admin"),
+            Some("neutron1operator is not an operator of authorization admin".to_string())
+        );
+        assert_eq!(
+            new_operator("stake"),
+            Some("Authorization stake not found".to_string())
+        );
+    }
+}
@@ -0,0 +1,627 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{
+    fees::{get_registry_chain, FeeEstimate},
+    querier::ChainQuerier,
+    verify::{get_domain_chain, library_contract_name, Queriers},
+};
+
+/// Something that would make the deployment fail, found before anything is instantiated
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightIssue {
+    /// Ex: `code_id`, `registry`, `external_contract`, `balance`
+    pub check: String,
+    pub message: String,
+}
+
+impl Display for PreflightIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.check, self.message)
+    }
+}
+
+/// Balance of the deployer on a chain against the fees it will pay there
+#[derive(Debug, Clone, Serialize)]
+pub struct DeployerBalance {
+    pub address: String,
+    pub balance: u128,
+    pub estimate: FeeEstimate,
+}
+
+/// Checks of a raw program config against the chains it is about to be deployed on
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightReport {
+    pub balances: Vec<DeployerBalance>,
+    /// What was checked successfully, Ex: `code id 3255 of valence_base_account on neutron`
+    pub passed: Vec<String>,
+    pub issues: Vec<PreflightIssue>,
+}
+
+impl PreflightReport {
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl Display for PreflightReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Preflight checks:")?;
+        for balance in &self.balances {
+            writeln!(
+                f,
+                "    Deployer {} has {}{}, estimated fees on {}",
+                balance.address, balance.balance, balance.estimate.denom, balance.estimate
+            )?;
+        }
+        for passed in &self.passed {
+            writeln!(f, "    OK {}", passed)?;
+        }
+        for issue in &self.issues {
+            writeln!(f, "    FAIL {}", issue)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs the checks and collects their outcome, a failing query is an issue
+struct Preflight<'a> {
+    manager_config: &'a Value,
+    queriers: &'a Queriers,
+    passed: Vec<String>,
+    issues: Vec<PreflightIssue>,
+}
+
+/// Check the deployer can pay the highest estimated fees on every chain, every code id the program
+/// needs and the registry exist, and the external contracts the libraries use are what they
+/// are expected to be
+pub async fn preflight(
+    program_config: &Value,
+    manager_config: &Value,
+    fees: Vec<FeeEstimate>,
+    deployer_addresses: &BTreeMap<String, String>,
+    queriers: &Queriers,
+) -> PreflightReport {
+    let mut preflight = Preflight {
+        manager_config,
+        queriers,
+        passed: vec![],
+        issues: vec![],
+    };
+
+    let balances = preflight.check_balances(fees, deployer_addresses).await;
+    preflight.check_code_ids(program_config).await;
+    preflight.check_registry().await;
+    preflight.check_external_contracts(program_config).await;
+
+    PreflightReport {
+        balances,
+        passed: preflight.passed,
+        issues: preflight.issues,
+    }
+}
+
+/// Denoms of an Astroport pair, Ex: `{"asset_infos": [{"native_token": {"denom": "untrn"}}, ..]}`
+fn get_pair_denoms(pair: &Value) -> BTreeSet<String> {
+    pair["asset_infos"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|info| {
+            info.pointer("/native_token/denom")
+                .or_else(|| info.pointer("/token/contract_addr"))
+                .and_then(|d| d.as_str())
+                .map(|d| d.to_string())
+        })
+        .collect()
+}
+
+impl Preflight<'_> {
+    fn issue(&mut self, check: &str, message: String) {
+        self.issues.push(PreflightIssue {
+            check: check.to_string(),
+            message,
+        });
+    }
+
+    fn querier(&mut self, check: &str, chain: &str) -> Option<&'_ dyn ChainQuerier> {
+        match self.queriers.get(chain) {
+            Some(querier) => Some(querier.as_ref()),
+            None => {
+                self.issue(check, format!("no querier for chain {}", chain));
+                None
+            }
+        }
+    }
+
+    fn code_id(&self, chain: &str, contract: &str) -> Option<u64> {
+        self.manager_config
+            .pointer(&format!("/contracts/code_ids/{}/{}", chain, contract))
+            .and_then(|c| match c {
+                Value::Number(n) => n.as_u64(),
+                Value::String(s) => s.parse().ok(),
+                _ => None,
+            })
+    }
+
+    async fn check_balances(
+        &mut self,
+        fees: Vec<FeeEstimate>,
+        deployer_addresses: &BTreeMap<String, String>,
+    ) -> Vec<DeployerBalance> {
+        let mut balances = vec![];
+
+        for estimate in fees {
+            let Some(address) = deployer_addresses.get(&estimate.chain).cloned() else {
+                self.issue(
+                    "balance",
+                    format!("no deployer address on {}", estimate.chain),
+                );
+                continue;
+            };
+            let Some(fee) = estimate.max_fee else {
+                self.issue(
+                    "balance",
+                    format!("no gas price for {} in the manager config", estimate.chain),
+                );
+                continue;
+            };
+            let Some(querier) = self.querier("balance", &estimate.chain) else {
+                continue;
+            };
+
+            match querier.balance(&address, &estimate.denom).await {
+                Ok(balance) => {
+                    if balance < fee {
+                        self.issue(
+                            "balance",
+                            format!(
+                                "deployer {} has {}{} but the deployment may cost up to {}{}",
+                                address, balance, estimate.denom, fee, estimate.denom
+                            ),
+                        );
+                    }
+                    balances.push(DeployerBalance {
+                        address,
+                        balance,
+                        estimate,
+                    });
+                }
+                Err(e) => self.issue(
+                    "balance",
+                    format!("failed to query the balance of {}: {}", address, e),
+                ),
+            }
+        }
+
+        balances
+    }
+
+    /// Every contract the program manager instantiates must have a code id that exists on its chain
+    async fn check_code_ids(&mut self, program_config: &Value) {
+        let main_chain = get_registry_chain(self.manager_config);
+        let mut contracts = BTreeSet::from([(main_chain, "valence_authorization".to_string())]);
+
+        let entries = |key: &str| {
+            program_config
+                .get(key)
+                .and_then(|e| e.as_object())
+                .into_iter()
+                .flat_map(|e| e.values())
+        };
+        for account in entries("accounts") {
+            let contract = match account["ty"].as_object().and_then(|ty| ty.keys().next()) {
+                Some(ty) if ty == "Base" => "valence_base_account",
+                Some(ty) if ty == "Storage" => "valence_storage_account",
+                _ => continue,
+            };
+            if let Some(chain) = get_domain_chain(&account["domain"]) {
+                contracts.insert((chain, contract.to_string()));
+            }
+        }
+        for library in entries("libraries") {
+            let variant = library["config"].as_object().and_then(|c| c.keys().next());
+            if let (Some(variant), Some(chain)) = (variant, get_domain_chain(&library["domain"])) {
+                contracts.insert((chain.clone(), library_contract_name(variant)));
+                contracts.insert((chain, "valence_processor".to_string()));
+            }
+        }
+
+        for (chain, contract) in contracts {
+            let Some(code_id) = self.code_id(&chain, &contract) else {
+                self.issue(
+                    "code_id",
+                    format!(
+                        "no code id for {} on {} in the manager config",
+                        contract, chain
+                    ),
+                );
+                continue;
+            };
+            let Some(querier) = self.querier("code_id", &chain) else {
+                continue;
+            };
+
+            match querier.code_info(code_id).await {
+                Ok(_) => self
+                    .passed
+                    .push(format!("code id {} of {} on {}", code_id, contract, chain)),
+                Err(e) => self.issue(
+                    "code_id",
+                    format!("code id {} of {} on {}: {}", code_id, contract, chain, e),
+                ),
+            }
+        }
+    }
+
+    /// The registry must be a contract of the registry code id, the program id is reserved there first
+    async fn check_registry(&mut self) {
+        let Some(registry_addr) = self
+            .manager_config
+            .pointer("/general/registry_addr")
+            .and_then(|r| r.as_str())
+            .map(|r| r.to_string())
+        else {
+            self.issue(
+                "registry",
+                "no registry_addr in the manager config".to_string(),
+            );
+            return;
+        };
+        let chain = get_registry_chain(self.manager_config);
+        let expected_code_id = self.code_id(&chain, "valence_program_registry");
+        let Some(querier) = self.querier("registry", &chain) else {
+            return;
+        };
+
+        match querier.contract_info(&registry_addr).await {
+            Ok(info) if expected_code_id.is_some_and(|c| c != info.code_id) => self.issue(
+                "registry",
+                format!(
+                    "{} has code id {}, expected the registry code id {}",
+                    registry_addr,
+                    info.code_id,
+                    expected_code_id.unwrap_or_default()
+                ),
+            ),
+            Ok(_) => self.passed.push(format!("registry {}", registry_addr)),
+            Err(e) => self.issue("registry", format!("{}: {}", registry_addr, e)),
+        }
+    }
+
+    /// Astroport pools must trade the denoms of the library and Drop cores must bond the denom of the library
+    async fn check_external_contracts(&mut self, program_config: &Value) {
+        let libraries = program_config
+            .get("libraries")
+            .and_then(|l| l.as_object())
+            .cloned()
+            .unwrap_or_default();
+        // The same contract is often used by several libraries
+        let mut checked = BTreeSet::new();
+
+        for library in libraries.values() {
+            let name = library["name"].as_str().unwrap_or_default();
+            let chain = get_domain_chain(&library["domain"]).unwrap_or_default();
+            let Some((variant, config)) =
+                library["config"].as_object().and_then(|c| c.iter().next())
+            else {
+                continue;
+            };
+
+            // Unstaking goes through contracts we only need to exist
+            if variant == "ValenceDropLiquidUnstaker" {
+                for key in ["withdrawal_manager_addr", "voucher_addr"] {
+                    let addr = config[key].as_str().unwrap_or_default().to_string();
+                    if !checked.insert((addr.clone(), String::new())) {
+                        continue;
+                    }
+                    let Some(querier) = self.querier("external_contract", &chain) else {
+                        continue;
+                    };
+
+                    match querier.contract_info(&addr).await {
+                        Ok(_) => self.passed.push(format!("{} {} of {}", key, addr, name)),
+                        Err(e) => self.issue(
+                            "external_contract",
+                            format!("{} {} of {}: {}", key, addr, name, e),
+                        ),
+                    }
+                }
+            }
+
+            let (addr, query, expected) = match variant.as_str() {
+                "ValenceAstroportLper" | "ValenceAstroportWithdrawer" => {
+                    let asset_data = config
+                        .pointer("/lp_config/asset_data")
+                        .or_else(|| config.pointer("/withdrawer_config/asset_data"));
+                    let denoms = ["asset1", "asset2"]
+                        .iter()
+                        .filter_map(|asset| asset_data?.get(*asset)?.as_str())
+                        .map(|d| d.to_string())
+                        .collect::<BTreeSet<_>>();
+
+                    (&config["pool_addr"], json!({"pair": {}}), json!(denoms))
+                }
+                "ValenceDropLiquidStaker" => (
+                    &config["liquid_staker_addr"],
+                    json!({"config": {}}),
+                    config["denom"].clone(),
+                ),
+                "ValenceDropLiquidUnstaker" => (
+                    &config["liquid_unstaker_addr"],
+                    json!({"config": {}}),
+                    config["denom"].clone(),
+                ),
+                _ => continue,
+            };
+            let addr = addr.as_str().unwrap_or_default().to_string();
+            if !checked.insert((addr.clone(), expected.to_string())) {
+                continue;
+            }
+            let Some(querier) = self.querier("external_contract", &chain) else {
+                continue;
+            };
+
+            let response = match querier.smart_query(&addr, &query).await {
+                Ok(response) => response,
+                Err(e) => {
+                    self.issue(
+                        "external_contract",
+                        format!("{} of {} doesn't answer {}: {}", addr, name, query, e),
+                    );
+                    continue;
+                }
+            };
+
+            let (actual, ok) = match variant.as_str() {
+                "ValenceDropLiquidStaker" => {
+                    let actual = response["base_denom"].clone();
+                    (actual.clone(), actual == expected)
+                }
+                "ValenceDropLiquidUnstaker" => {
+                    let actual = response["token_denom"].clone();
+                    (actual.clone(), actual == expected)
+                }
+                _ => {
+                    let actual = get_pair_denoms(&response);
+                    let ok = expected
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .all(|d| d.as_str().is_some_and(|d| actual.contains(d)));
+                    (json!(actual), ok)
+                }
+            };
+
+            if ok {
+                self.passed
+                    .push(format!("{} of {} holds {}", addr, name, expected));
+            } else {
+                self.issue(
+                    "external_contract",
+                    format!(
+                        "{} of {} holds {}, expected {}",
+                        addr, name, actual, expected
+                    ),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::{json, Value};
+
+    use super::{preflight, PreflightReport};
+    use crate::{
+        fees::{FeeEstimate, MessageCounts},
+        querier::{CodeInfo, ContractInfo, MockQuerier},
+        verify::Queriers,
+    };
+
+    const CODE_IDS: [(&str, u64); 6] = [
+        ("valence_authorization", 1),
+        ("valence_base_account", 2),
+        ("valence_forwarder_library", 3),
+        ("valence_astroport_lper", 4),
+        ("valence_processor", 5),
+        ("valence_program_registry", 6),
+    ];
+
+    fn program_config() -> Value {
+        let domain = json!({"CosmosCosmwasm": "neutron"});
+
+        json!({
+            "accounts": {"1": {"name": "input_account", "ty": {"Base": {"admin": null}}, "domain": domain}},
+            "libraries": {
+                "1": {
+                    "name": "forwarder",
+                    "domain": domain,
+                    "config": {"ValenceForwarderLibrary": {}},
+                },
+                "2": {
+                    "name": "lper",
+                    "domain": domain,
+                    "config": {"ValenceAstroportLper": {
+                        "pool_addr": "neutron1pool",
+                        "lp_config": {"asset_data": {"asset1": "untrn", "asset2": "uatom"}},
+                    }},
+                },
+            },
+        })
+    }
+
+    fn manager_config() -> Value {
+        json!({
+            "general": {"registry_addr": "neutron1registry"},
+            "chains": {"neutron": {"prefix": "neutron"}},
+            "contracts": {"code_ids": {"neutron": BTreeMap::from(CODE_IDS)}},
+        })
+    }
+
+    /// Chain where every code exists, the registry is one and the pool trades the denoms of the lper
+    fn chain() -> MockQuerier {
+        let querier = CODE_IDS
+            .iter()
+            .fold(MockQuerier::new(), |querier, (_, code_id)| {
+                querier.with_code(CodeInfo {
+                    code_id: *code_id,
+                    ..Default::default()
+                })
+            });
+
+        querier
+            .with_contract(
+                "neutron1registry",
+                ContractInfo {
+                    code_id: 6,
+                    ..Default::default()
+                },
+            )
+            .with_response(
+                "neutron1pool",
+                json!({"pair": {}}),
+                json!({"asset_infos": [
+                    {"native_token": {"denom": "uatom"}},
+                    {"native_token": {"denom": "untrn"}},
+                ]}),
+            )
+            .with_balance("neutron1deployer", "untrn", 1_000_000)
+    }
+
+    async fn check(manager_config: &Value, chain: Option<MockQuerier>) -> PreflightReport {
+        let fees = vec![FeeEstimate {
+            chain: "neutron".to_string(),
+            messages: MessageCounts::default(),
+            min_gas: 0,
+            max_gas: 0,
+            denom: "untrn".to_string(),
+            min_fee: Some(500_000),
+            max_fee: Some(1_000_000),
+            deployments: 0,
+        }];
+        let deployer_addresses =
+            BTreeMap::from([("neutron".to_string(), "neutron1deployer".to_string())]);
+        let queriers: Queriers = chain
+            .into_iter()
+            .map(|chain| ("neutron".to_string(), Box::new(chain) as _))
+            .collect();
+
+        preflight(
+            &program_config(),
+            manager_config,
+            fees,
+            &deployer_addresses,
+            &queriers,
+        )
+        .await
+    }
+
+    fn issues(report: &PreflightReport) -> Vec<String> {
+        report.issues.iter().map(|i| i.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn ready_deployment_passes() {
+        let report = check(&manager_config(), Some(chain())).await;
+
+        assert!(report.is_ok(), "{:?}", issues(&report));
+        assert_eq!(report.balances[0].balance, 1_000_000);
+        // Every code id, the registry and the pool
+        assert_eq!(report.passed.len(), 7);
+        assert!(report
+            .passed
+            .contains(&"code id 3 of valence_forwarder_library on neutron".to_string()));
+    }
+
+    #[tokio::test]
+    async fn balance_below_the_highest_fee_fails() {
+        let chain = chain().with_balance("neutron1deployer", "untrn", 999_999);
+        let report = check(&manager_config(), Some(chain)).await;
+
+        assert_eq!(
+            issues(&report),
+            vec!["balance: deployer neutron1deployer has 999999untrn but the deployment may cost up to 1000000untrn"]
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_code_ids_fail() {
+        let mut manager_config = manager_config();
+        manager_config["contracts"]["code_ids"]["neutron"]
+            .as_object_mut()
+            .unwrap()
+            .remove("valence_base_account");
+        // Set but not stored on the chain
+        manager_config["contracts"]["code_ids"]["neutron"]["valence_processor"] = json!(50);
+
+        let report = check(&manager_config, Some(chain())).await;
+
+        assert_eq!(
+            issues(&report),
+            vec![
+                "code_id: no code id for valence_base_account on neutron in the manager config",
+                "code_id: code id 50 of valence_processor on neutron: Code 50 not found",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn registry_of_another_code_fails() {
+        let chain = chain().with_contract(
+            "neutron1registry",
+            ContractInfo {
+                code_id: 2,
+                ..Default::default()
+            },
+        );
+        let report = check(&manager_config(), Some(chain)).await;
+
+        assert_eq!(
+            issues(&report),
+            vec!["registry: neutron1registry has code id 2, expected the registry code id 6"]
+        );
+    }
+
+    #[tokio::test]
+    async fn pool_of_other_denoms_fails() {
+        let chain = chain().with_response(
+            "neutron1pool",
+            json!({"pair": {}}),
+            json!({"asset_infos": [
+                {"native_token": {"denom": "untrn"}},
+                {"native_token": {"denom": "uosmo"}},
+            ]}),
+        );
+        let report = check(&manager_config(), Some(chain)).await;
+
+        assert_eq!(
+            issues(&report),
+            vec![
+                r#"external_contract: neutron1pool of lper holds ["untrn","uosmo"], expected ["uatom","untrn"]"#
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn unreachable_chain_fails_every_check() {
+        let report = check(&manager_config(), None).await;
+
+        assert!(report.balances.is_empty());
+        assert!(report.passed.is_empty());
+        // Balance, 5 code ids, registry and pool
+        assert_eq!(report.issues.len(), 8);
+        assert!(issues(&report)
+            .iter()
+            .all(|issue| issue.ends_with("no querier for chain neutron")));
+    }
+}
@@ -0,0 +1,566 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use k256::ecdsa::{
+    signature::{Signer as _, Verifier as _},
+    Signature, SigningKey, VerifyingKey,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    amount_checks::get_denom,
+    definition::is_address,
+    denoms::DenomRegistry,
+    error::DeployerError,
+    helpers::{derive_wallet_key, get_public_key},
+};
+
+/// Envs deployments are gated on, from the `[production]` table of `deployer.toml`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProductionConfig {
+    #[serde(default = "default_production_envs")]
+    pub envs: Vec<String>,
+    /// Approvals of distinct reviewers a plan needs, the deployer's own approval doesn't count
+    #[serde(default)]
+    pub required_approvals: usize,
+    #[serde(default)]
+    pub reviewers: Vec<Reviewer>,
+}
+
+impl Default for ProductionConfig {
+    fn default() -> Self {
+        ProductionConfig {
+            envs: default_production_envs(),
+            required_approvals: 0,
+            reviewers: vec![],
+        }
+    }
+}
+
+fn default_production_envs() -> Vec<String> {
+    vec!["mainnet".to_string()]
+}
+
+impl ProductionConfig {
+    pub fn is_production(&self, env: &str) -> bool {
+        self.envs.iter().any(|e| e.eq_ignore_ascii_case(env))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Reviewer {
+    pub name: String,
+    /// hex of the compressed secp256k1 public key, as printed by `approve`
+    pub public_key: String,
+}
+
+/// What was reviewed before deploying on a production env
+#[derive(Debug, Clone, Default)]
+pub struct Review {
+    /// Plan saved with `plan --out`, the program config deployed must be the one of the plan
+    pub plan: Option<PathBuf>,
+    /// Approvals of the saved plan, see `approve`
+    pub approvals: Vec<PathBuf>,
+    /// The summary of the plan was shown and confirmed
+    pub confirmed: bool,
+}
+
+/// The fields of a saved plan a deployment is checked against
+#[derive(Debug, Clone, Deserialize)]
+pub struct SavedPlan {
+    pub program: String,
+    pub env: String,
+    pub config_hash: String,
+}
+
+impl SavedPlan {
+    pub fn read(path: &Path) -> Result<Self, DeployerError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| DeployerError::Path(format!("Plan {}: {}", path.display(), e)))?;
+
+        serde_json::from_str(&content).map_err(|e| {
+            DeployerError::Validation(format!("Invalid plan {}: {}", path.display(), e))
+        })
+    }
+}
+
+/// Signature of a saved plan by a reviewer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Approval {
+    pub reviewer: String,
+    /// hex of the compressed secp256k1 public key
+    pub public_key: String,
+    pub program: String,
+    pub env: String,
+    pub config_hash: String,
+    /// hex of the ecdsa signature of the approval message
+    pub signature: String,
+}
+
+impl Approval {
+    fn message(program: &str, env: &str, config_hash: &str) -> String {
+        format!(
+            "Approve deploying {} on {} with program config {}",
+            program, env, config_hash
+        )
+    }
+
+    pub fn sign(plan: &SavedPlan, reviewer: &str, mnemonic: &str) -> Result<Self, DeployerError> {
+        let key = get_reviewer_key(mnemonic)?;
+        let signature: Signature =
+            key.sign(Approval::message(&plan.program, &plan.env, &plan.config_hash).as_bytes());
+
+        Ok(Approval {
+            reviewer: reviewer.to_string(),
+            public_key: get_public_key(&key),
+            program: plan.program.clone(),
+            env: plan.env.clone(),
+            config_hash: plan.config_hash.clone(),
+            signature: hex::encode(signature.to_bytes()),
+        })
+    }
+
+    /// Check the signature is from the public key of the approval
+    pub fn verify(&self) -> Result<(), DeployerError> {
+        let invalid = || {
+            DeployerError::Validation(format!("Invalid approval signature of {}", self.reviewer))
+        };
+
+        let public_key = hex::decode(&self.public_key).map_err(|_| invalid())?;
+        let signature = hex::decode(&self.signature).map_err(|_| invalid())?;
+        let key = VerifyingKey::from_sec1_bytes(&public_key).map_err(|_| invalid())?;
+        let signature = Signature::from_slice(&signature).map_err(|_| invalid())?;
+
+        key.verify(
+            Approval::message(&self.program, &self.env, &self.config_hash).as_bytes(),
+            &signature,
+        )
+        .map_err(|_| invalid())
+    }
+
+    pub fn read(path: &Path) -> Result<Self, DeployerError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| DeployerError::Path(format!("Approval {}: {}", path.display(), e)))?;
+
+        serde_json::from_str(&content).map_err(|e| {
+            DeployerError::Validation(format!("Invalid approval {}: {}", path.display(), e))
+        })
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), DeployerError> {
+        let content =
+            serde_json::to_string_pretty(self).map_err(|e| DeployerError::Output(e.to_string()))?;
+
+        std::fs::write(path, content)
+            .map_err(|e| DeployerError::Output(format!("{}: {}", path.display(), e)))
+    }
+}
+
+/// Reviewers sign with the key of their cosmos wallet
+fn get_reviewer_key(mnemonic: &str) -> Result<SigningKey, DeployerError> {
+    derive_wallet_key(mnemonic)
+        .map_err(|e| DeployerError::Signer(format!("Failed to derive the key: {}", e)))
+}
+
+/// Everything that must hold to deploy on a production env, checked before anything is instantiated
+pub(crate) struct ProductionGate<'a> {
+    pub config: &'a ProductionConfig,
+    pub program: &'a str,
+    pub env: &'a str,
+    pub explicit_env: bool,
+    pub dirty_tree: bool,
+    pub config_hash: &'a str,
+    pub review: &'a Review,
+    /// Approvals by the deployer's own key don't count
+    pub mnemonic: &'a str,
+}
+
+impl ProductionGate<'_> {
+    pub fn check(&self) -> Result<(), DeployerError> {
+        let mut issues = vec![];
+
+        if !self.explicit_env {
+            issues.push(format!("{} must be chosen explicitly with --env", self.env));
+        }
+        if self.dirty_tree {
+            issues.push("the git tree has uncommitted changes".to_string());
+        }
+        if !self.review.confirmed {
+            issues.push("the plan summary wasn't confirmed".to_string());
+        }
+
+        match &self.review.plan {
+            None => issues.push("no reviewed plan, save one with `plan --out`".to_string()),
+            Some(path) => {
+                let plan = SavedPlan::read(path)?;
+                if plan.program != self.program || plan.env != self.env {
+                    issues.push(format!(
+                        "reviewed plan is for {} on {}",
+                        plan.program, plan.env
+                    ));
+                } else if plan.config_hash != self.config_hash {
+                    issues.push(format!(
+                        "program config {} is not the one of the reviewed plan {}",
+                        self.config_hash, plan.config_hash
+                    ));
+                }
+            }
+        }
+
+        let approvers = self.get_approvers(&mut issues)?;
+        if approvers.len() < self.config.required_approvals {
+            issues.push(format!(
+                "{} approvals of {} required",
+                approvers.len(),
+                self.config.required_approvals
+            ));
+        }
+
+        if !issues.is_empty() {
+            return Err(DeployerError::Validation(format!(
+                "Refusing to deploy on production env {}: {}",
+                self.env,
+                issues.join(", ")
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Names of the listed reviewers with a valid approval of the program config
+    fn get_approvers(&self, issues: &mut Vec<String>) -> Result<BTreeSet<String>, DeployerError> {
+        let mut approvers = BTreeSet::new();
+        if self.review.approvals.is_empty() {
+            return Ok(approvers);
+        }
+
+        // Best effort, the mnemonic might not be one reviewer keys can be derived from
+        let deployer_key = get_reviewer_key(self.mnemonic)
+            .map(|key| get_public_key(&key))
+            .unwrap_or_default();
+
+        for path in &self.review.approvals {
+            let approval = Approval::read(path)?;
+            let reviewer = self.config.reviewers.iter().find(|reviewer| {
+                reviewer
+                    .public_key
+                    .eq_ignore_ascii_case(&approval.public_key)
+            });
+
+            let issue = match reviewer {
+                None => Some("is not signed by a listed reviewer"),
+                Some(_) if approval.public_key.eq_ignore_ascii_case(&deployer_key) => {
+                    Some("is signed by the deployer")
+                }
+                Some(_) if approval.verify().is_err() => Some("has an invalid signature"),
+                Some(_)
+                    if approval.program != self.program
+                        || approval.env != self.env
+                        || approval.config_hash != self.config_hash =>
+                {
+                    Some("is for another program config")
+                }
+                Some(_) => None,
+            };
+
+            match (issue, reviewer) {
+                (Some(issue), _) => issues.push(format!("approval {} {}", path.display(), issue)),
+                (None, Some(reviewer)) => {
+                    approvers.insert(reviewer.name.clone());
+                }
+                (None, None) => (),
+            }
+        }
+
+        Ok(approvers)
+    }
+}
+
+/// What a deployment puts on chain that a human should look at before confirming
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DeploymentSummary {
+    pub owner: String,
+    /// Address to the labels of the authorizations it can execute
+    pub operators: BTreeMap<String, BTreeSet<String>>,
+    /// Amounts in the library configs, Ex: `forwarder max_amount: 6500000000000untrn (6.5M NTRN)`
+    pub amounts: Vec<String>,
+}
+
+impl DeploymentSummary {
+    pub(crate) fn new(program_config: &Value, registry: &DenomRegistry) -> Self {
+        let mut operators: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for authorization in program_config["authorizations"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+        {
+            let label = authorization["label"].as_str().unwrap_or_default();
+            let mut addresses = vec![];
+            collect_addresses(&authorization["mode"], &mut addresses);
+
+            for address in addresses {
+                operators
+                    .entry(address)
+                    .or_default()
+                    .insert(label.to_string());
+            }
+        }
+
+        let mut amounts = vec![];
+        for library in program_config["libraries"]
+            .as_object()
+            .map(|l| l.values().cloned().collect::<Vec<_>>())
+            .unwrap_or_default()
+        {
+            let name = library["name"].as_str().unwrap_or_default().to_string();
+            collect_amounts(&library["config"], "", &name, registry, &mut amounts);
+        }
+
+        DeploymentSummary {
+            owner: program_config["owner"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            operators,
+            amounts,
+        }
+    }
+}
+
+impl Display for DeploymentSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Owner: {}", self.owner)?;
+
+        writeln!(f, "Operators:")?;
+        for (address, labels) in &self.operators {
+            let labels = labels.iter().cloned().collect::<Vec<_>>();
+            writeln!(f, "    {}: {}", address, labels.join(", "))?;
+        }
+
+        writeln!(f, "Amounts:")?;
+        for amount in &self.amounts {
+            writeln!(f, "    {}", amount)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn collect_addresses(value: &Value, addresses: &mut Vec<String>) {
+    match value {
+        Value::String(s) if is_address(s) => addresses.push(s.clone()),
+        Value::Array(values) => values.iter().for_each(|v| collect_addresses(v, addresses)),
+        Value::Object(map) => map.values().for_each(|v| collect_addresses(v, addresses)),
+        _ => (),
+    }
+}
+
+/// Integer amounts of fields named like `*amount*`, with the closest denom around them
+fn collect_amounts(
+    value: &Value,
+    denom: &str,
+    library: &str,
+    registry: &DenomRegistry,
+    amounts: &mut Vec<String>,
+) {
+    match value {
+        Value::Object(map) => {
+            let denom = map
+                .get("denom")
+                .map(get_denom)
+                .unwrap_or_else(|| denom.to_string());
+
+            for (key, value) in map {
+                // Splitter amounts are wrapped, Ex: {"fixed_amount": "100"}
+                let amount = match value {
+                    Value::Object(inner) if inner.len() == 1 => inner.values().next(),
+                    value => Some(value),
+                }
+                .and_then(|amount| amount.as_str()?.parse::<u128>().ok());
+
+                match amount {
+                    Some(amount) if key.contains("amount") => amounts.push(format!(
+                        "{} {}: {}",
+                        library,
+                        key,
+                        registry.display_amount(amount, &denom)
+                    )),
+                    _ => collect_amounts(value, &denom, library, registry, amounts),
+                }
+            }
+        }
+        Value::Array(values) => values
+            .iter()
+            .for_each(|v| collect_amounts(v, denom, library, registry, amounts)),
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    use super::{
+        get_reviewer_key, Approval, ProductionConfig, ProductionGate, Review, Reviewer, SavedPlan,
+    };
+    use crate::helpers::get_public_key;
+
+    const DEPLOYER: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
+    const REVIEWER: &str = "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote";
+    const CONFIG_HASH: &str = "c0ffee";
+
+    fn saved_plan(config_hash: &str) -> SavedPlan {
+        SavedPlan {
+            program: "gradual-ls".to_string(),
+            env: "mainnet".to_string(),
+            config_hash: config_hash.to_string(),
+        }
+    }
+
+    fn write_plan(dir: &Path, config_hash: &str) -> PathBuf {
+        let path = dir.join("plan.json");
+        let plan = saved_plan(config_hash);
+        let content = json!({
+            "program": plan.program,
+            "env": plan.env,
+            "config_hash": plan.config_hash,
+        });
+        std::fs::write(&path, content.to_string()).unwrap();
+        path
+    }
+
+    fn write_approval(dir: &Path, approval: &Approval) -> PathBuf {
+        let path = dir.join(format!("plan.{}.approval.json", approval.reviewer));
+        approval.write(&path).unwrap();
+        path
+    }
+
+    fn config(required_approvals: usize) -> ProductionConfig {
+        ProductionConfig {
+            required_approvals,
+            reviewers: [("alice", REVIEWER), ("deployer", DEPLOYER)]
+                .iter()
+                .map(|(name, mnemonic)| Reviewer {
+                    name: name.to_string(),
+                    public_key: get_public_key(&get_reviewer_key(mnemonic).unwrap()),
+                })
+                .collect(),
+            ..ProductionConfig::default()
+        }
+    }
+
+    fn check(config: &ProductionConfig, review: &Review) -> Result<(), String> {
+        ProductionGate {
+            config,
+            program: "gradual-ls",
+            env: "mainnet",
+            explicit_env: true,
+            dirty_tree: false,
+            config_hash: CONFIG_HASH,
+            review,
+            mnemonic: DEPLOYER,
+        }
+        .check()
+        .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn reviewed_and_approved_plan_passes() {
+        let dir = tempdir().unwrap();
+        let approval = Approval::sign(&saved_plan(CONFIG_HASH), "alice", REVIEWER).unwrap();
+        let review = Review {
+            plan: Some(write_plan(dir.path(), CONFIG_HASH)),
+            approvals: vec![write_approval(dir.path(), &approval)],
+            confirmed: true,
+        };
+
+        let result = check(&config(1), &review);
+
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn unreviewed_deployment_is_refused() {
+        let review = Review::default();
+
+        let e = ProductionGate {
+            config: &config(0),
+            program: "gradual-ls",
+            env: "mainnet",
+            explicit_env: false,
+            dirty_tree: true,
+            config_hash: CONFIG_HASH,
+            review: &review,
+            mnemonic: DEPLOYER,
+        }
+        .check()
+        .unwrap_err();
+
+        assert_eq!(
+            e.to_string(),
+            "Refusing to deploy on production env mainnet: mainnet must be chosen explicitly with --env, \
+             the git tree has uncommitted changes, the plan summary wasn't confirmed, \
+             no reviewed plan, save one with `plan --out`"
+        );
+    }
+
+    #[test]
+    fn plan_of_another_program_config_is_refused() {
+        let dir = tempdir().unwrap();
+        let review = Review {
+            plan: Some(write_plan(dir.path(), "deadbeef")),
+            approvals: vec![],
+            confirmed: true,
+        };
+
+        let result = check(&config(0), &review);
+
+        assert_eq!(
+            result,
+            Err(
+                "Refusing to deploy on production env mainnet: program config c0ffee \
+                 is not the one of the reviewed plan deadbeef"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn invalid_approvals_dont_count() {
+        let dir = tempdir().unwrap();
+        let by_deployer = Approval::sign(&saved_plan(CONFIG_HASH), "deployer", DEPLOYER).unwrap();
+        let of_other_config = Approval::sign(&saved_plan("deadbeef"), "alice", REVIEWER).unwrap();
+        let tampered = Approval {
+            reviewer: "bob".to_string(),
+            config_hash: CONFIG_HASH.to_string(),
+            ..of_other_config.clone()
+        };
+        let review = Review {
+            plan: Some(write_plan(dir.path(), CONFIG_HASH)),
+            approvals: vec![
+                write_approval(dir.path(), &by_deployer),
+                write_approval(dir.path(), &of_other_config),
+                write_approval(dir.path(), &tampered),
+            ],
+            confirmed: true,
+        };
+
+        let result = check(&config(1), &review);
+
+        let e = result.unwrap_err();
+        assert!(e.contains("plan.deployer.approval.json is signed by the deployer"));
+        assert!(e.contains("plan.alice.approval.json is for another program config"));
+        assert!(e.contains("plan.bob.approval.json has an invalid signature"));
+        assert!(e.ends_with("0 approvals of 1 required"));
+    }
+}
@@ -1,6 +1,58 @@
+use std::{error::Error, path::Path};
+
+use serde_json::Value;
 use valence_program_manager::program_config::ProgramConfig;
 
-pub(crate) fn read_program_config_from_json(path: &str) -> ProgramConfig {
-    let content = std::fs::read_to_string(path).expect("Unable to open program config file");
-    serde_json::from_str::<ProgramConfig>(&content).expect("Failed to parse into ProgramConfig")
+use crate::{
+    definition::{build_program, ProgramDefinition},
+    error::DeployerError,
+};
+
+pub(crate) fn read_program_config_from_json(path: &Path) -> Result<ProgramConfig, DeployerError> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        DeployerError::Path(format!(
+            "Unable to open program config file {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+
+    serde_json::from_str::<ProgramConfig>(&content).map_err(|e| {
+        DeployerError::Validation(format!(
+            "Failed to parse {} into ProgramConfig: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// Read a program config as json from either `builder:<env>`, a program config json file
+/// or an output directory, in which case the most complete program config in it is used
+pub(crate) fn read_program_config_source(
+    source: &str,
+    program: &dyn ProgramDefinition,
+) -> Result<Value, Box<dyn Error>> {
+    if let Some(env) = source.strip_prefix("builder:") {
+        return Ok(serde_json::to_value(build_program(program, env)?)?);
+    }
+
+    let mut path = Path::new(source).to_path_buf();
+
+    if path.is_dir() {
+        path = ["instantiated", "debug", "raw"]
+            .iter()
+            .map(|prefix| path.join(format!("{}-program-config.json", prefix)))
+            .find(|p| p.exists())
+            .ok_or_else(|| DeployerError::Path(format!("No program config found in {}", source)))?;
+    }
+
+    let content = std::fs::read_to_string(&path).map_err(|e| {
+        DeployerError::Path(format!(
+            "Unable to open program config file {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+
+    Ok(serde_json::from_str(&content)?)
 }
@@ -1,32 +1,95 @@
 use config::Config as ConfigHelper;
-use std::{error::Error, path::PathBuf};
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
 
-pub fn get_program_params(
-    program_path: &PathBuf,
+use serde_json::Value;
+
+use crate::{denoms::DenomRegistry, error::DeployerError};
+
+/// Table of the params file with the amount checks of the program, see `check-amounts`
+pub(crate) const CHECKS_TABLE: &str = "checks";
+
+/// Path to the params file of the program for the given environment, the program path is taken
+/// as given, the deployer passes it joined to its workspace
+pub(crate) fn get_program_params_path(program_path: &Path, env: &str) -> PathBuf {
+    program_path
+        .join("program_params")
+        .join(format!("{}.toml", env))
+}
+
+/// Params of the environment as they are written, see `get_valid_params` to resolve the amounts
+pub fn get_program_params(program_path: &Path, env: &str) -> Result<ProgramParams, DeployerError> {
+    get_resolved_params(program_path, env, &[])
+}
+
+/// Params of the environment with the human amounts of the given params resolved
+pub(crate) fn get_resolved_params(
+    program_path: &Path,
     env: &str,
-) -> Result<ProgramParams, Box<dyn Error>> {
-    let params_path = std::env::current_dir()?
-        .join(program_path)
-        .join("program_params");
-    let params_path_str = params_path
-        .to_str()
-        .expect("Params path should be a string");
-    let params_env_path = params_path.join(format!("{}.toml", env));
+    amounts: &[&str],
+) -> Result<ProgramParams, DeployerError> {
+    let mut params = read_program_params(program_path, env)?;
 
-    if !params_env_path.exists() {
-        return Err(format!("Program params file not found: {}.toml", env).into());
+    // Amount checks are read by `check-amounts`, they are not params of the builder
+    if let Some(params) = params.as_object_mut() {
+        params.remove(CHECKS_TABLE);
     }
 
+    let invalid = |e: &dyn Error| {
+        DeployerError::Params(format!(
+            "Invalid program params {}.toml of {}: {}",
+            env,
+            program_path.display(),
+            e
+        ))
+    };
+
+    // Amount params can be written in whole tokens of the env denoms, Ex: "6.5M NTRN"
+    let registry = DenomRegistry::load(program_path, env)
+        .map_err(|e| DeployerError::categorize(e, DeployerError::Params))?;
+    let resolved = registry
+        .resolve_amounts(params, amounts)
+        .map_err(|e| invalid(e.as_ref()))?;
     let params = ConfigHelper::builder()
-        .add_source(config::File::with_name(&format!(
-            "{}/{}.toml",
-            params_path_str, env
-        )))
-        .build()?;
+        .add_source(config::File::from_str(
+            &resolved.to_string(),
+            config::FileFormat::Json,
+        ))
+        .build()
+        .map_err(|e| invalid(&e))?;
 
     Ok(ProgramParams::new(params))
 }
 
+/// Read the params file of the program for the environment as it is written, including the checks
+pub(crate) fn read_program_params(program_path: &Path, env: &str) -> Result<Value, DeployerError> {
+    let params_env_path = get_program_params_path(program_path, env);
+
+    if !params_env_path.exists() {
+        return Err(DeployerError::Path(format!(
+            "Program params file not found: {}",
+            params_env_path.display()
+        )));
+    }
+
+    let invalid = |e: &dyn Error| {
+        DeployerError::Params(format!(
+            "Invalid program params {}: {}",
+            params_env_path.display(),
+            e
+        ))
+    };
+
+    ConfigHelper::builder()
+        .add_source(config::File::from(params_env_path.as_path()))
+        .build()
+        .map_err(|e| invalid(&e))?
+        .try_deserialize()
+        .map_err(|e| invalid(&e))
+}
+
 #[derive(Debug)]
 pub struct ProgramParams {
     cfg: ConfigHelper,
@@ -37,18 +100,27 @@ impl ProgramParams {
         ProgramParams { cfg }
     }
 
-    pub fn get(&self, key: &str) -> String {
+    pub fn get(&self, key: &str) -> Result<String, DeployerError> {
         self.cfg
             .get::<String>(key)
-            .unwrap_or_else(|_| panic!("Key {} not found", key))
+            .map_err(|_| DeployerError::Params(format!("Key {} not found", key)))
     }
 
-    pub fn get_array(&self, key: &str) -> Vec<String> {
-        self.cfg
+    pub fn get_array(&self, key: &str) -> Result<Vec<String>, DeployerError> {
+        Ok(self
+            .cfg
             .get_array(key)
-            .unwrap_or_else(|_| panic!("Key {} not found", key))
+            .map_err(|_| DeployerError::Params(format!("Key {} not found", key)))?
             .iter()
             .map(|v| v.to_string())
-            .collect()
+            .collect())
+    }
+
+    /// All params as json, as they were resolved from the params file
+    pub fn to_json(&self) -> Result<serde_json::Value, DeployerError> {
+        self.cfg
+            .clone()
+            .try_deserialize()
+            .map_err(|e| DeployerError::Params(e.to_string()))
     }
 }
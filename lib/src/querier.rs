@@ -0,0 +1,271 @@
+use std::{collections::BTreeMap, error::Error};
+
+use async_trait::async_trait;
+use cosmos_sdk_proto::{
+    cosmos::{
+        bank::v1beta1::{
+            query_client::QueryClient as BankQueryClient, QueryAllBalancesRequest,
+            QueryBalanceRequest,
+        },
+        base::tendermint::v1beta1::{service_client::ServiceClient, GetNodeInfoRequest},
+    },
+    cosmwasm::wasm::v1::{
+        query_client::QueryClient as WasmQueryClient, QueryCodeRequest, QueryContractInfoRequest,
+        QuerySmartContractStateRequest,
+    },
+};
+use serde::Serialize;
+use serde_json::Value;
+use tonic::transport::{Channel, ClientTlsConfig};
+
+use crate::helpers::canonicalize;
+
+/// On chain info of an instantiated contract
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ContractInfo {
+    pub code_id: u64,
+    pub creator: String,
+    pub admin: Option<String>,
+    pub label: String,
+}
+
+/// On chain info of a stored code
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CodeInfo {
+    pub code_id: u64,
+    pub creator: String,
+    /// Hex sha256 of the wasm
+    pub checksum: String,
+}
+
+/// Read only queries the deployer needs from a chain
+#[async_trait]
+pub trait ChainQuerier: Send + Sync {
+    async fn contract_info(&self, addr: &str) -> Result<ContractInfo, Box<dyn Error>>;
+
+    /// Errors if the code doesn't exist
+    async fn code_info(&self, code_id: u64) -> Result<CodeInfo, Box<dyn Error>>;
+
+    async fn smart_query(&self, addr: &str, msg: &Value) -> Result<Value, Box<dyn Error>>;
+
+    async fn balance(&self, addr: &str, denom: &str) -> Result<u128, Box<dyn Error>>;
+
+    /// Every non zero balance of the address by denom
+    async fn all_balances(&self, addr: &str) -> Result<BTreeMap<String, u128>, Box<dyn Error>>;
+
+    async fn chain_id(&self) -> Result<String, Box<dyn Error>>;
+}
+
+/// Querier over the grpc endpoint of a chain
+pub struct GrpcQuerier {
+    channel: Channel,
+}
+
+impl GrpcQuerier {
+    pub async fn connect(grpc: &str) -> Result<Self, Box<dyn Error>> {
+        let mut endpoint = Channel::from_shared(grpc.to_string())?;
+
+        if grpc.starts_with("https") {
+            endpoint = endpoint.tls_config(ClientTlsConfig::new())?;
+        }
+
+        Ok(GrpcQuerier {
+            channel: endpoint.connect().await?,
+        })
+    }
+
+    /// Channel to the grpc endpoint, to use services the querier doesn't cover
+    pub(crate) fn channel(&self) -> Channel {
+        self.channel.clone()
+    }
+}
+
+#[async_trait]
+impl ChainQuerier for GrpcQuerier {
+    async fn contract_info(&self, addr: &str) -> Result<ContractInfo, Box<dyn Error>> {
+        let info = WasmQueryClient::new(self.channel.clone())
+            .contract_info(QueryContractInfoRequest {
+                address: addr.to_string(),
+            })
+            .await?
+            .into_inner()
+            .contract_info
+            .ok_or_else(|| format!("Contract {} not found", addr))?;
+
+        Ok(ContractInfo {
+            code_id: info.code_id,
+            creator: info.creator,
+            admin: Some(info.admin).filter(|admin| !admin.is_empty()),
+            label: info.label,
+        })
+    }
+
+    async fn code_info(&self, code_id: u64) -> Result<CodeInfo, Box<dyn Error>> {
+        // The response also has the wasm, there is no query for the info only
+        let info = WasmQueryClient::new(self.channel.clone())
+            .code(QueryCodeRequest { code_id })
+            .await?
+            .into_inner()
+            .code_info
+            .ok_or_else(|| format!("Code {} not found", code_id))?;
+
+        Ok(CodeInfo {
+            code_id: info.code_id,
+            creator: info.creator,
+            checksum: hex::encode(info.data_hash),
+        })
+    }
+
+    async fn smart_query(&self, addr: &str, msg: &Value) -> Result<Value, Box<dyn Error>> {
+        let data = WasmQueryClient::new(self.channel.clone())
+            .smart_contract_state(QuerySmartContractStateRequest {
+                address: addr.to_string(),
+                query_data: serde_json::to_vec(msg)?,
+            })
+            .await?
+            .into_inner()
+            .data;
+
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    async fn balance(&self, addr: &str, denom: &str) -> Result<u128, Box<dyn Error>> {
+        let balance = BankQueryClient::new(self.channel.clone())
+            .balance(QueryBalanceRequest {
+                address: addr.to_string(),
+                denom: denom.to_string(),
+            })
+            .await?
+            .into_inner()
+            .balance;
+
+        Ok(balance
+            .map(|c| c.amount.parse::<u128>())
+            .transpose()?
+            .unwrap_or_default())
+    }
+
+    async fn all_balances(&self, addr: &str) -> Result<BTreeMap<String, u128>, Box<dyn Error>> {
+        let balances = BankQueryClient::new(self.channel.clone())
+            .all_balances(QueryAllBalancesRequest {
+                address: addr.to_string(),
+                ..Default::default()
+            })
+            .await?
+            .into_inner()
+            .balances;
+
+        balances
+            .into_iter()
+            .map(|c| Ok((c.denom, c.amount.parse::<u128>()?)))
+            .collect()
+    }
+
+    async fn chain_id(&self) -> Result<String, Box<dyn Error>> {
+        let node_info = ServiceClient::new(self.channel.clone())
+            .get_node_info(GetNodeInfoRequest {})
+            .await?
+            .into_inner()
+            .default_node_info
+            .ok_or("Node info not found")?;
+
+        Ok(node_info.network)
+    }
+}
+
+/// In memory querier that answers with preset responses, used to test against a mock chain
+#[derive(Debug, Clone, Default)]
+pub struct MockQuerier {
+    contracts: BTreeMap<String, ContractInfo>,
+    codes: BTreeMap<u64, CodeInfo>,
+    /// (contract address, canonical query json) to response
+    responses: BTreeMap<(String, String), Value>,
+    balances: BTreeMap<String, BTreeMap<String, u128>>,
+    chain_id: String,
+}
+
+impl MockQuerier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_contract(mut self, addr: &str, info: ContractInfo) -> Self {
+        self.contracts.insert(addr.to_string(), info);
+        self
+    }
+
+    pub fn with_code(mut self, info: CodeInfo) -> Self {
+        self.codes.insert(info.code_id, info);
+        self
+    }
+
+    pub fn with_response(mut self, addr: &str, msg: Value, response: Value) -> Self {
+        self.responses
+            .insert((addr.to_string(), canonicalize(msg).to_string()), response);
+        self
+    }
+
+    pub fn with_balance(mut self, addr: &str, denom: &str, amount: u128) -> Self {
+        self.balances
+            .entry(addr.to_string())
+            .or_default()
+            .insert(denom.to_string(), amount);
+        self
+    }
+
+    pub fn with_chain_id(mut self, chain_id: &str) -> Self {
+        self.chain_id = chain_id.to_string();
+        self
+    }
+}
+
+#[async_trait]
+impl ChainQuerier for MockQuerier {
+    async fn contract_info(&self, addr: &str) -> Result<ContractInfo, Box<dyn Error>> {
+        self.contracts
+            .get(addr)
+            .cloned()
+            .ok_or_else(|| format!("Contract {} not found", addr).into())
+    }
+
+    async fn code_info(&self, code_id: u64) -> Result<CodeInfo, Box<dyn Error>> {
+        self.codes
+            .get(&code_id)
+            .cloned()
+            .ok_or_else(|| format!("Code {} not found", code_id).into())
+    }
+
+    async fn smart_query(&self, addr: &str, msg: &Value) -> Result<Value, Box<dyn Error>> {
+        self.responses
+            .get(&(addr.to_string(), canonicalize(msg.clone()).to_string()))
+            .cloned()
+            .ok_or_else(|| format!("No response for query {} on {}", msg, addr).into())
+    }
+
+    async fn balance(&self, addr: &str, denom: &str) -> Result<u128, Box<dyn Error>> {
+        Ok(self
+            .balances
+            .get(addr)
+            .and_then(|b| b.get(denom))
+            .copied()
+            .unwrap_or_default())
+    }
+
+    async fn all_balances(&self, addr: &str) -> Result<BTreeMap<String, u128>, Box<dyn Error>> {
+        Ok(self
+            .balances
+            .get(addr)
+            .map(|balances| {
+                balances
+                    .iter()
+                    .filter(|(_, amount)| **amount > 0)
+                    .map(|(denom, amount)| (denom.clone(), *amount))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    async fn chain_id(&self) -> Result<String, Box<dyn Error>> {
+        Ok(self.chain_id.clone())
+    }
+}
@@ -0,0 +1,274 @@
+use std::{error::Error, fmt::Display, path::Path};
+
+use deployer_simulator::{Scenario, Simulation, StepResult};
+use serde_json::Value;
+
+use crate::definition::{get_valid_params, ProgramDefinition};
+
+const SCENARIOS_DIR: &str = "scenarios";
+
+/// Result of running a scenario file against a simulation of the program
+#[derive(Debug)]
+pub struct ScenarioReport {
+    /// Scenario file name
+    pub name: String,
+    pub description: String,
+    pub steps: Vec<StepResult>,
+    /// The scenario couldn't be read or the simulation couldn't be set up
+    pub error: Option<String>,
+}
+
+impl ScenarioReport {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none() && self.steps.iter().all(|step| step.failures.is_empty())
+    }
+}
+
+impl Display for ScenarioReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = if self.is_ok() { "passed" } else { "failed" };
+        writeln!(f, "{}: {} {}", self.name, status, self.description)?;
+
+        if let Some(error) = &self.error {
+            writeln!(f, "    error: {}", error)?;
+        }
+
+        for (i, step) in self.steps.iter().enumerate() {
+            let status = if step.failures.is_empty() {
+                "ok"
+            } else {
+                "FAILED"
+            };
+            writeln!(f, "    {}. {}: {}", i + 1, step.step, status)?;
+
+            for failure in &step.failures {
+                writeln!(f, "        {}", failure)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Run every `scenarios/*.toml` and `scenarios/*.json` of the program, or only the one with the given name
+pub(crate) fn run_scenarios(
+    program: &dyn ProgramDefinition,
+    name: Option<&str>,
+) -> Result<Vec<ScenarioReport>, Box<dyn Error>> {
+    let scenarios_path = program.path().join(SCENARIOS_DIR);
+    if !scenarios_path.exists() {
+        return Err(format!("Program has no {} directory", SCENARIOS_DIR).into());
+    }
+
+    let mut paths = std::fs::read_dir(&scenarios_path)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == "toml" || ext == "json")
+        })
+        .filter(|path| name.is_none_or(|name| path.file_stem().is_some_and(|stem| stem == name)))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    let mut reports = vec![];
+    for path in paths {
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default()
+            .to_string();
+
+        let report = match run_scenario(program, &path) {
+            Ok((description, steps)) => ScenarioReport {
+                name,
+                description,
+                steps,
+                error: None,
+            },
+            Err(e) => ScenarioReport {
+                name,
+                description: String::new(),
+                steps: vec![],
+                error: Some(e.to_string()),
+            },
+        };
+
+        reports.push(report);
+    }
+
+    Ok(reports)
+}
+
+fn run_scenario(
+    program: &dyn ProgramDefinition,
+    path: &Path,
+) -> Result<(String, Vec<StepResult>), Box<dyn Error>> {
+    let env = Scenario::get_env(path)?;
+    let program_params = get_valid_params(program, &program.path(), &env)?;
+    let scenario = Scenario::from_file(path, &program_params.to_json()?)?;
+
+    let steps = scenario.run(&program.build(program_params)?)?;
+
+    Ok((scenario.description, steps))
+}
+
+/// Simulation of a program set up with the mocks and balances of a scenario, without running its steps
+pub(crate) struct ScenarioSimulation {
+    pub sim: Simulation,
+    /// Env of the scenario the program was built for
+    pub env: String,
+    pub program_config: Value,
+    pub params: Value,
+}
+
+/// Set up the simulation of the scenario with the given file name, without extension
+pub(crate) fn get_scenario_simulation(
+    program: &dyn ProgramDefinition,
+    name: &str,
+) -> Result<ScenarioSimulation, Box<dyn Error>> {
+    let path = ["toml", "json"]
+        .iter()
+        .map(|ext| {
+            program
+                .path()
+                .join(SCENARIOS_DIR)
+                .join(format!("{}.{}", name, ext))
+        })
+        .find(|path| path.exists())
+        .ok_or_else(|| format!("Scenario {} not found in {}", name, SCENARIOS_DIR))?;
+
+    let env = Scenario::get_env(&path)?;
+    let program_params = get_valid_params(program, &program.path(), &env)?;
+    let params = program_params.to_json()?;
+    let scenario = Scenario::from_file(&path, &params)?;
+    let program_config = program.build(program_params)?;
+
+    Ok(ScenarioSimulation {
+        sim: scenario.simulation(&program_config)?,
+        env,
+        program_config: serde_json::to_value(&program_config)?,
+        params,
+    })
+}
+
+/// Run the scenarios of a program from its tests, panics if any scenario fails
+pub fn assert_scenarios(program: &dyn ProgramDefinition) {
+    let reports =
+        run_scenarios(program, None).unwrap_or_else(|e| panic!("Failed to run scenarios: {}", e));
+
+    reports.iter().for_each(|report| print!("{report}"));
+
+    if reports.iter().any(|report| !report.is_ok()) {
+        panic!("Some scenarios failed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error::Error, path::PathBuf};
+
+    use deployer_simulator::StepResult;
+    use tempfile::{tempdir, TempDir};
+    use valence_program_manager::program_config::ProgramConfig;
+
+    use super::{run_scenarios, ScenarioReport};
+    use crate::{definition::ProgramDefinition, program_params::ProgramParams};
+
+    struct Forwarder(PathBuf);
+
+    impl ProgramDefinition for Forwarder {
+        fn path(&self) -> PathBuf {
+            self.0.clone()
+        }
+
+        fn build(&self, _: ProgramParams) -> Result<ProgramConfig, Box<dyn Error>> {
+            Err("Scenarios are read before building".into())
+        }
+    }
+
+    /// Program with mainnet params and the given scenario files
+    fn forwarder(dir: &TempDir, scenarios: &[(&str, &str)]) -> Forwarder {
+        let path = dir.path().to_path_buf();
+        std::fs::create_dir_all(path.join("program_params")).unwrap();
+        std::fs::create_dir_all(path.join("scenarios")).unwrap();
+        std::fs::write(
+            path.join("program_params").join("mainnet.toml"),
+            "operator_list = [\"neutron1operator\"]",
+        )
+        .unwrap();
+        for (name, content) in scenarios {
+            std::fs::write(path.join("scenarios").join(name), content).unwrap();
+        }
+
+        Forwarder(path)
+    }
+
+    #[test]
+    fn malformed_scenario_files_are_reported() {
+        let dir = tempdir().unwrap();
+        let program = forwarder(
+            &dir,
+            &[
+                ("invalid.toml", "steps = ["),
+                (
+                    "missing-param.toml",
+                    "[actors]\noperator1 = \"${operators.0}\"",
+                ),
+                ("notes.txt", "not a scenario"),
+                ("typo.json", r#"{"step": []}"#),
+            ],
+        );
+
+        let reports = run_scenarios(&program, None).unwrap();
+
+        assert_eq!(
+            reports.iter().map(|r| r.name.as_str()).collect::<Vec<_>>(),
+            vec!["invalid.toml", "missing-param.toml", "typo.json"]
+        );
+        assert!(reports.iter().all(|r| !r.is_ok() && r.steps.is_empty()));
+        assert_eq!(
+            reports[1].error.as_deref(),
+            Some("Param operators.0 not found")
+        );
+        assert!(reports[2]
+            .error
+            .as_deref()
+            .is_some_and(|e| e.starts_with("unknown field `step`")));
+    }
+
+    #[test]
+    fn scenario_with_a_failing_step_fails() {
+        let report = ScenarioReport {
+            name: "forward.toml".to_string(),
+            description: "Operator forwards".to_string(),
+            steps: vec![
+                StepResult {
+                    step: "tick".to_string(),
+                    failures: vec![],
+                },
+                StepResult {
+                    step: "operator1 executes forward".to_string(),
+                    failures: vec!["unexpected error: Unauthorized".to_string()],
+                },
+            ],
+            error: None,
+        };
+
+        assert!(!report.is_ok());
+        assert_eq!(
+            report.to_string(),
+            "forward.toml: failed Operator forwards\n    1. tick: ok\n    2. operator1 executes forward: FAILED\n        unexpected error: Unauthorized\n"
+        );
+    }
+
+    #[test]
+    fn missing_scenarios_directory_is_an_error() {
+        let dir = tempdir().unwrap();
+        let program = Forwarder(dir.path().to_path_buf());
+
+        assert_eq!(
+            run_scenarios(&program, None).unwrap_err().to_string(),
+            "Program has no scenarios directory"
+        );
+    }
+}
@@ -0,0 +1,365 @@
+use std::{collections::BTreeMap, error::Error, fmt::Display};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::denoms::DenomRegistry;
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Minimum interval between 2 batches of a forwarder
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchInterval {
+    /// Batches can be forwarded back to back
+    None,
+    Seconds(u64),
+    Blocks(u64),
+}
+
+impl Display for BatchInterval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BatchInterval::None => write!(f, "no interval"),
+            BatchInterval::Seconds(seconds) => write!(f, "every {}", format_duration(*seconds)),
+            BatchInterval::Blocks(blocks) => write!(f, "every {} blocks", blocks),
+        }
+    }
+}
+
+/// Projection of how a forwarder drains a denom from its input account
+#[derive(Debug, Clone, Serialize)]
+pub struct ForwarderSchedule {
+    pub library: String,
+    /// Name of the input account, or its address if it isn't part of the program
+    pub account: String,
+    pub denom: String,
+    pub max_amount: u128,
+    /// Max amount in whole tokens if the denom is in the registry, Ex: `6.5M NTRN`
+    pub max_amount_human: Option<String>,
+    pub interval: BatchInterval,
+    /// Amount expected in the input account
+    pub input_amount: Option<u128>,
+    pub input_amount_human: Option<String>,
+    /// Number of batches needed to drain the input amount
+    pub batches: Option<u128>,
+    /// Time (or blocks) from the first to the last batch
+    pub duration: Option<u128>,
+    /// The schedule doesn't match the intended duration
+    pub warning: Option<String>,
+}
+
+impl Display for ForwarderSchedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let human = |amount: &Option<String>| {
+            amount
+                .as_ref()
+                .map(|amount| format!(" ({})", amount))
+                .unwrap_or_default()
+        };
+
+        writeln!(
+            f,
+            "{} forwards at most {}{}{} from {} {}",
+            self.library,
+            self.max_amount,
+            self.denom,
+            human(&self.max_amount_human),
+            self.account,
+            self.interval
+        )?;
+
+        match (self.input_amount, self.batches, self.duration) {
+            (Some(input_amount), Some(batches), Some(duration)) => {
+                let duration = match self.interval {
+                    BatchInterval::Blocks(_) => format!("{} blocks", duration),
+                    _ => format_duration(duration as u64),
+                };
+                writeln!(
+                    f,
+                    "    {}{}{} drains in {} batches over {}",
+                    input_amount,
+                    self.denom,
+                    human(&self.input_amount_human),
+                    batches,
+                    duration
+                )?;
+            }
+            _ => writeln!(f, "    no expected input amount")?,
+        }
+
+        if let Some(warning) = &self.warning {
+            writeln!(f, "    WARNING: {}", warning)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Expected amount in an input account, `<account>[:<denom>]=<amount>`,
+/// the amount is an integer or a human amount of the env denoms, Ex: `100M NTRN`
+#[derive(Debug, Clone)]
+pub(crate) struct InputAmount {
+    pub account: String,
+    pub denom: Option<String>,
+    pub amount: String,
+}
+
+impl std::str::FromStr for InputAmount {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, amount) = s
+            .rsplit_once('=')
+            .ok_or_else(|| format!("Expected <account>[:<denom>]=<amount>, got {}", s))?;
+        // Account names have no ':', denoms might
+        let (account, denom) = match key.split_once(':') {
+            Some((account, denom)) => (account, Some(denom.to_string())),
+            None => (key, None),
+        };
+
+        Ok(InputAmount {
+            account: account.to_string(),
+            denom,
+            amount: amount.to_string(),
+        })
+    }
+}
+
+/// Parse a duration like `90d`, `12w`, `3mo` (30 days), `36h` or `3600s` into seconds
+pub(crate) fn parse_duration(s: &str) -> Result<u64, String> {
+    let index = s
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("Duration {} has no unit (s, m, h, d, w, mo)", s))?;
+    let (value, unit) = s.split_at(index);
+    let value: u64 = value
+        .parse()
+        .map_err(|_| format!("Invalid duration {}", s))?;
+
+    let unit_seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3_600,
+        "d" => SECONDS_PER_DAY,
+        "w" => 7 * SECONDS_PER_DAY,
+        "mo" => 30 * SECONDS_PER_DAY,
+        _ => {
+            return Err(format!(
+                "Unknown duration unit {} (s, m, h, d, w, mo)",
+                unit
+            ))
+        }
+    };
+
+    value
+        .checked_mul(unit_seconds)
+        .ok_or_else(|| format!("Invalid duration {}", s))
+}
+
+pub(crate) fn format_duration(seconds: u64) -> String {
+    if seconds.is_multiple_of(SECONDS_PER_DAY) {
+        format!("{} days", seconds / SECONDS_PER_DAY)
+    } else {
+        format!("{:.1} days", seconds as f64 / SECONDS_PER_DAY as f64)
+    }
+}
+
+/// Project the batching schedule of every forwarder library of the program.
+/// The first batch is forwarded right away, so draining takes `batches - 1` intervals,
+/// and it matches the intended duration if it ends within the last interval of it
+pub(crate) fn get_schedule(
+    program_config: &Value,
+    inputs: &[InputAmount],
+    intended_duration: Option<u64>,
+    registry: &DenomRegistry,
+) -> Result<Vec<ForwarderSchedule>, Box<dyn Error>> {
+    let account_names = program_config["accounts"]
+        .as_object()
+        .map(|accounts| {
+            accounts
+                .iter()
+                .map(|(id, account)| {
+                    (
+                        id.clone(),
+                        account["name"].as_str().unwrap_or_default().to_string(),
+                    )
+                })
+                .collect::<BTreeMap<_, _>>()
+        })
+        .unwrap_or_default();
+
+    // Libraries are keyed by id, keep them in the order they were added
+    let mut libraries = program_config["libraries"]
+        .as_object()
+        .map(|l| l.iter().collect::<Vec<_>>())
+        .unwrap_or_default();
+    libraries.sort_by_key(|(id, _)| id.parse::<u64>().unwrap_or(u64::MAX));
+
+    let mut schedules = vec![];
+    for (_, library) in libraries {
+        let Some(config) = library["config"].get("ValenceForwarderLibrary") else {
+            continue;
+        };
+        let library_name = library["name"].as_str().unwrap_or_default();
+
+        let account = match &config["input_addr"] {
+            Value::Object(map) if map.contains_key("|account_id|") => {
+                let id = map["|account_id|"].to_string();
+                account_names.get(&id).cloned().unwrap_or(id)
+            }
+            Value::Object(map) => map
+                .values()
+                .next()
+                .map(|v| v.as_str().map(|s| s.to_string()).unwrap_or(v.to_string()))
+                .unwrap_or_default(),
+            v => v.as_str().unwrap_or_default().to_string(),
+        };
+
+        let interval = match config["forwarding_constraints"]["min_interval"].as_object() {
+            Some(min_interval) => match (min_interval.get("time"), min_interval.get("height")) {
+                (Some(time), _) => BatchInterval::Seconds(time.as_u64().unwrap_or_default()),
+                (_, Some(height)) => BatchInterval::Blocks(height.as_u64().unwrap_or_default()),
+                _ => BatchInterval::None,
+            },
+            None => BatchInterval::None,
+        };
+
+        for forwarding_config in config["forwarding_configs"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+        {
+            let denom = forwarding_config["denom"]
+                .as_object()
+                .and_then(|d| d.values().next())
+                .and_then(|d| d.as_str())
+                .unwrap_or_default()
+                .to_string();
+            let max_amount: u128 = forwarding_config["max_amount"]
+                .as_str()
+                .unwrap_or_default()
+                .parse()
+                .map_err(|_| format!("{} has an invalid max_amount", library_name))?;
+
+            // An input for the account and denom takes precedence over one for the whole account
+            let input_amount = inputs
+                .iter()
+                .filter(|input| input.account == account)
+                .filter(|input| input.denom.as_ref().is_none_or(|d| *d == denom))
+                .max_by_key(|input| input.denom.is_some())
+                .map(|input| match registry.parse_amount(&input.amount) {
+                    Some(amount) => amount,
+                    None => input
+                        .amount
+                        .parse()
+                        .map_err(|_| format!("Invalid input amount {}", input.amount)),
+                })
+                .transpose()?;
+
+            let mut schedule = ForwarderSchedule {
+                library: library_name.to_string(),
+                account: account.clone(),
+                max_amount_human: registry.format_amount(max_amount, &denom),
+                input_amount_human: input_amount
+                    .and_then(|amount| registry.format_amount(amount, &denom)),
+                denom,
+                max_amount,
+                interval,
+                input_amount,
+                batches: None,
+                duration: None,
+                warning: None,
+            };
+
+            if max_amount == 0 {
+                schedule.warning = Some("max_amount is 0, nothing is ever forwarded".to_string());
+            } else if let Some(input_amount) = input_amount {
+                let batches = input_amount.div_ceil(max_amount);
+                let interval_length = match interval {
+                    BatchInterval::None => 0,
+                    BatchInterval::Seconds(n) | BatchInterval::Blocks(n) => n as u128,
+                };
+                let duration = batches.saturating_sub(1).saturating_mul(interval_length);
+
+                schedule.batches = Some(batches);
+                schedule.duration = Some(duration);
+                schedule.warning = intended_duration.and_then(|intended| {
+                    check_duration(interval, interval_length, duration, intended as u128)
+                });
+            }
+
+            schedules.push(schedule);
+        }
+    }
+
+    Ok(schedules)
+}
+
+fn check_duration(
+    interval: BatchInterval,
+    interval_length: u128,
+    duration: u128,
+    intended: u128,
+) -> Option<String> {
+    match interval {
+        // Batches are forwarded as fast as operators execute them
+        BatchInterval::None => None,
+        BatchInterval::Blocks(_) => {
+            Some("interval is in blocks, the duration can't be compared".to_string())
+        }
+        _ if duration > intended => Some(format!(
+            "draining takes {}, longer than the intended {}",
+            format_duration(duration as u64),
+            format_duration(intended as u64)
+        )),
+        _ if duration + interval_length <= intended => Some(format!(
+            "draining takes {}, more than a batch interval shorter than the intended {}",
+            format_duration(duration as u64),
+            format_duration(intended as u64)
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_duration;
+
+    #[test]
+    fn durations_are_parsed() {
+        assert_eq!(parse_duration("30s"), Ok(30));
+        assert_eq!(parse_duration("5m"), Ok(300));
+        assert_eq!(parse_duration("2h"), Ok(7_200));
+        assert_eq!(parse_duration("7d"), Ok(604_800));
+        assert_eq!(parse_duration("4w"), Ok(2_419_200));
+        assert_eq!(parse_duration("1mo"), Ok(2_592_000));
+        assert_eq!(parse_duration("0d"), Ok(0));
+    }
+
+    #[test]
+    fn invalid_durations_are_rejected() {
+        assert!(parse_duration("7").unwrap_err().contains("has no unit"));
+        assert!(parse_duration("7y")
+            .unwrap_err()
+            .contains("Unknown duration unit"));
+        assert!(parse_duration("d")
+            .unwrap_err()
+            .contains("Invalid duration"));
+        assert!(parse_duration("-1d")
+            .unwrap_err()
+            .contains("Invalid duration"));
+        assert!(parse_duration("").is_err());
+    }
+
+    #[test]
+    fn overflowing_durations_are_rejected() {
+        assert_eq!(
+            parse_duration("99999999999999999w"),
+            Err("Invalid duration 99999999999999999w".to_string())
+        );
+        assert!(parse_duration("99999999999999999999s")
+            .unwrap_err()
+            .contains("Invalid duration"));
+        assert_eq!(parse_duration(&format!("{}s", u64::MAX)), Ok(u64::MAX));
+    }
+}
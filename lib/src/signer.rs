@@ -0,0 +1,419 @@
+use std::{
+    fmt::Display,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use log::info;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::error::DeployerError;
+
+/// Env var holding the passphrase of a keystore, prompted for if not set
+pub const KEYSTORE_PASSPHRASE_VAR: &str = "KEYSTORE_PASSPHRASE";
+
+const KEYSTORE_VERSION: u32 = 1;
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+/// Where the mnemonic of the wallet the program manager deploys with comes from
+#[derive(Clone, Default)]
+pub enum Signer {
+    /// `MANAGER_MNEMONIC` from the environment or the `.env` file of the workspace
+    #[default]
+    Env,
+    Mnemonic(String),
+    /// Keystore file encrypted with a passphrase, see `Keystore`,
+    /// the passphrase is read from `KEYSTORE_PASSPHRASE` or prompted for if not given
+    Keystore {
+        path: PathBuf,
+        passphrase: Option<String>,
+    },
+    /// Command printing the mnemonic on stdout, Ex: `pass show valence/deployer`
+    Command(Vec<String>),
+    /// Unix socket of a local signer, answers the mnemonic to a `mnemonic` line
+    Socket(PathBuf),
+}
+
+// Never print the mnemonic or the passphrase
+impl std::fmt::Debug for Signer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Signer::Mnemonic(_) => write!(f, "Mnemonic(..)"),
+            Signer::Keystore { path, .. } => write!(f, "Keystore({})", path.display()),
+            signer => write!(f, "{}", signer),
+        }
+    }
+}
+
+/// As recorded in the manifest, Ex: `keystore:keys/deployer.json`
+impl Display for Signer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Signer::Env => write!(f, "env"),
+            Signer::Mnemonic(_) => write!(f, "mnemonic"),
+            Signer::Keystore { path, .. } => write!(f, "keystore:{}", path.display()),
+            Signer::Command(command) => write!(f, "command:{}", command.join(" ")),
+            Signer::Socket(path) => write!(f, "socket:{}", path.display()),
+        }
+    }
+}
+
+/// `env`, `keystore:<path>`, `command:<command>` or `socket:<path>`,
+/// a mnemonic can't be given on the command line
+impl FromStr for Signer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "env" {
+            return Ok(Signer::Env);
+        }
+
+        match s.split_once(':') {
+            Some(("keystore", path)) if !path.is_empty() => Ok(Signer::Keystore {
+                path: path.into(),
+                passphrase: None,
+            }),
+            Some(("command", command)) if !command.trim().is_empty() => Ok(Signer::Command(
+                command.split_whitespace().map(|s| s.to_string()).collect(),
+            )),
+            Some(("socket", path)) if !path.is_empty() => Ok(Signer::Socket(path.into())),
+            _ => Err(format!(
+                "Invalid signer {:?}, expected env, keystore:<path>, command:<command> or socket:<path>",
+                s
+            )),
+        }
+    }
+}
+
+impl Signer {
+    /// Get the mnemonic from the source, relative paths are from the workspace
+    pub(crate) fn get_mnemonic(
+        &self,
+        workspace: &Path,
+    ) -> Result<Zeroizing<String>, DeployerError> {
+        let mnemonic = match self {
+            Signer::Env => {
+                info!("Loading environment variables from .env file");
+                let env_path = workspace.join(".env");
+                if env_path.exists() {
+                    dotenvy::from_path(&env_path).map_err(|e| {
+                        DeployerError::Signer(format!(
+                            "Failed to load {}: {}",
+                            env_path.display(),
+                            e
+                        ))
+                    })?;
+                }
+
+                Zeroizing::new(std::env::var("MANAGER_MNEMONIC").map_err(|_| {
+                    DeployerError::Signer(
+                        "MANAGER_MNEMONIC is not set, add it to the .env file".to_string(),
+                    )
+                })?)
+            }
+            Signer::Mnemonic(mnemonic) => Zeroizing::new(mnemonic.clone()),
+            Signer::Keystore { path, passphrase } => {
+                let passphrase = match passphrase {
+                    Some(passphrase) => Zeroizing::new(passphrase.clone()),
+                    None => get_passphrase(&format!("Passphrase of {}: ", path.display()))?,
+                };
+
+                Keystore::read(&workspace.join(path))?.decrypt(&passphrase)?
+            }
+            Signer::Command(command) => run_signer_command(workspace, command)?,
+            Signer::Socket(path) => request_socket(&workspace.join(path))?,
+        };
+
+        let mnemonic = Zeroizing::new(mnemonic.trim().to_string());
+        if mnemonic.is_empty() {
+            return Err(DeployerError::Signer(format!(
+                "Signer {} gave an empty mnemonic",
+                self
+            )));
+        }
+
+        Ok(mnemonic)
+    }
+}
+
+/// `KEYSTORE_PASSPHRASE`, or prompt for it without echo
+pub(crate) fn get_passphrase(prompt: &str) -> Result<Zeroizing<String>, DeployerError> {
+    if let Ok(passphrase) = std::env::var(KEYSTORE_PASSPHRASE_VAR) {
+        return Ok(Zeroizing::new(passphrase));
+    }
+
+    rpassword::prompt_password(prompt)
+        .map(Zeroizing::new)
+        .map_err(|e| {
+            DeployerError::Signer(format!(
+                "Failed to read the passphrase, set {}: {}",
+                KEYSTORE_PASSPHRASE_VAR, e
+            ))
+        })
+}
+
+fn run_signer_command(
+    workspace: &Path,
+    command: &[String],
+) -> Result<Zeroizing<String>, DeployerError> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| DeployerError::Signer("Signer command is empty".to_string()))?;
+
+    // stderr is inherited so the command can prompt, stdout is only the mnemonic
+    let output = std::process::Command::new(program)
+        .args(args)
+        .current_dir(workspace)
+        .stdin(std::process::Stdio::inherit())
+        .stderr(std::process::Stdio::inherit())
+        .output()
+        .map_err(|e| DeployerError::Signer(format!("Failed to run {}: {}", program, e)))?;
+    let stdout = Zeroizing::new(output.stdout);
+
+    if !output.status.success() {
+        return Err(DeployerError::Signer(format!(
+            "Signer command {} failed with {}",
+            program, output.status
+        )));
+    }
+
+    // Like `pass`, the mnemonic is the first line of the output
+    let stdout = std::str::from_utf8(&stdout)
+        .map_err(|_| DeployerError::Signer(format!("{} didn't print a mnemonic", program)))?;
+
+    Ok(Zeroizing::new(
+        stdout.lines().next().unwrap_or_default().to_string(),
+    ))
+}
+
+#[cfg(unix)]
+fn request_socket(path: &Path) -> Result<Zeroizing<String>, DeployerError> {
+    let socket_error = |e: std::io::Error| {
+        DeployerError::Signer(format!("Signer socket {}: {}", path.display(), e))
+    };
+
+    let mut stream = std::os::unix::net::UnixStream::connect(path).map_err(socket_error)?;
+    stream.write_all(b"mnemonic\n").map_err(socket_error)?;
+
+    let mut mnemonic = Zeroizing::new(String::new());
+    BufReader::new(stream)
+        .read_line(&mut mnemonic)
+        .map_err(socket_error)?;
+
+    Ok(mnemonic)
+}
+
+#[cfg(not(unix))]
+fn request_socket(path: &Path) -> Result<Zeroizing<String>, DeployerError> {
+    Err(DeployerError::Signer(format!(
+        "Signer socket {} is only supported on unix",
+        path.display()
+    )))
+}
+
+/// Mnemonic encrypted with aes-256-gcm, with a key derived from a passphrase with scrypt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Keystore {
+    pub version: u32,
+    pub kdf: String,
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+    /// hex
+    pub salt: String,
+    pub cipher: String,
+    /// hex
+    pub nonce: String,
+    /// hex
+    pub ciphertext: String,
+}
+
+impl Keystore {
+    pub fn encrypt(mnemonic: &str, passphrase: &str) -> Result<Self, DeployerError> {
+        let salt: [u8; 32] = rand::random();
+        let nonce: [u8; 12] = rand::random();
+        let key = derive_key(passphrase, &salt, SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P)?;
+
+        let ciphertext = Aes256Gcm::new_from_slice(key.as_slice())
+            .map_err(|e| DeployerError::Signer(e.to_string()))?
+            .encrypt(Nonce::from_slice(&nonce), mnemonic.trim().as_bytes())
+            .map_err(|_| DeployerError::Signer("Failed to encrypt the mnemonic".to_string()))?;
+
+        Ok(Keystore {
+            version: KEYSTORE_VERSION,
+            kdf: "scrypt".to_string(),
+            log_n: SCRYPT_LOG_N,
+            r: SCRYPT_R,
+            p: SCRYPT_P,
+            salt: hex::encode(salt),
+            cipher: "aes-256-gcm".to_string(),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    pub fn decrypt(&self, passphrase: &str) -> Result<Zeroizing<String>, DeployerError> {
+        if self.version != KEYSTORE_VERSION || self.kdf != "scrypt" || self.cipher != "aes-256-gcm"
+        {
+            return Err(DeployerError::Signer(format!(
+                "Unsupported keystore version {} with {} and {}",
+                self.version, self.kdf, self.cipher
+            )));
+        }
+
+        let invalid = |_| DeployerError::Signer("Invalid keystore".to_string());
+        let salt = hex::decode(&self.salt).map_err(invalid)?;
+        let nonce = hex::decode(&self.nonce).map_err(invalid)?;
+        let ciphertext = hex::decode(&self.ciphertext).map_err(invalid)?;
+        if nonce.len() != 12 {
+            return Err(DeployerError::Signer("Invalid keystore".to_string()));
+        }
+
+        let key = derive_key(passphrase, &salt, self.log_n, self.r, self.p)?;
+        let mnemonic = Aes256Gcm::new_from_slice(key.as_slice())
+            .map_err(|e| DeployerError::Signer(e.to_string()))?
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| DeployerError::Signer("Wrong keystore passphrase".to_string()))?;
+
+        String::from_utf8(mnemonic)
+            .map(Zeroizing::new)
+            .map_err(|_| DeployerError::Signer("Invalid keystore".to_string()))
+    }
+
+    pub fn read(path: &Path) -> Result<Self, DeployerError> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            DeployerError::Signer(format!("Failed to read keystore {}: {}", path.display(), e))
+        })?;
+
+        serde_json::from_str(&content).map_err(|e| {
+            DeployerError::Signer(format!("Invalid keystore {}: {}", path.display(), e))
+        })
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), DeployerError> {
+        let content =
+            serde_json::to_string_pretty(self).map_err(|e| DeployerError::Output(e.to_string()))?;
+
+        std::fs::write(path, content)
+            .map_err(|e| DeployerError::Output(format!("{}: {}", path.display(), e)))
+    }
+}
+
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    log_n: u8,
+    r: u32,
+    p: u32,
+) -> Result<Zeroizing<[u8; 32]>, DeployerError> {
+    let params = scrypt::Params::new(log_n, r, p, 32)
+        .map_err(|e| DeployerError::Signer(format!("Invalid keystore: {}", e)))?;
+
+    let mut key = Zeroizing::new([0u8; 32]);
+    scrypt::scrypt(passphrase.as_bytes(), salt, &params, key.as_mut_slice())
+        .map_err(|e| DeployerError::Signer(e.to_string()))?;
+
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::{Keystore, Signer};
+    use crate::error::DeployerError;
+
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
+
+    #[test]
+    fn keystore_decrypts_to_the_mnemonic() {
+        let workspace = tempdir().unwrap();
+        let keystore = Keystore::encrypt(&format!("{}\n", MNEMONIC), "passphrase").unwrap();
+        keystore
+            .write(&workspace.path().join("deployer.json"))
+            .unwrap();
+
+        let signer = Signer::Keystore {
+            path: "deployer.json".into(),
+            passphrase: Some("passphrase".to_string()),
+        };
+
+        assert_eq!(keystore.decrypt("passphrase").unwrap().as_str(), MNEMONIC);
+        assert_eq!(
+            signer.get_mnemonic(workspace.path()).unwrap().as_str(),
+            MNEMONIC
+        );
+    }
+
+    #[test]
+    fn wrong_passphrase_is_rejected() {
+        let keystore = Keystore::encrypt(MNEMONIC, "passphrase").unwrap();
+
+        assert!(matches!(
+            keystore.decrypt("wrong passphrase"),
+            Err(DeployerError::Signer(e)) if e == "Wrong keystore passphrase"
+        ));
+    }
+
+    #[test]
+    fn signers_are_parsed() {
+        let parse = |s: &str| s.parse::<Signer>().map(|signer| signer.to_string());
+
+        assert_eq!(parse("env"), Ok("env".to_string()));
+        assert_eq!(
+            parse("keystore:keys/deployer.json"),
+            Ok("keystore:keys/deployer.json".to_string())
+        );
+        assert_eq!(
+            parse("socket:/run/signer.sock"),
+            Ok("socket:/run/signer.sock".to_string())
+        );
+        assert!(matches!(
+            "command:pass show valence/deployer".parse(),
+            Ok(Signer::Command(command)) if command == ["pass", "show", "valence/deployer"]
+        ));
+    }
+
+    #[test]
+    fn unknown_signers_are_rejected() {
+        for signer in [
+            "",
+            "mnemonic:abandon",
+            "ledger:0",
+            "keystore:",
+            "command: ",
+            "socket",
+        ] {
+            assert_eq!(
+                signer.parse::<Signer>().unwrap_err(),
+                format!(
+                    "Invalid signer {:?}, expected env, keystore:<path>, command:<command> or socket:<path>",
+                    signer
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn signer_command_mnemonic_is_its_first_line() {
+        let workspace = tempdir().unwrap();
+        let signer = Signer::Command(vec![
+            "printf".to_string(),
+            format!("{}\nurl: https://example.com\n", MNEMONIC),
+        ]);
+
+        assert_eq!(
+            signer.get_mnemonic(workspace.path()).unwrap().as_str(),
+            MNEMONIC
+        );
+    }
+}
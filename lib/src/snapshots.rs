@@ -0,0 +1,184 @@
+use std::{error::Error, fmt::Display, path::Path};
+
+use serde_json::Value;
+
+use crate::{
+    definition::{build_program, ProgramDefinition},
+    deployments::get_latest_success_output,
+    diff::{diff_json, Change},
+    helpers::canonicalize,
+};
+
+const SNAPSHOTS_DIR: &str = "snapshots";
+/// Set to create and update snapshots from the program tests, like `--accept` does
+const UPDATE_SNAPSHOTS_VAR: &str = "UPDATE_SNAPSHOTS";
+
+#[derive(Debug, PartialEq)]
+pub enum SnapshotStatus {
+    /// Built program config is the same as the golden one
+    Matches,
+    /// There was no golden program config and it was written, only when accepting changes
+    Created,
+    /// Golden program config differs from the built one and was replaced
+    Updated(Vec<Change>),
+    /// Golden program config differs from the built one
+    Changed(Vec<Change>),
+    /// There is no golden program config
+    Missing,
+}
+
+/// Result of comparing the program built for an env to its golden snapshot
+#[derive(Debug)]
+pub struct SnapshotResult {
+    pub env: String,
+    pub status: SnapshotStatus,
+    /// Output directory of the last successful deployment on the env and how the built program differs from it
+    pub deployed: Option<(String, Vec<Change>)>,
+}
+
+impl SnapshotResult {
+    /// The snapshot matches or was accepted, and the built program is what is deployed on the env
+    pub fn is_ok(&self) -> bool {
+        !matches!(
+            self.status,
+            SnapshotStatus::Changed(_) | SnapshotStatus::Missing
+        ) && self
+            .deployed
+            .as_ref()
+            .is_none_or(|(_, changes)| changes.is_empty())
+    }
+}
+
+impl Display for SnapshotResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let print_changes = |f: &mut std::fmt::Formatter<'_>, changes: &[Change]| {
+            changes
+                .iter()
+                .try_for_each(|change| writeln!(f, "    {change}"))
+        };
+
+        match &self.status {
+            SnapshotStatus::Matches => writeln!(f, "{}: matches snapshot", self.env)?,
+            SnapshotStatus::Created => writeln!(f, "{}: snapshot created", self.env)?,
+            SnapshotStatus::Updated(changes) => {
+                writeln!(f, "{}: snapshot updated", self.env)?;
+                print_changes(f, changes)?;
+            }
+            SnapshotStatus::Changed(changes) => {
+                writeln!(f, "{}: {} changes from snapshot", self.env, changes.len())?;
+                print_changes(f, changes)?;
+            }
+            SnapshotStatus::Missing => writeln!(f, "{}: snapshot missing", self.env)?,
+        }
+
+        if let Some((output_dir, changes)) = &self.deployed {
+            if !changes.is_empty() {
+                writeln!(
+                    f,
+                    "{}: {} changes from the last successful deployment {}",
+                    self.env,
+                    changes.len(),
+                    output_dir
+                )?;
+                print_changes(f, changes)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Build the program for every env that has a params file and compare it to the golden
+/// snapshot in `snapshots/<env>.json`. If `accept` is set, snapshots that are missing or differ
+/// are written with the built program config
+pub(crate) fn check_snapshots(
+    program: &dyn ProgramDefinition,
+    accept: bool,
+) -> Result<Vec<SnapshotResult>, Box<dyn Error>> {
+    let program_path = &program.path();
+    let snapshots_path = program_path.join(SNAPSHOTS_DIR);
+
+    let mut results = vec![];
+    for env in program.envs() {
+        let program_config = canonicalize(serde_json::to_value(build_program(program, &env)?)?);
+
+        let snapshot_path = snapshots_path.join(format!("{}.json", env));
+        let status = match read_json(&snapshot_path)? {
+            Some(snapshot) => match diff_json(&canonicalize(snapshot), &program_config) {
+                changes if changes.is_empty() => SnapshotStatus::Matches,
+                changes if accept => {
+                    write_snapshot(&snapshot_path, &program_config)?;
+                    SnapshotStatus::Updated(changes)
+                }
+                changes => SnapshotStatus::Changed(changes),
+            },
+            None if accept => {
+                write_snapshot(&snapshot_path, &program_config)?;
+                SnapshotStatus::Created
+            }
+            None => SnapshotStatus::Missing,
+        };
+
+        // The raw program config of a deployment is the builder output at the time,
+        // so any difference means what is deployed is not what we would deploy now
+        let deployed = match get_latest_success_output(program_path, &env) {
+            Some(output_path) => {
+                read_json(&output_path.join("raw-program-config.json"))?.map(|deployed| {
+                    (
+                        output_path
+                            .strip_prefix(program_path)
+                            .unwrap_or(&output_path)
+                            .display()
+                            .to_string(),
+                        diff_json(&canonicalize(deployed), &program_config),
+                    )
+                })
+            }
+            None => None,
+        };
+
+        results.push(SnapshotResult {
+            env,
+            status,
+            deployed,
+        });
+    }
+
+    Ok(results)
+}
+
+/// Check the snapshots of a program from its tests, panics if any snapshot is missing or differs
+/// from the built program, or if the built program differs from what is deployed.
+/// Snapshots are written instead if `UPDATE_SNAPSHOTS` is set
+pub fn assert_snapshots(program: &dyn ProgramDefinition) {
+    let accept = std::env::var(UPDATE_SNAPSHOTS_VAR).is_ok();
+    let results = check_snapshots(program, accept)
+        .unwrap_or_else(|e| panic!("Failed to check snapshots: {}", e));
+
+    results.iter().for_each(|result| print!("{result}"));
+
+    if results.iter().any(|result| !result.is_ok()) {
+        panic!("Program config differs from snapshots or from the last deployment, review the changes and accept them with `cargo run -p deployer -- snapshots <program> --accept`");
+    }
+}
+
+fn read_json(path: &Path) -> Result<Option<Value>, Box<dyn Error>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    Ok(Some(serde_json::from_str(&std::fs::read_to_string(path)?)?))
+}
+
+fn write_snapshot(path: &Path, program_config: &Value) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::write(
+        path,
+        format!("{}\n", serde_json::to_string_pretty(program_config)?),
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,777 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::verify::{get_address_chain, get_domain_chain, Queriers};
+
+/// Executions are read from the authorization contract in pages of this size
+const EXECUTIONS_PAGE_SIZE: u64 = 100;
+/// Queues of the processor, by priority
+const PRIORITIES: [&str; 2] = ["high", "medium"];
+
+/// Balances, processor queues and authorization executions of a deployed program
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgramStatus {
+    pub program_name: String,
+    pub program_id: Option<u64>,
+    pub env: String,
+    pub accounts: Vec<AccountStatus>,
+    pub processors: Vec<ProcessorStatus>,
+    pub authorizations: Vec<AuthorizationStatus>,
+    /// Queries that failed, the status is incomplete if any
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountStatus {
+    pub name: String,
+    pub chain: Option<String>,
+    pub addr: String,
+    /// Non zero balances by denom
+    pub balances: BTreeMap<String, u128>,
+    /// Libraries that take their input from the account
+    pub drained_by: Vec<String>,
+    /// Labels of the authorizations that execute those libraries
+    pub authorizations: Vec<String>,
+    /// The account holds funds that a library should move, but nothing is queued
+    /// on a processor or in process for those libraries
+    pub idle: bool,
+    /// The libraries can't move the funds again before this time, in seconds, after their last
+    /// successful execution, Ex: between the weekly batches of a forwarder with a min interval
+    pub waiting_until: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessorStatus {
+    pub domain: String,
+    pub addr: String,
+    pub queue: Vec<QueuedBatch>,
+}
+
+/// A batch of messages waiting on a processor for a tick
+#[derive(Debug, Clone, Serialize)]
+pub struct QueuedBatch {
+    pub id: Option<u64>,
+    pub priority: String,
+    /// Libraries of the program the batch executes
+    pub libraries: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthorizationStatus {
+    pub label: String,
+    pub executions: u64,
+    pub in_process: u64,
+    pub last_execution: Option<Execution>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Execution {
+    pub id: u64,
+    /// Ex: `success`, `in_process`, `rejected`
+    pub result: String,
+    /// As stored by the authorization contract, in seconds
+    pub last_updated_at: Option<u64>,
+}
+
+impl ProgramStatus {
+    /// Query the status of an instantiated program, queries that fail are listed in `errors`
+    pub async fn new(
+        program_config: &Value,
+        manager_config: &Value,
+        env: &str,
+        queriers: &Queriers,
+    ) -> Self {
+        let mut errors = vec![];
+        let libraries = get_entries(program_config, "libraries");
+        let now = u64::try_from(Utc::now().timestamp()).unwrap_or_default();
+
+        let mut processors = vec![];
+        for (domain, addr) in program_config
+            .pointer("/authorization_data/processor_addrs")
+            .and_then(|p| p.as_object())
+            .cloned()
+            .unwrap_or_default()
+        {
+            let addr = addr.as_str().unwrap_or_default().to_string();
+            let chain = get_domain_chain(&Value::String(domain.clone()));
+
+            let mut queue = vec![];
+            match chain.and_then(|chain| queriers.get(&chain)) {
+                Some(querier) => {
+                    for priority in PRIORITIES {
+                        let msg =
+                            json!({"get_queue": {"from": null, "to": null, "priority": priority}});
+                        match querier.smart_query(&addr, &msg).await {
+                            Ok(Value::Array(batches)) => {
+                                queue.extend(batches.iter().map(|batch| QueuedBatch {
+                                    id: batch.get("id").and_then(|id| id.as_u64()),
+                                    priority: priority.to_string(),
+                                    libraries: find_entries(batch, &libraries),
+                                }))
+                            }
+                            Ok(response) => errors.push(format!(
+                                "Unexpected {} queue of processor {}: {}",
+                                priority, domain, response
+                            )),
+                            Err(e) => errors.push(format!(
+                                "Failed to query the {} queue of processor {}: {}",
+                                priority, domain, e
+                            )),
+                        }
+                    }
+                }
+                None => errors.push(format!("No querier for the domain {}", domain)),
+            }
+
+            processors.push(ProcessorStatus {
+                domain,
+                addr,
+                queue,
+            });
+        }
+
+        let executions = match get_executions(program_config, manager_config, queriers).await {
+            Ok(executions) => executions,
+            Err(e) => {
+                errors.push(e);
+                vec![]
+            }
+        };
+        let authorizations = program_config
+            .get("authorizations")
+            .and_then(|a| a.as_array())
+            .cloned()
+            .unwrap_or_default();
+        let authorization_statuses = authorizations
+            .iter()
+            .filter_map(|authorization| authorization.get("label")?.as_str())
+            .map(|label| get_authorization_status(label, &executions))
+            .collect::<Vec<_>>();
+
+        let mut accounts = vec![];
+        for (name, account) in get_entries(program_config, "accounts") {
+            let addr = account["addr"].as_str().unwrap_or_default().to_string();
+            let chain = get_domain_chain(&account["domain"]);
+
+            let balances = match chain.as_ref().and_then(|chain| queriers.get(chain)) {
+                Some(querier) => querier.all_balances(&addr).await.unwrap_or_else(|e| {
+                    errors.push(format!("Failed to query the balances of {}: {}", name, e));
+                    BTreeMap::new()
+                }),
+                None => {
+                    errors.push(format!("No querier for the domain of {}", name));
+                    BTreeMap::new()
+                }
+            };
+
+            // Libraries reference their input account by address in an instantiated program config
+            let drained_by = libraries
+                .iter()
+                .filter(|(_, library)| {
+                    get_library_config(library, "input_addr")
+                        .and_then(get_account_addr)
+                        .is_some_and(|input| !addr.is_empty() && input == addr)
+                })
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<_>>();
+            let drained_by_addrs = drained_by
+                .iter()
+                .filter_map(|name| libraries.get(name)?["addr"].as_str())
+                .collect::<Vec<_>>();
+            let account_authorizations = authorizations
+                .iter()
+                .filter(|authorization| {
+                    drained_by_addrs
+                        .iter()
+                        .any(|addr| contains_addr(authorization, addr))
+                })
+                .filter_map(|authorization| authorization.get("label")?.as_str())
+                .map(|label| label.to_string())
+                .collect::<Vec<_>>();
+
+            // Nothing is expected to move the funds before the min interval of the libraries
+            // is over since their last successful execution
+            let waiting_until = drained_by
+                .iter()
+                .filter_map(|name| {
+                    get_library_config(&libraries[name], "forwarding_constraints")?
+                        .pointer("/min_interval/time")?
+                        .as_u64()
+                })
+                .max()
+                .and_then(|interval| {
+                    let last_success = executions
+                        .iter()
+                        .map(get_execution)
+                        .filter(|(label, execution)| {
+                            execution.result == "success" && account_authorizations.contains(label)
+                        })
+                        .filter_map(|(_, execution)| execution.last_updated_at)
+                        .max()?;
+
+                    Some(last_success.saturating_add(interval))
+                })
+                .filter(|until| *until > now);
+
+            let queued = processors
+                .iter()
+                .flat_map(|processor| &processor.queue)
+                .any(|batch| batch.libraries.iter().any(|l| drained_by.contains(l)));
+            let in_process = authorization_statuses
+                .iter()
+                .any(|a| a.in_process > 0 && account_authorizations.contains(&a.label));
+
+            accounts.push(AccountStatus {
+                idle: !balances.is_empty()
+                    && !drained_by.is_empty()
+                    && !queued
+                    && !in_process
+                    && waiting_until.is_none(),
+                waiting_until,
+                name,
+                chain,
+                addr,
+                balances,
+                drained_by,
+                authorizations: account_authorizations,
+            });
+        }
+
+        ProgramStatus {
+            program_name: program_config["name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            program_id: program_config.get("id").and_then(|id| id.as_u64()),
+            env: env.to_string(),
+            accounts,
+            processors,
+            authorizations: authorization_statuses,
+            errors,
+        }
+    }
+
+    pub fn idle_accounts(&self) -> impl Iterator<Item = &AccountStatus> {
+        self.accounts.iter().filter(|account| account.idle)
+    }
+
+    /// Metrics in the Prometheus text format, Ex: for the textfile collector of node exporter
+    pub fn to_prometheus(&self) -> String {
+        let program = format!(
+            "program=\"{}\",env=\"{}\"",
+            escape_label(&self.program_name),
+            escape_label(&self.env)
+        );
+        let mut metrics = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, samples: Vec<(String, String)>| {
+            metrics.push_str(&format!(
+                "# HELP {} {}\n# TYPE {} {}\n",
+                name, help, name, kind
+            ));
+            for (labels, value) in samples {
+                metrics.push_str(&format!("{}{{{}{}}} {}\n", name, program, labels, value));
+            }
+        };
+
+        metric(
+            "valence_account_balance",
+            "gauge",
+            "Balance of an account of the program",
+            self.accounts
+                .iter()
+                .flat_map(|account| {
+                    account.balances.iter().map(|(denom, amount)| {
+                        (
+                            format!(
+                                ",account=\"{}\",denom=\"{}\"",
+                                escape_label(&account.name),
+                                escape_label(denom)
+                            ),
+                            amount.to_string(),
+                        )
+                    })
+                })
+                .collect(),
+        );
+        metric(
+            "valence_account_idle",
+            "gauge",
+            "1 if the account holds funds with nothing queued or in process to move them",
+            self.accounts
+                .iter()
+                .map(|account| {
+                    (
+                        format!(",account=\"{}\"", escape_label(&account.name)),
+                        (account.idle as u8).to_string(),
+                    )
+                })
+                .collect(),
+        );
+        metric(
+            "valence_processor_queue_length",
+            "gauge",
+            "Batches waiting on a processor for a tick",
+            self.processors
+                .iter()
+                .flat_map(|processor| {
+                    PRIORITIES.iter().map(|priority| {
+                        (
+                            format!(
+                                ",domain=\"{}\",priority=\"{}\"",
+                                escape_label(&processor.domain),
+                                priority
+                            ),
+                            processor
+                                .queue
+                                .iter()
+                                .filter(|batch| batch.priority == *priority)
+                                .count()
+                                .to_string(),
+                        )
+                    })
+                })
+                .collect(),
+        );
+        metric(
+            "valence_authorization_executions",
+            "counter",
+            "Executions of an authorization",
+            self.authorizations
+                .iter()
+                .map(|a| {
+                    (
+                        format!(",label=\"{}\"", escape_label(&a.label)),
+                        a.executions.to_string(),
+                    )
+                })
+                .collect(),
+        );
+        metric(
+            "valence_authorization_executions_in_process",
+            "gauge",
+            "Executions of an authorization that are not done yet",
+            self.authorizations
+                .iter()
+                .map(|a| {
+                    (
+                        format!(",label=\"{}\"", escape_label(&a.label)),
+                        a.in_process.to_string(),
+                    )
+                })
+                .collect(),
+        );
+        metric(
+            "valence_status_errors",
+            "gauge",
+            "Queries that failed while getting the status",
+            vec![(String::new(), self.errors.len().to_string())],
+        );
+
+        metrics
+    }
+}
+
+impl Display for ProgramStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.program_id {
+            Some(id) => writeln!(f, "{} (program {}) on {}", self.program_name, id, self.env)?,
+            None => writeln!(f, "{} on {}", self.program_name, self.env)?,
+        }
+
+        writeln!(f, "Accounts:")?;
+        for account in &self.accounts {
+            let balances = match account.balances.is_empty() {
+                true => "empty".to_string(),
+                false => account
+                    .balances
+                    .iter()
+                    .map(|(denom, amount)| format!("{}{}", amount, denom))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            };
+            write!(f, "    {}: {}", account.name, balances)?;
+            if account.idle {
+                write!(
+                    f,
+                    " IDLE, nothing queued or in process for {}",
+                    account.drained_by.join(", ")
+                )?;
+            }
+            if let Some(until) = account
+                .waiting_until
+                .and_then(|until| DateTime::from_timestamp(i64::try_from(until).ok()?, 0))
+            {
+                write!(f, " next batch after {}", until.format("%Y-%m-%d %H:%M:%S"))?;
+            }
+            writeln!(f)?;
+        }
+
+        writeln!(f, "Processors:")?;
+        for processor in &self.processors {
+            writeln!(
+                f,
+                "    {}: {} batches queued",
+                processor.domain,
+                processor.queue.len()
+            )?;
+            for batch in &processor.queue {
+                writeln!(
+                    f,
+                    "        {} ({}): {}",
+                    batch.id.map(|id| id.to_string()).unwrap_or_default(),
+                    batch.priority,
+                    batch.libraries.join(", ")
+                )?;
+            }
+        }
+
+        writeln!(f, "Authorizations:")?;
+        for authorization in &self.authorizations {
+            write!(
+                f,
+                "    {}: {} executions, {} in process",
+                authorization.label, authorization.executions, authorization.in_process
+            )?;
+            if let Some(last) = &authorization.last_execution {
+                write!(f, ", last {} {}", last.id, last.result)?;
+            }
+            writeln!(f)?;
+        }
+
+        for error in &self.errors {
+            writeln!(f, "Error: {}", error)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Accounts or libraries of a program config by name
+fn get_entries(program_config: &Value, key: &str) -> BTreeMap<String, Value> {
+    program_config
+        .get(key)
+        .and_then(|entries| entries.as_object())
+        .map(|entries| {
+            entries
+                .values()
+                .filter_map(|entry| Some((entry.get("name")?.as_str()?.to_string(), entry.clone())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Names of the entries whose address appears in the value
+fn find_entries(value: &Value, entries: &BTreeMap<String, Value>) -> Vec<String> {
+    entries
+        .iter()
+        .filter(|(_, entry)| {
+            entry["addr"]
+                .as_str()
+                .is_some_and(|addr| contains_addr(value, addr))
+        })
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+/// Whether the address is one of the strings of the value, addresses that start with it don't count
+fn contains_addr(value: &Value, addr: &str) -> bool {
+    match value {
+        Value::String(s) => s == addr,
+        Value::Array(values) => values.iter().any(|v| contains_addr(v, addr)),
+        Value::Object(map) => map.values().any(|v| contains_addr(v, addr)),
+        _ => false,
+    }
+}
+
+/// Field of the config of a library, configs are keyed by the library type
+fn get_library_config<'a>(library: &'a Value, field: &str) -> Option<&'a Value> {
+    library["config"].as_object()?.values().next()?.get(field)
+}
+
+/// Address of an account as referenced by a library, Ex: `{"|library_account_addr|": "neutron1..."}`
+fn get_account_addr(value: &Value) -> Option<&str> {
+    match value {
+        Value::String(addr) => Some(addr),
+        Value::Object(map) if map.len() == 1 => map.values().next()?.as_str(),
+        _ => None,
+    }
+}
+
+/// Every execution stored by the authorization contract, oldest first
+async fn get_executions(
+    program_config: &Value,
+    manager_config: &Value,
+    queriers: &Queriers,
+) -> Result<Vec<Value>, String> {
+    let addr = program_config
+        .pointer("/authorization_data/authorization_addr")
+        .and_then(|a| a.as_str())
+        .ok_or("Program has no authorization contract")?;
+    let querier = get_address_chain(addr, manager_config)
+        .and_then(|chain| queriers.get(&chain))
+        .ok_or("No querier for the chain of the authorization contract")?;
+
+    let mut executions = vec![];
+    let mut start_after = None;
+    loop {
+        let msg =
+            json!({"executions": {"start_after": start_after, "limit": EXECUTIONS_PAGE_SIZE}});
+        let page = querier
+            .smart_query(addr, &msg)
+            .await
+            .map_err(|e| format!("Failed to query the executions: {}", e))?;
+        let page = page
+            .as_array()
+            .ok_or_else(|| format!("Unexpected executions: {}", page))?;
+
+        start_after = page.last().and_then(|e| e["execution_id"].as_u64());
+        executions.extend(page.iter().cloned());
+
+        if (page.len() as u64) < EXECUTIONS_PAGE_SIZE || start_after.is_none() {
+            return Ok(executions);
+        }
+    }
+}
+
+/// Label of the authorization and the execution
+fn get_execution(execution: &Value) -> (String, Execution) {
+    let label = execution["label"].as_str().unwrap_or_default().to_string();
+
+    (
+        label,
+        Execution {
+            id: execution["execution_id"].as_u64().unwrap_or_default(),
+            // Unit variants are strings, the others objects with the variant as only key
+            result: match &execution["execution_result"] {
+                Value::String(result) => result.clone(),
+                Value::Object(result) => result.keys().next().cloned().unwrap_or_default(),
+                result => result.to_string(),
+            },
+            last_updated_at: execution["last_updated_at"].as_u64(),
+        },
+    )
+}
+
+fn get_authorization_status(label: &str, executions: &[Value]) -> AuthorizationStatus {
+    let executions = executions
+        .iter()
+        .map(get_execution)
+        .filter(|(execution_label, _)| execution_label == label)
+        .map(|(_, execution)| execution)
+        .collect::<Vec<_>>();
+
+    AuthorizationStatus {
+        label: label.to_string(),
+        executions: executions.len() as u64,
+        in_process: executions
+            .iter()
+            .filter(|execution| execution.result == "in_process")
+            .count() as u64,
+        last_execution: executions.last().cloned(),
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use serde_json::{json, Value};
+
+    use super::ProgramStatus;
+    use crate::{querier::MockQuerier, verify::Queriers};
+
+    const DAY: u64 = 86_400;
+    const WEEK: u64 = 7 * DAY;
+
+    fn program_config() -> Value {
+        let domain = json!({"CosmosCosmwasm": "neutron"});
+
+        json!({
+            "name": "test",
+            "id": 3,
+            "accounts": {
+                "1": {"name": "input_account", "addr": "neutron1input", "domain": domain},
+                "2": {"name": "output_account", "addr": "neutron1output", "domain": domain},
+            },
+            "libraries": {
+                "1": {
+                    "name": "forwarder",
+                    "addr": "neutron1forwarder",
+                    "domain": domain,
+                    "config": {"ValenceForwarderLibrary": {
+                        "input_addr": "neutron1input",
+                        "output_addr": "neutron1output",
+                    }},
+                },
+            },
+            "authorizations": [{
+                "label": "forward",
+                "subroutine": {"atomic": {"functions": [{"contract_address": "neutron1forwarder"}]}},
+            }],
+            "authorization_data": {
+                "authorization_addr": "neutron1authorization",
+                "processor_addrs": {"CosmosCosmwasm:neutron": "neutron1processor"},
+            },
+        })
+    }
+
+    /// Program where the input account is forwarded to the output account at most once a week
+    fn weekly_program_config() -> Value {
+        let mut program_config = program_config();
+        program_config["libraries"]["1"]["config"]["ValenceForwarderLibrary"]
+            ["forwarding_constraints"] = json!({"min_interval": {"time": WEEK}});
+        program_config
+    }
+
+    async fn status(queue: Value, executions: Value, balance: u128) -> ProgramStatus {
+        status_of(program_config(), queue, executions, balance).await
+    }
+
+    /// Status with the high priority queue and the executions, both accounts hold the given balance
+    async fn status_of(
+        program_config: Value,
+        queue: Value,
+        executions: Value,
+        balance: u128,
+    ) -> ProgramStatus {
+        let queue_msg =
+            |priority: &str| json!({"get_queue": {"from": null, "to": null, "priority": priority}});
+        let querier = MockQuerier::new()
+            .with_balance("neutron1input", "untrn", balance)
+            .with_balance("neutron1output", "untrn", balance)
+            .with_response("neutron1processor", queue_msg("high"), queue)
+            .with_response("neutron1processor", queue_msg("medium"), json!([]))
+            .with_response(
+                "neutron1authorization",
+                json!({"executions": {"start_after": null, "limit": 100}}),
+                executions,
+            );
+        let queriers: Queriers = [("neutron".to_string(), Box::new(querier) as _)].into();
+        let manager_config = json!({"chains": {"neutron": {"prefix": "neutron"}}});
+
+        ProgramStatus::new(&program_config, &manager_config, "mainnet", &queriers).await
+    }
+
+    fn idle_accounts(status: &ProgramStatus) -> Vec<&str> {
+        status
+            .idle_accounts()
+            .map(|account| account.name.as_str())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn funded_input_with_nothing_to_move_it_is_idle() {
+        let status = status(json!([]), json!([]), 100).await;
+
+        assert!(status.errors.is_empty(), "{:?}", status.errors);
+        // The output account isn't drained by any library
+        assert_eq!(idle_accounts(&status), vec!["input_account"]);
+        let input = &status.accounts[0];
+        assert_eq!(input.drained_by, vec!["forwarder"]);
+        assert_eq!(input.authorizations, vec!["forward"]);
+    }
+
+    #[tokio::test]
+    async fn empty_input_is_not_idle() {
+        let status = status(json!([]), json!([]), 0).await;
+
+        assert!(idle_accounts(&status).is_empty());
+    }
+
+    #[tokio::test]
+    async fn queued_batch_keeps_the_input_busy() {
+        let queue = json!([{"id": 4, "msgs": [{"contract_address": "neutron1forwarder"}]}]);
+        let status = status(queue, json!([]), 100).await;
+
+        assert!(idle_accounts(&status).is_empty());
+        let batch = &status.processors[0].queue[0];
+        assert_eq!(batch.id, Some(4));
+        assert_eq!(batch.libraries, vec!["forwarder"]);
+    }
+
+    #[tokio::test]
+    async fn execution_in_process_keeps_the_input_busy() {
+        let executions = json!([
+            {"execution_id": 0, "label": "forward", "execution_result": {"rejected": {"error": "x"}}},
+            {"execution_id": 1, "label": "forward", "execution_result": "in_process"},
+        ]);
+        let status = status(json!([]), executions, 100).await;
+
+        assert!(idle_accounts(&status).is_empty());
+        let authorization = &status.authorizations[0];
+        assert_eq!((authorization.executions, authorization.in_process), (2, 1));
+        assert_eq!(
+            authorization.last_execution.as_ref().unwrap().result,
+            "in_process"
+        );
+
+        // Once the execution is done the funds wait for the next one
+        let executions = json!([
+            {"execution_id": 0, "label": "forward", "execution_result": {"rejected": {"error": "x"}}},
+        ]);
+        let status = self::status(json!([]), executions, 100).await;
+        assert_eq!(idle_accounts(&status), vec!["input_account"]);
+        assert_eq!(
+            status.authorizations[0]
+                .last_execution
+                .as_ref()
+                .unwrap()
+                .result,
+            "rejected"
+        );
+    }
+
+    #[tokio::test]
+    async fn input_is_matched_by_its_exact_address() {
+        let mut program_config = program_config();
+        program_config["accounts"]["2"]["addr"] = json!("neutron1inpu");
+        program_config["libraries"]["1"]["config"]["ValenceForwarderLibrary"]["input_addr"] =
+            json!({"|library_account_addr|": "neutron1input"});
+        let status = status_of(program_config, json!([]), json!([]), 100).await;
+
+        assert_eq!(status.accounts[0].drained_by, vec!["forwarder"]);
+        assert!(status.accounts[1].drained_by.is_empty());
+        assert_eq!(idle_accounts(&status), vec!["input_account"]);
+    }
+
+    #[tokio::test]
+    async fn input_of_a_forwarder_between_its_batches_is_not_idle() {
+        let now = u64::try_from(Utc::now().timestamp()).unwrap();
+        // Executions that are too soon are rejected, they don't delay the next batch
+        let executions = |days_ago: u64| {
+            json!([
+                {"execution_id": 0, "label": "forward", "execution_result": "success", "last_updated_at": now - days_ago * DAY},
+                {"execution_id": 1, "label": "forward", "execution_result": {"rejected": {"error": "x"}}, "last_updated_at": now},
+            ])
+        };
+
+        let status = status_of(weekly_program_config(), json!([]), executions(1), 100).await;
+        assert!(idle_accounts(&status).is_empty());
+        assert_eq!(status.accounts[0].waiting_until, Some(now - DAY + WEEK));
+
+        // The batch of this week is late
+        let status = status_of(weekly_program_config(), json!([]), executions(8), 100).await;
+        assert_eq!(idle_accounts(&status), vec!["input_account"]);
+        assert_eq!(status.accounts[0].waiting_until, None);
+    }
+
+    #[tokio::test]
+    async fn failed_queries_are_reported() {
+        let queriers = Queriers::new();
+        let manager_config = json!({"chains": {"neutron": {"prefix": "neutron"}}});
+        let status =
+            ProgramStatus::new(&program_config(), &manager_config, "mainnet", &queriers).await;
+
+        assert_eq!(status.errors.len(), 4);
+        assert!(idle_accounts(&status).is_empty());
+    }
+}
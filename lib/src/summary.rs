@@ -0,0 +1,417 @@
+use std::{collections::BTreeMap, error::Error, fmt::Display, path::Path};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    deployments::get_addresses,
+    querier::{ChainQuerier, GrpcQuerier},
+    verify::{get_address_chain, get_program_chains},
+};
+
+const DEFAULT_UI_URL: &str = "https://app.valence.zone";
+
+/// Settings of the summary printed and written after a deployment, from the `[summary]` table of `deployer.toml`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SummaryConfig {
+    /// Base url of the Valence UI, empty to not link to it
+    #[serde(default = "default_ui_url")]
+    pub ui_url: String,
+    /// Overrides per env
+    #[serde(default)]
+    pub envs: BTreeMap<String, EnvSummaryConfig>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnvSummaryConfig {
+    pub ui_url: Option<String>,
+    /// Chain ids by chain name, chains not listed are asked for their chain id
+    #[serde(default)]
+    pub chain_ids: BTreeMap<String, String>,
+}
+
+impl Default for SummaryConfig {
+    fn default() -> Self {
+        SummaryConfig {
+            ui_url: default_ui_url(),
+            envs: BTreeMap::new(),
+        }
+    }
+}
+
+fn default_ui_url() -> String {
+    DEFAULT_UI_URL.to_string()
+}
+
+impl SummaryConfig {
+    fn ui_url(&self, env: &str) -> Option<&str> {
+        let ui_url = self
+            .envs
+            .get(env)
+            .and_then(|env| env.ui_url.as_deref())
+            .unwrap_or(&self.ui_url);
+
+        Some(ui_url.trim_end_matches('/')).filter(|url| !url.is_empty())
+    }
+}
+
+/// A chain of a deployed program
+#[derive(Debug, Clone, Serialize)]
+pub struct SummaryDomain {
+    pub name: String,
+    /// Unknown if it isn't configured and the chain couldn't be asked
+    pub chain_id: Option<String>,
+    pub rpc: String,
+}
+
+/// What was deployed, printed after a successful deployment and written to `summary.json` and `summary.md`
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgramSummary {
+    pub program_id: u64,
+    pub program_name: String,
+    pub env: String,
+    /// Chain of the authorization contract and the registry, unknown if no chain of the manager config
+    /// has the prefix of the authorization address
+    pub main: Option<SummaryDomain>,
+    /// Every other chain the program has contracts on
+    pub external: Vec<SummaryDomain>,
+    pub registry_addr: String,
+    pub ui_link: Option<String>,
+    /// Addresses by account/library name, `authorization` and `processor:<domain>`
+    pub addresses: BTreeMap<String, String>,
+}
+
+impl ProgramSummary {
+    /// Summarize an instantiated program config deployed with the manager config
+    pub async fn new(
+        program_config: &Value,
+        manager_config: &Value,
+        env: &str,
+        config: &SummaryConfig,
+    ) -> Self {
+        let env_config = config.envs.get(env).cloned().unwrap_or_default();
+        let main_chain = get_main_chain(program_config, manager_config);
+
+        let mut chains = get_program_chains(program_config);
+        chains.extend(main_chain.clone());
+
+        let mut main = None;
+        let mut external = vec![];
+        for chain in chains {
+            let domain = get_domain(&chain, manager_config, &env_config).await;
+            match main_chain.as_ref() == Some(&chain) {
+                true => main = Some(domain),
+                false => external.push(domain),
+            }
+        }
+
+        let mut summary = ProgramSummary {
+            program_id: program_config
+                .get("id")
+                .and_then(|id| id.as_u64())
+                .unwrap_or_default(),
+            program_name: program_config
+                .get("name")
+                .and_then(|name| name.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            env: env.to_string(),
+            main,
+            external,
+            registry_addr: manager_config
+                .pointer("/general/registry_addr")
+                .and_then(|r| r.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            ui_link: None,
+            addresses: get_addresses(program_config),
+        };
+        summary.ui_link = config
+            .ui_url(env)
+            .and_then(|ui_url| summary.get_ui_link(ui_url));
+
+        summary
+    }
+
+    fn get_ui_link(&self, ui_url: &str) -> Option<String> {
+        let Some(main) = &self.main else {
+            warn!("The main chain of the program is unknown, not linking to the UI");
+            return None;
+        };
+
+        let domain = |domain: &SummaryDomain| {
+            json!({
+                "name": domain.name,
+                "chainId": domain.chain_id.as_deref().unwrap_or(&domain.name),
+                "rpcUrl": domain.rpc,
+            })
+        };
+        let mut main_domain = domain(main);
+        main_domain["registryAddress"] = json!(self.registry_addr);
+        let query_config = json!({
+            "main": main_domain,
+            "external": self.external.iter().map(domain).collect::<Vec<_>>(),
+        });
+
+        Some(format!(
+            "{}/programs/{}?queryConfig={}",
+            ui_url, self.program_id, query_config
+        ))
+    }
+
+    /// Write `summary.json` and `summary.md` to the output directory
+    pub(crate) fn write(&self, output_path: &Path) -> Result<(), Box<dyn Error>> {
+        std::fs::write(
+            output_path.join("summary.json"),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        std::fs::write(output_path.join("summary.md"), self.to_markdown())?;
+
+        Ok(())
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("# {} on {}\n\n", self.program_name, self.env);
+
+        md.push_str(&format!("- Program id: {}\n", self.program_id));
+        if let Some(ui_link) = &self.ui_link {
+            md.push_str(&format!("- [View on the Valence UI]({})\n", ui_link));
+        }
+        md.push_str(&format!("- Registry: `{}`\n", self.registry_addr));
+
+        md.push_str("\n## Domains\n\n| Domain | Chain id | RPC |\n| --- | --- | --- |\n");
+        for (domain, kind) in self
+            .main
+            .iter()
+            .map(|d| (d, "main"))
+            .chain(self.external.iter().map(|d| (d, "external")))
+        {
+            md.push_str(&format!(
+                "| {} ({}) | {} | {} |\n",
+                domain.name,
+                kind,
+                domain.chain_id.as_deref().unwrap_or("unknown"),
+                domain.rpc
+            ));
+        }
+
+        md.push_str("\n## Addresses\n\n| Name | Address |\n| --- | --- |\n");
+        for (name, addr) in &self.addresses {
+            md.push_str(&format!("| {} | `{}` |\n", name, addr));
+        }
+
+        md
+    }
+}
+
+impl Display for ProgramSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Program id: {}", self.program_id)?;
+        if let Some(ui_link) = &self.ui_link {
+            writeln!(f, "View program on Valence UI: {}", ui_link)?;
+        }
+
+        for domain in self.main.iter().chain(&self.external) {
+            writeln!(
+                f,
+                "    {}: {}",
+                domain.name,
+                domain.chain_id.as_deref().unwrap_or("unknown chain id")
+            )?;
+        }
+
+        // Accounts and libraries are in the summary files, only the entry points are printed
+        for (name, addr) in self
+            .addresses
+            .iter()
+            .filter(|(name, _)| *name == "authorization" || name.starts_with("processor:"))
+        {
+            writeln!(f, "    {}: {}", name, addr)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The chain whose prefix is the one of the authorization address
+fn get_main_chain(program_config: &Value, manager_config: &Value) -> Option<String> {
+    let authorization_addr = program_config
+        .pointer("/authorization_data/authorization_addr")
+        .and_then(|a| a.as_str())?;
+
+    get_address_chain(authorization_addr, manager_config)
+}
+
+async fn get_domain(
+    chain: &str,
+    manager_config: &Value,
+    config: &EnvSummaryConfig,
+) -> SummaryDomain {
+    let field = |name: &str| {
+        manager_config
+            .pointer(&format!("/chains/{}/{}", chain, name))
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string())
+    };
+
+    let chain_id = match config.chain_ids.get(chain) {
+        Some(chain_id) => Some(chain_id.clone()),
+        None => match query_chain_id(field("grpc")).await {
+            Ok(chain_id) => Some(chain_id),
+            Err(e) => {
+                warn!("Failed to get the chain id of {}: {}", chain, e);
+                None
+            }
+        },
+    };
+
+    SummaryDomain {
+        name: chain.to_string(),
+        chain_id,
+        rpc: field("rpc").unwrap_or_default(),
+    }
+}
+
+async fn query_chain_id(grpc: Option<String>) -> Result<String, Box<dyn Error>> {
+    let grpc = grpc.ok_or("No grpc endpoint in the manager config")?;
+
+    GrpcQuerier::connect(&grpc).await?.chain_id().await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::{json, Value};
+
+    use super::{EnvSummaryConfig, ProgramSummary, SummaryConfig};
+
+    fn program_config() -> Value {
+        json!({
+            "id": 7,
+            "name": "gradual-ls",
+            "accounts": {
+                "1": { "name": "receiver_acc", "addr": "neutron1receiver", "domain": "CosmosCosmwasm:neutron" },
+            },
+            "libraries": {
+                "2": { "name": "ibc_forwarder", "addr": "juno1forwarder", "domain": { "CosmosCosmwasm": "juno" } },
+            },
+            "authorization_data": {
+                "authorization_addr": "neutron1authorization",
+                "processor_addrs": {
+                    "CosmosCosmwasm:neutron": "neutron1processor",
+                    "CosmosCosmwasm:juno": "juno1processor",
+                },
+            },
+        })
+    }
+
+    fn manager_config() -> Value {
+        json!({
+            "chains": {
+                "neutron": { "prefix": "neutron", "rpc": "https://rpc.neutron" },
+                "juno": { "prefix": "juno", "rpc": "https://rpc.juno" },
+            },
+            "general": { "registry_addr": "neutron1registry" },
+        })
+    }
+
+    /// Summary config with the chain id of neutron, the one of juno is unknown without grpc
+    fn config(ui_url: &str) -> SummaryConfig {
+        let env = EnvSummaryConfig {
+            ui_url: None,
+            chain_ids: BTreeMap::from([("neutron".to_string(), "neutron-1".to_string())]),
+        };
+
+        SummaryConfig {
+            ui_url: ui_url.to_string(),
+            envs: BTreeMap::from([("mainnet".to_string(), env)]),
+        }
+    }
+
+    #[tokio::test]
+    async fn program_is_summarized() {
+        let summary = ProgramSummary::new(
+            &program_config(),
+            &manager_config(),
+            "mainnet",
+            &config("https://app.valence.zone/"),
+        )
+        .await;
+
+        let main = summary.main.as_ref().unwrap();
+        assert_eq!(
+            (main.name.as_str(), main.chain_id.as_deref()),
+            ("neutron", Some("neutron-1"))
+        );
+        assert_eq!(summary.external.len(), 1);
+        assert_eq!(summary.external[0].name, "juno");
+        assert_eq!(summary.external[0].chain_id, None);
+        assert_eq!(summary.registry_addr, "neutron1registry");
+        assert_eq!(
+            summary.addresses.keys().collect::<Vec<_>>(),
+            [
+                "authorization",
+                "ibc_forwarder",
+                "processor:CosmosCosmwasm:juno",
+                "processor:CosmosCosmwasm:neutron",
+                "receiver_acc",
+            ]
+        );
+
+        let ui_link = summary.ui_link.as_deref().unwrap();
+        let (url, query_config) = ui_link.split_once("?queryConfig=").unwrap();
+        assert_eq!(url, "https://app.valence.zone/programs/7");
+        assert_eq!(
+            serde_json::from_str::<Value>(query_config).unwrap(),
+            json!({
+                "main": {
+                    "name": "neutron",
+                    "chainId": "neutron-1",
+                    "rpcUrl": "https://rpc.neutron",
+                    "registryAddress": "neutron1registry",
+                },
+                "external": [{ "name": "juno", "chainId": "juno", "rpcUrl": "https://rpc.juno" }],
+            })
+        );
+
+        let md = summary.to_markdown();
+        assert!(md.starts_with("# gradual-ls on mainnet\n\n- Program id: 7\n"));
+        assert!(md.contains("| neutron (main) | neutron-1 | https://rpc.neutron |\n"));
+        assert!(md.contains("| juno (external) | unknown | https://rpc.juno |\n"));
+        assert!(md.contains("| receiver_acc | `neutron1receiver` |\n"));
+
+        // Only the entry points are printed
+        let printed = summary.to_string();
+        assert!(printed.contains("    authorization: neutron1authorization\n"));
+        assert!(!printed.contains("receiver_acc"));
+    }
+
+    #[tokio::test]
+    async fn empty_ui_url_has_no_link() {
+        let summary =
+            ProgramSummary::new(&program_config(), &manager_config(), "mainnet", &config("")).await;
+
+        assert_eq!(summary.ui_link, None);
+    }
+
+    #[tokio::test]
+    async fn unknown_main_chain_has_no_link() {
+        let mut manager_config = manager_config();
+        manager_config["chains"]["neutron"]["prefix"] = json!("ntrn");
+
+        let summary = ProgramSummary::new(
+            &program_config(),
+            &manager_config,
+            "mainnet",
+            &config("https://app.valence.zone"),
+        )
+        .await;
+
+        assert!(summary.main.is_none());
+        assert_eq!(summary.ui_link, None);
+    }
+}
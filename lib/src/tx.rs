@@ -0,0 +1,299 @@
+use std::{error::Error, time::Duration};
+
+use cosmos_sdk_proto::{
+    cosmos::{
+        auth::v1beta1::{
+            query_client::QueryClient as AuthQueryClient, BaseAccount, QueryAccountRequest,
+        },
+        tx::v1beta1::{
+            service_client::ServiceClient as TxServiceClient, BroadcastMode, BroadcastTxRequest,
+            GetTxRequest, SimulateRequest,
+        },
+    },
+    traits::Message,
+};
+use cosmrs::{
+    cosmwasm::MsgExecuteContract,
+    crypto::secp256k1::SigningKey,
+    tx::{Body, Fee, Msg, SignDoc, SignerInfo},
+    AccountId, Coin,
+};
+use log::info;
+use serde_json::Value;
+
+use crate::{
+    helpers::derive_account_key,
+    querier::{ChainQuerier, GrpcQuerier},
+};
+
+/// Gas used is multiplied by this to set the gas limit, simulations are not exact
+const GAS_ADJUSTMENT: f64 = 1.5;
+/// How long to wait for a broadcast transaction to be included in a block
+const TX_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Signs and broadcasts transactions on a chain of the manager config
+pub(crate) struct TxSender {
+    querier: GrpcQuerier,
+    signing_key: SigningKey,
+    address: AccountId,
+    chain_id: String,
+    gas_price: f64,
+    gas_denom: String,
+}
+
+impl TxSender {
+    /// Connect to a chain of the manager config with the first account of the mnemonic
+    pub(crate) async fn connect(
+        manager_config: &Value,
+        chain: &str,
+        mnemonic: &str,
+    ) -> Result<Self, Box<dyn Error>> {
+        let chain_info = manager_config
+            .pointer(&format!("/chains/{}", chain))
+            .ok_or_else(|| format!("Chain {} not found in manager config", chain))?;
+        let field = |name: &str| -> Result<String, Box<dyn Error>> {
+            match chain_info.get(name) {
+                Some(Value::String(s)) => Ok(s.clone()),
+                Some(Value::Number(n)) => Ok(n.to_string()),
+                _ => Err(format!("No {} for chain {} in manager config", name, chain).into()),
+            }
+        };
+
+        let signing_key = derive_account_key(mnemonic, field("coin_type")?.parse()?)?;
+        let address = signing_key
+            .public_key()
+            .account_id(&field("prefix")?)
+            .map_err(|e| e.to_string())?;
+        let querier = GrpcQuerier::connect(&field("grpc")?).await?;
+
+        Ok(TxSender {
+            chain_id: querier.chain_id().await?,
+            querier,
+            signing_key,
+            address,
+            gas_price: field("gas_price")?.parse()?,
+            gas_denom: field("gas_denom")?,
+        })
+    }
+
+    pub(crate) fn address(&self) -> String {
+        self.address.to_string()
+    }
+
+    /// Execute a contract, returns the hash of the transaction once it is included in a block
+    pub(crate) async fn execute(
+        &self,
+        contract: &str,
+        msg: &Value,
+        funds: Vec<(u128, String)>,
+    ) -> Result<String, Box<dyn Error>> {
+        let msg = MsgExecuteContract {
+            sender: self.address.clone(),
+            contract: contract
+                .parse()
+                .map_err(|e| format!("{}: {}", contract, e))?,
+            msg: serde_json::to_vec(msg)?,
+            funds: funds
+                .into_iter()
+                .map(|(amount, denom)| {
+                    Ok(Coin {
+                        amount,
+                        denom: denom.parse().map_err(|e| format!("{}: {}", denom, e))?,
+                    })
+                })
+                .collect::<Result<_, Box<dyn Error>>>()?,
+        }
+        .to_any()
+        .map_err(|e| e.to_string())?;
+        let body = Body::new(vec![msg], "", 0u32);
+
+        let (account_number, sequence) = self.get_account().await?;
+
+        // Simulate with no fee to know the gas to pay for
+        let tx = self.sign(&body, account_number, sequence, 0, 0)?;
+        let gas_used = TxServiceClient::new(self.querier.channel())
+            .simulate(SimulateRequest {
+                tx_bytes: tx,
+                ..Default::default()
+            })
+            .await?
+            .into_inner()
+            .gas_info
+            .ok_or("Simulation returned no gas info")?
+            .gas_used;
+
+        let (gas_limit, fee) = get_fee(gas_used, self.gas_price);
+        let tx = self.sign(&body, account_number, sequence, gas_limit, fee)?;
+
+        let response = TxServiceClient::new(self.querier.channel())
+            .broadcast_tx(BroadcastTxRequest {
+                tx_bytes: tx,
+                mode: BroadcastMode::Sync as i32,
+            })
+            .await?
+            .into_inner()
+            .tx_response
+            .ok_or("Broadcast returned no response")?;
+        if response.code != 0 {
+            return Err(format!("Transaction failed: {}", response.raw_log).into());
+        }
+
+        info!("Broadcast transaction {}", response.txhash);
+        self.wait_for_tx(&response.txhash).await?;
+
+        Ok(response.txhash)
+    }
+
+    async fn get_account(&self) -> Result<(u64, u64), Box<dyn Error>> {
+        let account = AuthQueryClient::new(self.querier.channel())
+            .account(QueryAccountRequest {
+                address: self.address.to_string(),
+            })
+            .await?
+            .into_inner()
+            .account
+            .ok_or_else(|| format!("Account {} not found", self.address))?;
+        let account = BaseAccount::decode(account.value.as_slice())?;
+
+        Ok((account.account_number, account.sequence))
+    }
+
+    fn sign(
+        &self,
+        body: &Body,
+        account_number: u64,
+        sequence: u64,
+        gas_limit: u64,
+        fee: u128,
+    ) -> Result<Vec<u8>, Box<dyn Error>> {
+        let fee = Coin {
+            amount: fee,
+            denom: self
+                .gas_denom
+                .parse()
+                .map_err(|e| format!("{}: {}", self.gas_denom, e))?,
+        };
+        let auth_info = SignerInfo::single_direct(Some(self.signing_key.public_key()), sequence)
+            .auth_info(Fee::from_amount_and_gas(fee, gas_limit));
+        let sign_doc = SignDoc::new(
+            body,
+            &auth_info,
+            &self
+                .chain_id
+                .parse()
+                .map_err(|e| format!("{}: {}", self.chain_id, e))?,
+            account_number,
+        )
+        .map_err(|e| e.to_string())?;
+
+        Ok(sign_doc
+            .sign(&self.signing_key)
+            .and_then(|tx| tx.to_bytes())
+            .map_err(|e| e.to_string())?)
+    }
+
+    async fn wait_for_tx(&self, hash: &str) -> Result<(), Box<dyn Error>> {
+        let started = tokio::time::Instant::now();
+
+        loop {
+            let response = TxServiceClient::new(self.querier.channel())
+                .get_tx(GetTxRequest {
+                    hash: hash.to_string(),
+                })
+                .await;
+
+            match response.map(|r| r.into_inner().tx_response) {
+                Ok(Some(tx)) if tx.code == 0 => return Ok(()),
+                Ok(Some(tx)) => {
+                    return Err(format!("Transaction {} failed: {}", hash, tx.raw_log).into())
+                }
+                // Not in a block yet
+                _ if started.elapsed() < TX_TIMEOUT => {
+                    tokio::time::sleep(Duration::from_secs(2)).await
+                }
+                _ => return Err(format!("Transaction {} was not included in a block", hash).into()),
+            }
+        }
+    }
+}
+
+/// Gas limit and fee of a transaction from the gas its simulation used
+fn get_fee(gas_used: u64, gas_price: f64) -> (u64, u128) {
+    let gas_limit = (gas_used as f64 * GAS_ADJUSTMENT).ceil() as u64;
+
+    (gas_limit, (gas_limit as f64 * gas_price).ceil() as u128)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::{get_fee, TxSender};
+
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
+
+    fn manager_config(neutron: Value) -> Value {
+        json!({"chains": {"neutron": neutron}})
+    }
+
+    /// Error of connecting, the config is invalid before any endpoint is reached
+    async fn connect_error(manager_config: Value, chain: &str, mnemonic: &str) -> String {
+        TxSender::connect(&manager_config, chain, mnemonic)
+            .await
+            .err()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn fee_covers_the_adjusted_gas() {
+        assert_eq!(get_fee(100_000, 0.0053), (150_000, 795));
+        // Rounded up, never short of gas or fee
+        assert_eq!(get_fee(3, 0.01), (5, 1));
+        assert_eq!(get_fee(0, 0.0053), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn unknown_chain_is_refused() {
+        assert_eq!(
+            connect_error(manager_config(json!({})), "juno", MNEMONIC).await,
+            "Chain juno not found in manager config"
+        );
+    }
+
+    #[tokio::test]
+    async fn chain_fields_are_required() {
+        assert_eq!(
+            connect_error(
+                manager_config(json!({"prefix": "neutron"})),
+                "neutron",
+                MNEMONIC
+            )
+            .await,
+            "No coin_type for chain neutron in manager config"
+        );
+        assert_eq!(
+            connect_error(
+                manager_config(json!({"coin_type": 118, "prefix": "neutron"})),
+                "neutron",
+                MNEMONIC
+            )
+            .await,
+            "No grpc for chain neutron in manager config"
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_mnemonic_is_refused() {
+        let config = manager_config(json!({
+            "coin_type": "118",
+            "prefix": "neutron",
+            "grpc": "http://localhost:9090",
+        }));
+
+        assert_eq!(
+            connect_error(config, "neutron", "not a mnemonic").await,
+            "bip39 error"
+        );
+    }
+}
@@ -0,0 +1,943 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fmt::Display,
+    path::Path,
+};
+
+use base64::Engine;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{
+    deployments::get_addresses,
+    diff::diff_program_configs,
+    fees::get_registry_chain,
+    helpers::canonicalize,
+    querier::{ChainQuerier, GrpcQuerier},
+};
+
+/// A value on chain that doesn't match the instantiated program config
+#[derive(Debug, Clone, Serialize)]
+pub struct Mismatch {
+    /// Name of the contract in the program config, Ex: `receiver_acc`, `authorization`
+    pub contract: String,
+    pub check: String,
+    pub expected: Value,
+    pub actual: Value,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}: expected {} got {}",
+            self.contract, self.check, self.expected, self.actual
+        )
+    }
+}
+
+/// Queriers per chain name
+pub type Queriers = BTreeMap<String, Box<dyn ChainQuerier>>;
+
+/// Verifies the contracts of a deployed program against its instantiated program config
+struct Verifier<'a> {
+    program_config: &'a Value,
+    /// Raw program config of the same deployment, used to know which accounts were instantiated by us
+    raw_program_config: Option<&'a Value>,
+    manager_config: &'a Value,
+    queriers: &'a Queriers,
+    mismatches: Vec<Mismatch>,
+}
+
+/// Verify every account, library, authorization and processor of a deployed program
+/// and the registry entry of the program match the instantiated program config
+pub async fn verify_deployment(
+    program_config: &Value,
+    raw_program_config: Option<&Value>,
+    manager_config: &Value,
+    queriers: &Queriers,
+) -> Result<Vec<Mismatch>, Box<dyn Error>> {
+    let mut verifier = Verifier {
+        program_config,
+        raw_program_config,
+        manager_config,
+        queriers,
+        mismatches: vec![],
+    };
+
+    verifier.verify_accounts().await;
+    verifier.verify_libraries().await;
+    verifier.verify_authorization().await;
+    verifier.verify_processors().await;
+    verifier.verify_registry().await;
+
+    Ok(verifier.mismatches)
+}
+
+/// Connect to the grpc endpoint of every chain the program uses
+pub async fn get_grpc_queriers(
+    program_config: &Value,
+    manager_config: &Value,
+) -> Result<Queriers, Box<dyn Error>> {
+    connect_queriers(get_program_chains(program_config), manager_config).await
+}
+
+/// Connect to every chain a program is deployed on and to the chain of the registry
+pub(crate) async fn get_deployment_queriers(
+    program_config: &Value,
+    manager_config: &Value,
+) -> Result<Queriers, Box<dyn Error>> {
+    let mut chains = get_program_chains(program_config);
+    chains.insert(get_registry_chain(manager_config));
+
+    connect_queriers(chains, manager_config).await
+}
+
+/// Connect to the grpc endpoint of the chains in the manager config
+pub(crate) async fn connect_queriers(
+    chains: impl IntoIterator<Item = String>,
+    manager_config: &Value,
+) -> Result<Queriers, Box<dyn Error>> {
+    let mut queriers: Queriers = BTreeMap::new();
+
+    for chain in chains {
+        let grpc = manager_config
+            .pointer(&format!("/chains/{}/grpc", chain))
+            .and_then(|g| g.as_str())
+            .ok_or_else(|| format!("No grpc endpoint for chain {} in manager config", chain))?;
+
+        queriers.insert(chain, Box::new(GrpcQuerier::connect(grpc).await?));
+    }
+
+    Ok(queriers)
+}
+
+/// Read an output directory and verify the deployment on chain
+pub async fn verify_output_dir(
+    output_path: &Path,
+    manager_config: &Value,
+) -> Result<Vec<Mismatch>, Box<dyn Error>> {
+    let read = |name: &str| -> Result<Value, Box<dyn Error>> {
+        let content = std::fs::read_to_string(output_path.join(name))?;
+        Ok(serde_json::from_str(&content)?)
+    };
+
+    let program_config = read("instantiated-program-config.json")
+        .map_err(|e| format!("Only successful deployments can be verified: {}", e))?;
+    let raw_program_config = read("raw-program-config.json").ok();
+
+    let queriers = get_grpc_queriers(&program_config, manager_config).await?;
+
+    verify_deployment(
+        &program_config,
+        raw_program_config.as_ref(),
+        manager_config,
+        &queriers,
+    )
+    .await
+}
+
+/// Get the chain name of a domain, Ex: `{"CosmosCosmwasm": "neutron"}` or `CosmosCosmwasm:neutron`
+pub(crate) fn get_domain_chain(domain: &Value) -> Option<String> {
+    match domain {
+        Value::Object(map) => map.values().next()?.as_str().map(|s| s.to_string()),
+        Value::String(s) => s.rsplit(':').next().map(|s| s.to_string()),
+        _ => None,
+    }
+}
+
+/// The chain of the manager config whose prefix is the one of the address
+pub(crate) fn get_address_chain(addr: &str, manager_config: &Value) -> Option<String> {
+    let (prefix, _) = addr.rsplit_once('1')?;
+
+    manager_config
+        .get("chains")?
+        .as_object()?
+        .iter()
+        .find(|(_, chain)| chain.get("prefix").and_then(|p| p.as_str()) == Some(prefix))
+        .map(|(name, _)| name.clone())
+}
+
+/// Get the names of all chains the program has contracts on
+pub(crate) fn get_program_chains(program_config: &Value) -> BTreeSet<String> {
+    let mut chains = BTreeSet::new();
+
+    for key in ["accounts", "libraries"] {
+        if let Some(entries) = program_config.get(key).and_then(|e| e.as_object()) {
+            chains.extend(
+                entries
+                    .values()
+                    .filter_map(|entry| entry.get("domain").and_then(get_domain_chain)),
+            );
+        }
+    }
+
+    if let Some(processors) = program_config
+        .pointer("/authorization_data/processor_addrs")
+        .and_then(|p| p.as_object())
+    {
+        chains.extend(
+            processors
+                .keys()
+                .filter_map(|domain| get_domain_chain(&Value::String(domain.clone()))),
+        );
+    }
+
+    chains
+}
+
+/// Convert a library config variant to its contract name, Ex: `ValenceForwarderLibrary` to `valence_forwarder_library`
+pub(crate) fn library_contract_name(variant: &str) -> String {
+    let mut name = String::new();
+
+    for (i, c) in variant.chars().enumerate() {
+        if c.is_uppercase() && i != 0 {
+            name.push('_');
+        }
+        name.push(c.to_ascii_lowercase());
+    }
+
+    name
+}
+
+/// Replace `{"|library_account_addr|": addr}` with the plain address, like contracts store them
+fn flatten_addrs(value: Value) -> Value {
+    match value {
+        Value::Object(map) if map.len() == 1 && map.contains_key("|library_account_addr|") => {
+            map["|library_account_addr|"].clone()
+        }
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| (k, flatten_addrs(v)))
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(flatten_addrs).collect()),
+        v => v,
+    }
+}
+
+impl Verifier<'_> {
+    fn mismatch(&mut self, contract: &str, check: &str, expected: Value, actual: Value) {
+        self.mismatches.push(Mismatch {
+            contract: contract.to_string(),
+            check: check.to_string(),
+            expected,
+            actual,
+        });
+    }
+
+    fn querier(&mut self, contract: &str, domain: &Value) -> Option<&'_ dyn ChainQuerier> {
+        let chain = get_domain_chain(domain).unwrap_or_default();
+
+        match self.queriers.get(&chain) {
+            Some(querier) => Some(querier.as_ref()),
+            None => {
+                self.mismatch(
+                    contract,
+                    "chain",
+                    json!(chain),
+                    json!("no querier for chain"),
+                );
+                None
+            }
+        }
+    }
+
+    fn owner(&self) -> Value {
+        self.program_config
+            .get("owner")
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn authorization_addr(&self) -> String {
+        self.program_config
+            .pointer("/authorization_data/authorization_addr")
+            .and_then(|a| a.as_str())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    /// Chain of the authorization contract, where the registry also lives
+    fn main_domain(&self) -> Value {
+        let processors = self
+            .program_config
+            .pointer("/authorization_data/processor_addrs")
+            .and_then(|p| p.as_object());
+
+        match processors {
+            Some(processors) if processors.len() == 1 => {
+                Value::String(processors.keys().next().unwrap().clone())
+            }
+            _ => json!("neutron"),
+        }
+    }
+
+    fn expected_code_id(&self, domain: &Value, contract_name: &str) -> Option<u64> {
+        let chain = get_domain_chain(domain)?;
+
+        self.manager_config
+            .pointer(&format!("/contracts/code_ids/{}/{}", chain, contract_name))
+            .and_then(|c| match c {
+                Value::Number(n) => n.as_u64(),
+                Value::String(s) => s.parse().ok(),
+                _ => None,
+            })
+    }
+
+    /// Verify the code id and the admin of a contract
+    async fn verify_contract_info(
+        &mut self,
+        name: &str,
+        addr: &str,
+        domain: &Value,
+        contract_name: Option<&str>,
+        admin: Value,
+    ) {
+        let expected_code_id = contract_name.and_then(|c| self.expected_code_id(domain, c));
+        let Some(querier) = self.querier(name, domain) else {
+            return;
+        };
+
+        match querier.contract_info(addr).await {
+            Ok(info) => {
+                if let Some(code_id) = expected_code_id {
+                    if info.code_id != code_id {
+                        self.mismatch(name, "code_id", json!(code_id), json!(info.code_id));
+                    }
+                }
+
+                if json!(info.admin) != admin {
+                    self.mismatch(name, "admin", admin, json!(info.admin));
+                }
+            }
+            Err(e) => self.mismatch(name, "contract_info", json!(addr), json!(e.to_string())),
+        }
+    }
+
+    async fn query(&mut self, name: &str, addr: &str, domain: &Value, msg: Value) -> Option<Value> {
+        let querier = self.querier(name, domain)?;
+
+        match querier.smart_query(addr, &msg).await {
+            Ok(res) => Some(res),
+            Err(e) => {
+                self.mismatch(
+                    name,
+                    &format!("query {}", msg),
+                    Value::Null,
+                    json!(e.to_string()),
+                );
+                None
+            }
+        }
+    }
+
+    async fn verify_accounts(&mut self) {
+        let accounts = self
+            .program_config
+            .get("accounts")
+            .and_then(|a| a.as_object())
+            .cloned()
+            .unwrap_or_default();
+        let addresses = get_addresses(self.program_config);
+
+        for (id, account) in accounts {
+            let account_id = id.parse::<u64>().ok();
+            let name = account["name"].as_str().unwrap_or_default().to_string();
+            let addr = account["addr"].as_str().unwrap_or_default().to_string();
+            let domain = account["domain"].clone();
+
+            // Accounts that already existed before the deployment are not ours to verify
+            let raw_ty = self
+                .raw_program_config
+                .and_then(|raw| raw.pointer(&format!("/accounts/{}/ty", id)))
+                .cloned()
+                .unwrap_or_default();
+            let Some((ty, ty_info)) = raw_ty.as_object().and_then(|ty| ty.iter().next()) else {
+                continue;
+            };
+            let contract_name = match ty.as_str() {
+                "Base" => "valence_base_account",
+                "Storage" => "valence_storage_account",
+                _ => continue,
+            };
+
+            let admin = ty_info
+                .get("admin")
+                .filter(|a| !a.is_null())
+                .cloned()
+                .unwrap_or_else(|| self.owner());
+
+            self.verify_contract_info(&name, &addr, &domain, Some(contract_name), admin)
+                .await;
+
+            // Approved libraries are all the libraries that have this account as input
+            let expected = self
+                .program_config
+                .get("links")
+                .and_then(|l| l.as_object())
+                .into_iter()
+                .flat_map(|links| links.values())
+                .filter(|link| {
+                    link["input_accounts_id"]
+                        .as_array()
+                        .is_some_and(|ids| ids.iter().any(|i| i.as_u64() == account_id))
+                })
+                .filter_map(|link| {
+                    let library_id = link["library_id"].to_string();
+                    self.program_config
+                        .pointer(&format!("/libraries/{}/name", library_id))
+                        .and_then(|n| n.as_str())
+                        .and_then(|n| addresses.get(n))
+                        .cloned()
+                })
+                .collect::<BTreeSet<_>>();
+
+            if let Some(approved) = self
+                .query(
+                    &name,
+                    &addr,
+                    &domain,
+                    json!({"list_approved_libraries": {}}),
+                )
+                .await
+            {
+                let approved = serde_json::from_value::<BTreeSet<String>>(approved.clone())
+                    .unwrap_or_default();
+
+                if approved != expected {
+                    self.mismatch(
+                        &name,
+                        "approved_libraries",
+                        json!(expected),
+                        json!(approved),
+                    );
+                }
+            }
+        }
+    }
+
+    async fn verify_libraries(&mut self) {
+        let libraries = self
+            .program_config
+            .get("libraries")
+            .and_then(|l| l.as_object())
+            .cloned()
+            .unwrap_or_default();
+        let processors = self
+            .program_config
+            .pointer("/authorization_data/processor_addrs")
+            .cloned()
+            .unwrap_or_default();
+
+        for library in libraries.values() {
+            let name = library["name"].as_str().unwrap_or_default().to_string();
+            let addr = library["addr"].as_str().unwrap_or_default().to_string();
+            let domain = library["domain"].clone();
+            let Some((variant, config)) = library["config"]
+                .as_object()
+                .and_then(|c| c.iter().next())
+                .map(|(k, v)| (k.clone(), v.clone()))
+            else {
+                continue;
+            };
+
+            self.verify_contract_info(
+                &name,
+                &addr,
+                &domain,
+                Some(&library_contract_name(&variant)),
+                self.owner(),
+            )
+            .await;
+
+            if let Some(raw_config) = self
+                .query(&name, &addr, &domain, json!({"get_raw_library_config": {}}))
+                .await
+            {
+                if canonicalize(flatten_addrs(raw_config.clone()))
+                    != canonicalize(flatten_addrs(config.clone()))
+                {
+                    self.mismatch(&name, "library_config", config, raw_config);
+                }
+            }
+
+            // The library must be processed by the processor of its domain
+            let expected_processor = processors
+                .as_object()
+                .and_then(|p| {
+                    p.iter()
+                        .find(|(d, _)| {
+                            get_domain_chain(&Value::String(d.to_string()))
+                                == get_domain_chain(&domain)
+                        })
+                        .map(|(_, addr)| addr.clone())
+                })
+                .unwrap_or_default();
+
+            if let Some(processor) = self
+                .query(&name, &addr, &domain, json!({"get_processor": {}}))
+                .await
+            {
+                if processor != expected_processor {
+                    self.mismatch(&name, "processor", expected_processor, processor);
+                }
+            }
+        }
+    }
+
+    async fn verify_authorization(&mut self) {
+        let authorization_addr = self.authorization_addr();
+        let domain = self.main_domain();
+
+        self.verify_contract_info(
+            "authorization",
+            &authorization_addr,
+            &domain,
+            Some("valence_authorization"),
+            self.owner(),
+        )
+        .await;
+
+        let Some(on_chain) = self
+            .query(
+                "authorization",
+                &authorization_addr,
+                &domain,
+                json!({"authorizations": {"limit": 100}}),
+            )
+            .await
+        else {
+            return;
+        };
+
+        let on_chain = on_chain
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|a| Some((a.get("label")?.as_str()?.to_string(), a)))
+            .collect::<BTreeMap<_, _>>();
+
+        let expected = self
+            .program_config
+            .get("authorizations")
+            .and_then(|a| a.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        for authorization in &expected {
+            let label = authorization["label"].as_str().unwrap_or_default();
+            let contract = format!("authorization:{}", label);
+
+            let Some(actual) = on_chain.get(label) else {
+                self.mismatch(&contract, "exists", json!(true), json!(false));
+                continue;
+            };
+
+            // Modes are stored with the authorization token, so we only compare the kind of mode
+            let mode_kind = |mode: &Value| -> Value {
+                match mode {
+                    Value::Object(m) => m
+                        .iter()
+                        .next()
+                        .map(|(k, v)| match v {
+                            Value::Object(inner) => {
+                                json!(format!(
+                                    "{}.{}",
+                                    k,
+                                    inner.keys().next().cloned().unwrap_or_default()
+                                ))
+                            }
+                            Value::String(inner) => json!(format!("{}.{}", k, inner)),
+                            _ => json!(k),
+                        })
+                        .unwrap_or_default(),
+                    v => v.clone(),
+                }
+            };
+            if mode_kind(&authorization["mode"]) != mode_kind(&actual["mode"]) {
+                self.mismatch(
+                    &contract,
+                    "mode",
+                    authorization["mode"].clone(),
+                    actual["mode"].clone(),
+                );
+            }
+
+            let expected_subroutine =
+                canonicalize(flatten_addrs(authorization["subroutine"].clone()));
+            let actual_subroutine = canonicalize(flatten_addrs(actual["subroutine"].clone()));
+            if expected_subroutine != actual_subroutine {
+                self.mismatch(
+                    &contract,
+                    "subroutine",
+                    expected_subroutine,
+                    actual_subroutine,
+                );
+            }
+
+            // Every operator of a permissioned authorization must hold the authorization token
+            let operators = authorization
+                .pointer("/mode/permissioned")
+                .and_then(|p| p.as_object())
+                .and_then(|p| p.values().next())
+                .and_then(|o| o.as_array())
+                .cloned()
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|o| match o {
+                    // with call limit operators are (address, limit) pairs
+                    Value::Array(pair) => {
+                        pair.first().and_then(|a| a.as_str()).map(|a| a.to_string())
+                    }
+                    Value::String(a) => Some(a),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            let denom = format!("factory/{}/{}", authorization_addr, label);
+            for operator in operators {
+                let Some(querier) = self.querier(&contract, &domain) else {
+                    break;
+                };
+
+                match querier.balance(&operator, &denom).await {
+                    Ok(0) => self.mismatch(
+                        &contract,
+                        &format!("operator {} holds {}", operator, denom),
+                        json!(true),
+                        json!(false),
+                    ),
+                    Ok(_) => (),
+                    Err(e) => self.mismatch(
+                        &contract,
+                        &format!("operator {} holds {}", operator, denom),
+                        json!(true),
+                        json!(e.to_string()),
+                    ),
+                }
+            }
+        }
+
+        for label in on_chain.keys() {
+            if !expected
+                .iter()
+                .any(|a| a["label"].as_str() == Some(label.as_str()))
+            {
+                self.mismatch(
+                    &format!("authorization:{}", label),
+                    "exists",
+                    json!(false),
+                    json!(true),
+                );
+            }
+        }
+    }
+
+    async fn verify_processors(&mut self) {
+        let processors = self
+            .program_config
+            .pointer("/authorization_data/processor_addrs")
+            .and_then(|p| p.as_object())
+            .cloned()
+            .unwrap_or_default();
+        let authorization_addr = self.authorization_addr();
+
+        for (domain, addr) in processors {
+            let name = format!("processor:{}", domain);
+            let addr = addr.as_str().unwrap_or_default().to_string();
+            let domain = Value::String(domain);
+
+            self.verify_contract_info(
+                &name,
+                &addr,
+                &domain,
+                Some("valence_processor"),
+                self.owner(),
+            )
+            .await;
+
+            if let Some(config) = self
+                .query(&name, &addr, &domain, json!({"config": {}}))
+                .await
+            {
+                let actual = config
+                    .get("authorization_contract")
+                    .cloned()
+                    .unwrap_or_default();
+
+                if actual != json!(authorization_addr) {
+                    self.mismatch(
+                        &name,
+                        "authorization_contract",
+                        json!(authorization_addr),
+                        actual,
+                    );
+                }
+            }
+        }
+    }
+
+    async fn verify_registry(&mut self) {
+        let registry_addr = self
+            .manager_config
+            .pointer("/general/registry_addr")
+            .and_then(|r| r.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let program_id = self.program_config.get("id").cloned().unwrap_or_default();
+        let domain = self.main_domain();
+
+        let Some(response) = self
+            .query(
+                "registry",
+                &registry_addr,
+                &domain,
+                json!({"get_config": {"id": program_id}}),
+            )
+            .await
+        else {
+            return;
+        };
+
+        let registered = response
+            .get("program_config")
+            .and_then(|c| c.as_str())
+            .and_then(|c| base64::engine::general_purpose::STANDARD.decode(c).ok())
+            .and_then(|c| serde_json::from_slice::<Value>(&c).ok());
+
+        let Some(registered) = registered else {
+            self.mismatch("registry", "program_config", program_id, response);
+            return;
+        };
+
+        let changes = diff_program_configs(self.program_config, &registered);
+        if !changes.is_empty() {
+            self.mismatch(
+                "registry",
+                "program_config",
+                Value::Null,
+                json!(changes.iter().map(|c| c.to_string()).collect::<Vec<_>>()),
+            );
+        }
+
+        let expected = get_addresses(self.program_config);
+        let actual = get_addresses(&registered);
+        if expected != actual {
+            self.mismatch("registry", "addresses", json!(expected), json!(actual));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::Engine;
+    use serde_json::{json, Value};
+
+    use super::{verify_deployment, Mismatch, Queriers};
+    use crate::querier::{ContractInfo, MockQuerier};
+
+    const OWNER: &str = "neutron1owner";
+
+    fn program_config() -> Value {
+        json!({
+            "id": 7,
+            "name": "Program",
+            "owner": OWNER,
+            "accounts": {
+                "1": {
+                    "name": "input",
+                    "ty": { "Base": { "admin": null } },
+                    "domain": { "CosmosCosmwasm": "neutron" },
+                    "addr": "neutron1input",
+                },
+            },
+            "libraries": {
+                "1": {
+                    "name": "forwarder",
+                    "domain": { "CosmosCosmwasm": "neutron" },
+                    "config": {
+                        "ValenceForwarderLibrary": {
+                            "input_addr": { "|library_account_addr|": "neutron1input" },
+                            "output_addr": "neutron1dao",
+                        }
+                    },
+                    "addr": "neutron1forwarder",
+                },
+            },
+            "links": {
+                "1": { "input_accounts_id": [1], "output_accounts_id": [], "library_id": 1 },
+            },
+            "authorizations": [{
+                "label": "forward",
+                "mode": { "permissioned": { "without_call_limit": ["neutron1operator"] } },
+                "subroutine": { "atomic": { "functions": [{
+                    "domain": "main",
+                    "message_details": { "message_type": "cosmwasm_execute_msg", "message": { "name": "process_function" } },
+                    "contract_address": { "|library_account_addr|": "neutron1forwarder" },
+                }] } },
+            }],
+            "authorization_data": {
+                "authorization_addr": "neutron1authorization",
+                "processor_addrs": { "CosmosCosmwasm:neutron": "neutron1processor" },
+            },
+        })
+    }
+
+    fn manager_config() -> Value {
+        json!({
+            "chains": { "neutron": { "prefix": "neutron" } },
+            "general": { "registry_addr": "neutron1registry" },
+            "contracts": { "code_ids": { "neutron": {
+                "valence_base_account": 1,
+                "valence_forwarder_library": 2,
+                "valence_authorization": 3,
+                "valence_processor": 4,
+            } } },
+        })
+    }
+
+    fn contract(code_id: u64) -> ContractInfo {
+        ContractInfo {
+            code_id,
+            admin: Some(OWNER.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Chain with the program of `program_config` deployed as it should be
+    fn deployed_chain() -> MockQuerier {
+        let config = program_config();
+        let registered = base64::engine::general_purpose::STANDARD.encode(config.to_string());
+
+        MockQuerier::new()
+            .with_contract("neutron1input", contract(1))
+            .with_response(
+                "neutron1input",
+                json!({"list_approved_libraries": {}}),
+                json!(["neutron1forwarder"]),
+            )
+            .with_contract("neutron1forwarder", contract(2))
+            .with_response(
+                "neutron1forwarder",
+                json!({"get_raw_library_config": {}}),
+                json!({ "input_addr": "neutron1input", "output_addr": "neutron1dao" }),
+            )
+            .with_response(
+                "neutron1forwarder",
+                json!({"get_processor": {}}),
+                json!("neutron1processor"),
+            )
+            .with_contract("neutron1authorization", contract(3))
+            .with_response(
+                "neutron1authorization",
+                json!({"authorizations": {"limit": 100}}),
+                json!([{
+                    "label": "forward",
+                    "mode": { "permissioned": "without_call_limit" },
+                    "subroutine": config["authorizations"][0]["subroutine"],
+                }]),
+            )
+            .with_balance(
+                "neutron1operator",
+                "factory/neutron1authorization/forward",
+                1,
+            )
+            .with_contract("neutron1processor", contract(4))
+            .with_response(
+                "neutron1processor",
+                json!({"config": {}}),
+                json!({ "authorization_contract": "neutron1authorization" }),
+            )
+            .with_response(
+                "neutron1registry",
+                json!({"get_config": {"id": 7}}),
+                json!({ "program_config": registered }),
+            )
+    }
+
+    async fn verify(program_config: &Value, chain: MockQuerier) -> Vec<Mismatch> {
+        let queriers: Queriers = [("neutron".to_string(), Box::new(chain) as _)].into();
+
+        verify_deployment(
+            program_config,
+            Some(program_config),
+            &manager_config(),
+            &queriers,
+        )
+        .await
+        .unwrap()
+    }
+
+    fn checks(mismatches: &[Mismatch]) -> Vec<(&str, &str)> {
+        mismatches
+            .iter()
+            .map(|m| (m.contract.as_str(), m.check.as_str()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn deployed_program_matches() {
+        let mismatches = verify(&program_config(), deployed_chain()).await;
+
+        assert!(mismatches.is_empty(), "{:?}", mismatches);
+    }
+
+    #[tokio::test]
+    async fn account_admin_mismatch() {
+        let chain = deployed_chain().with_contract(
+            "neutron1input",
+            ContractInfo {
+                code_id: 1,
+                admin: Some("neutron1attacker".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let mismatches = verify(&program_config(), chain).await;
+
+        assert_eq!(checks(&mismatches), vec![("input", "admin")]);
+        assert_eq!(mismatches[0].expected, json!(OWNER));
+        assert_eq!(mismatches[0].actual, json!("neutron1attacker"));
+    }
+
+    #[tokio::test]
+    async fn library_missing_on_chain() {
+        let mut program_config = program_config();
+        program_config["libraries"]["2"] = json!({
+            "name": "splitter",
+            "domain": { "CosmosCosmwasm": "neutron" },
+            "config": { "ValenceSplitterLibrary": {} },
+            "addr": "neutron1splitter",
+        });
+
+        let mismatches = verify(&program_config, deployed_chain()).await;
+
+        assert!(checks(&mismatches).contains(&("splitter", "contract_info")));
+    }
+
+    #[tokio::test]
+    async fn wrong_authorization_on_chain() {
+        let chain = deployed_chain().with_response(
+            "neutron1authorization",
+            json!({"authorizations": {"limit": 100}}),
+            json!([{
+                "label": "forward",
+                "mode": { "permissionless": {} },
+                "subroutine": { "atomic": { "functions": [] } },
+            }]),
+        );
+
+        let mismatches = verify(&program_config(), chain).await;
+
+        assert_eq!(
+            checks(&mismatches),
+            vec![
+                ("authorization:forward", "mode"),
+                ("authorization:forward", "subroutine")
+            ]
+        );
+    }
+}
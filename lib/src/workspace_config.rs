@@ -0,0 +1,37 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::{
+    audit::AuditConfig, error::DeployerError, production::ProductionConfig, summary::SummaryConfig,
+};
+
+const WORKSPACE_CONFIG_FILE: &str = "deployer.toml";
+
+/// Settings of the deployer for the whole workspace, read from `deployer.toml` at its root
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceConfig {
+    #[serde(default)]
+    pub production: ProductionConfig,
+    #[serde(default)]
+    pub summary: SummaryConfig,
+    #[serde(default)]
+    pub audit: AuditConfig,
+}
+
+impl WorkspaceConfig {
+    /// A workspace without `deployer.toml` has the default settings
+    pub fn load(workspace: &Path) -> Result<Self, DeployerError> {
+        let path = workspace.join(WORKSPACE_CONFIG_FILE);
+        if !path.exists() {
+            return Ok(WorkspaceConfig::default());
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| DeployerError::Path(format!("{}: {}", path.display(), e)))?;
+
+        toml::from_str(&content)
+            .map_err(|e| DeployerError::Validation(format!("Invalid {}: {}", path.display(), e)))
+    }
+}
@@ -0,0 +1,37 @@
+mod program_builder;
+
+use std::{error::Error, path::PathBuf};
+
+use deployer_lib::{ProgramDefinition, ProgramParams};
+use valence_program_manager::program_config::ProgramConfig;
+
+pub struct TestBootstrapNtrnDntrn;
+
+impl ProgramDefinition for TestBootstrapNtrnDntrn {
+    fn path(&self) -> PathBuf {
+        env!("CARGO_MANIFEST_DIR").into()
+    }
+
+    fn description(&self) -> String {
+        "Test program bootstrapping the NTRN-dNTRN liquidity pool".to_string()
+    }
+
+    /// The ntrn-devnet params were superseded by ntrn-devnet-2 before any deployment
+    fn envs(&self) -> Vec<String> {
+        vec!["ntrn-devnet-2".to_string()]
+    }
+
+    fn build(&self, params: ProgramParams) -> Result<ProgramConfig, Box<dyn Error>> {
+        program_builder::program_builder(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TestBootstrapNtrnDntrn;
+
+    #[test]
+    fn program_config_matches_snapshots() {
+        deployer_lib::assert_snapshots(&TestBootstrapNtrnDntrn);
+    }
+}
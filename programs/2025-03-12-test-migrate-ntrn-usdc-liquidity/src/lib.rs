@@ -0,0 +1,37 @@
+mod program_builder;
+
+use std::{error::Error, path::PathBuf};
+
+use deployer_lib::{ProgramDefinition, ProgramParams};
+use valence_program_manager::program_config::ProgramConfig;
+
+pub struct TestMigrateNtrnUsdcLiquidity;
+
+impl ProgramDefinition for TestMigrateNtrnUsdcLiquidity {
+    fn path(&self) -> PathBuf {
+        env!("CARGO_MANIFEST_DIR").into()
+    }
+
+    fn description(&self) -> String {
+        "Test program migrating NTRN-USDC liquidity".to_string()
+    }
+
+    /// Test program, only deployed on the devnets
+    fn envs(&self) -> Vec<String> {
+        vec!["ntrn-devnet".to_string(), "ntrn-devnet-2".to_string()]
+    }
+
+    fn build(&self, params: ProgramParams) -> Result<ProgramConfig, Box<dyn Error>> {
+        program_builder::program_builder(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TestMigrateNtrnUsdcLiquidity;
+
+    #[test]
+    fn program_config_matches_snapshots() {
+        deployer_lib::assert_snapshots(&TestMigrateNtrnUsdcLiquidity);
+    }
+}
@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{error::Error, str::FromStr};
 
 use cosmwasm_std::{to_json_binary, Decimal};
 use deployer_lib::EMPTY_VEC;
@@ -15,23 +15,25 @@ use valence_program_manager::{
 };
 
 /// Write your program using the program builder
-pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
+pub fn program_builder(
+    params: deployer_lib::ProgramParams,
+) -> Result<ProgramConfig, Box<dyn Error>> {
     // program params
-    let owner = params.get("owner");
-    let init_lp_token_denom = params.get("init_lp_token_denom");
-    let usdc_dntrn_lp_token_denom = params.get("usdc_dntrn_lp_token_denom");
-    let usdc_ntrn_pool_addr = params.get("usdc_ntrn_pool_addr");
-    let usdc_dntrn_pool_addr = params.get("usdc_dntrn_pool_addr");
-    let ntrn_denom = params.get("ntrn_denom");
-    let dntrn_denom = params.get("dntrn_denom");
-    let usdc_denom = params.get("usdc_denom");
-    let drop_liquid_staker_addr = params.get("drop_liquid_staker_addr");
-    let pool_max_spread = params.get("pool_max_spread");
-    let neutron_dao_addr = params.get("neutron_dao_addr");
-    let security_dao_addr = params.get("security_dao_addr");
-    let expected_pool_ratio_min = params.get("expected_pool_ratio_min");
-    let expected_pool_ratio_max = params.get("expected_pool_ratio_max");
-    let authorizations_allowed_list = params.get_array("authorizations_allowed_list");
+    let owner = params.get("owner")?;
+    let init_lp_token_denom = params.get("init_lp_token_denom")?;
+    let usdc_dntrn_lp_token_denom = params.get("usdc_dntrn_lp_token_denom")?;
+    let usdc_ntrn_pool_addr = params.get("usdc_ntrn_pool_addr")?;
+    let usdc_dntrn_pool_addr = params.get("usdc_dntrn_pool_addr")?;
+    let ntrn_denom = params.get("ntrn_denom")?;
+    let dntrn_denom = params.get("dntrn_denom")?;
+    let usdc_denom = params.get("usdc_denom")?;
+    let drop_liquid_staker_addr = params.get("drop_liquid_staker_addr")?;
+    let pool_max_spread = params.get("pool_max_spread")?;
+    let neutron_dao_addr = params.get("neutron_dao_addr")?;
+    let security_dao_addr = params.get("security_dao_addr")?;
+    let expected_pool_ratio_min = params.get("expected_pool_ratio_min")?;
+    let expected_pool_ratio_max = params.get("expected_pool_ratio_max")?;
+    let authorizations_allowed_list = params.get_array("authorizations_allowed_list")?;
 
     let permissioned_all_mode =
         valence_authorization_utils::authorization::AuthorizationModeInfo::Permissioned(
@@ -210,7 +212,7 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
     } else {
         Some(
             Decimal::from_str(pool_max_spread.as_str())
-                .expect("pool_max_spread must be valid Decimal string"),
+                .map_err(|e| format!("pool_max_spread must be valid Decimal string: {}", e))?,
         )
     };
     let astroport_lper_config = valence_astroport_lper::msg::LibraryConfig {
@@ -443,10 +445,12 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
     // Provide double sided liquidity
     let expected_pool_ratio_range =
         Some(valence_library_utils::liquidity_utils::DecimalRange::new(
-            Decimal::from_str(expected_pool_ratio_min.as_str())
-                .expect("expected_pool_ratio_min must be parsed into Decimal"),
-            Decimal::from_str(expected_pool_ratio_max.as_str())
-                .expect("expected_pool_ratio_max must be parsed into Decimal"),
+            Decimal::from_str(expected_pool_ratio_min.as_str()).map_err(|e| {
+                format!("expected_pool_ratio_min must be parsed into Decimal: {}", e)
+            })?,
+            Decimal::from_str(expected_pool_ratio_max.as_str()).map_err(|e| {
+                format!("expected_pool_ratio_max must be parsed into Decimal: {}", e)
+            })?,
         ));
     let double_sided_lp_func = AtomicFunctionBuilder::new()
         .with_contract_address(lib_astroport_lper.clone())
@@ -460,8 +464,9 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
                         "provide_double_sided_liquidity".to_string(),
                         "expected_pool_ratio_range".to_string(),
                     ],
-                    to_json_binary(&expected_pool_ratio_range)
-                        .expect("expected_pool_ratio_range must parse to binary"),
+                    to_json_binary(&expected_pool_ratio_range).map_err(|e| {
+                        format!("expected_pool_ratio_range must parse to binary: {}", e)
+                    })?,
                 )]),
             },
         })
@@ -667,5 +672,5 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
 
     builder.add_authorization(authorization);
 
-    builder.build()
+    Ok(builder.build())
 }
@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::error::Error;
 
 use cosmwasm_std::Decimal;
 use deployer_lib::EMPTY_VEC;
@@ -14,19 +14,21 @@ use valence_program_manager::{
 };
 
 /// Write your program using the program builder
-pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
+pub fn program_builder(
+    params: deployer_lib::ProgramParams,
+) -> Result<ProgramConfig, Box<dyn Error>> {
     // program params
-    let owner = params.get("owner");
-    let ntrn_denom = params.get("ntrn_denom");
-    let dntrn_denom = params.get("dntrn_denom");
-    let bootstrap_ntrn_dntrn_receive_addr = params.get("bootstrap_ntrn_dntrn_receive_addr");
-    let drop_liquid_staker_addr = params.get("drop_liquid_staker_addr");
-    let drop_liquid_unstaker_addr = params.get("drop_liquid_unstaker_addr");
-    let drop_withdrawal_manager_addr = params.get("drop_withdrawal_manager_addr");
-    let voucher_addr = params.get("voucher_addr");
-    let neutron_dao_addr = params.get("neutron_dao_addr");
-    let security_dao_addr = params.get("security_dao_addr");
-    let authorizations_allowed_list = params.get_array("authorizations_allowed_list");
+    let owner = params.get("owner")?;
+    let ntrn_denom = params.get("ntrn_denom")?;
+    let dntrn_denom = params.get("dntrn_denom")?;
+    let bootstrap_ntrn_dntrn_receive_addr = params.get("bootstrap_ntrn_dntrn_receive_addr")?;
+    let drop_liquid_staker_addr = params.get("drop_liquid_staker_addr")?;
+    let drop_liquid_unstaker_addr = params.get("drop_liquid_unstaker_addr")?;
+    let drop_withdrawal_manager_addr = params.get("drop_withdrawal_manager_addr")?;
+    let voucher_addr = params.get("voucher_addr")?;
+    let neutron_dao_addr = params.get("neutron_dao_addr")?;
+    let security_dao_addr = params.get("security_dao_addr")?;
+    let authorizations_allowed_list = params.get_array("authorizations_allowed_list")?;
 
     let permissioned_all_mode =
         valence_authorization_utils::authorization::AuthorizationModeInfo::Permissioned(
@@ -118,16 +120,16 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
             valence_splitter_library::msg::UncheckedSplitConfig::new(
                 cw_denom::UncheckedDenom::Native(dntrn_denom.clone()),
                 acc_stake_holder.clone(),
-                valence_splitter_library::msg::UncheckedSplitAmount::FixedRatio(
-                    Decimal::from_str("0.5").expect("Decimal::from_str failed"),
-                ),
+                valence_splitter_library::msg::UncheckedSplitAmount::FixedRatio(Decimal::percent(
+                    50,
+                )),
             ),
             valence_splitter_library::msg::UncheckedSplitConfig::new(
                 cw_denom::UncheckedDenom::Native(dntrn_denom.clone()),
                 bootstrap_ntrn_dntrn_receive_addr.as_str(),
-                valence_splitter_library::msg::UncheckedSplitAmount::FixedRatio(
-                    Decimal::from_str("0.5").expect("Decimal::from_str failed"),
-                ),
+                valence_splitter_library::msg::UncheckedSplitAmount::FixedRatio(Decimal::percent(
+                    50,
+                )),
             ),
         ],
     };
@@ -438,5 +440,5 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
 
     builder.add_authorization(authorization);
 
-    builder.build()
+    Ok(builder.build())
 }
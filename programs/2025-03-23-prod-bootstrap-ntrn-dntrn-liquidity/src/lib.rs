@@ -0,0 +1,80 @@
+mod program_builder;
+
+use std::{error::Error, path::PathBuf};
+
+use deployer_lib::{ProgramDefinition, ProgramParams};
+use valence_program_manager::program_config::ProgramConfig;
+
+pub struct BootstrapNtrnDntrnLiquidity;
+
+impl ProgramDefinition for BootstrapNtrnDntrnLiquidity {
+    fn path(&self) -> PathBuf {
+        env!("CARGO_MANIFEST_DIR").into()
+    }
+
+    fn description(&self) -> String {
+        "dICS Program 4: bootstraps the NTRN-dNTRN liquidity pool".to_string()
+    }
+
+    fn build(&self, params: ProgramParams) -> Result<ProgramConfig, Box<dyn Error>> {
+        program_builder::program_builder(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use cosmwasm_std::Uint128;
+    use deployer_lib::ProgramDefinition;
+    use deployer_simulator::{AstroportPoolMock, Simulation};
+    use serde_json::json;
+
+    use super::BootstrapNtrnDntrnLiquidity;
+
+    #[test]
+    fn program_config_matches_snapshots() {
+        deployer_lib::assert_snapshots(&BootstrapNtrnDntrnLiquidity);
+    }
+
+    #[test]
+    fn lp_tokens_reach_the_dao() {
+        let params =
+            deployer_lib::get_program_params(Path::new(env!("CARGO_MANIFEST_DIR")), "mainnet")
+                .unwrap();
+        let ntrn_denom = params.get("ntrn_denom").unwrap();
+        let dntrn_denom = params.get("dntrn_denom").unwrap();
+        let pool_addr = params.get("astroport_pool_addr").unwrap();
+        let neutron_dao_addr = params.get("neutron_dao_addr").unwrap();
+        let operator = params.get_array("operator_list").unwrap()[0].clone();
+        let lp_denom = format!("factory/{}/astroport/share", pool_addr);
+
+        // Pool ratio of 1, inside the expected range
+        let pool = AstroportPoolMock {
+            addr: pool_addr,
+            denoms: [ntrn_denom.clone(), dntrn_denom.clone()],
+            lp_denom: lp_denom.clone(),
+            pair_type: json!({"custom": "concentrated"}),
+            reserves: [Uint128::new(1_000_000_000_000); 2],
+            total_share: Uint128::new(1_000_000_000_000),
+        };
+        let mut sim = Simulation::builder(&BootstrapNtrnDntrnLiquidity.build(params).unwrap())
+            .unwrap()
+            .with_astroport_pool(pool)
+            .build()
+            .unwrap();
+        sim.fund("receive_acc", 1_000_000_000, &ntrn_denom).unwrap();
+        sim.fund("receive_acc", 1_000_000_000, &dntrn_denom)
+            .unwrap();
+
+        sim.execute(&operator, "double_sided_lp").unwrap();
+        sim.tick().unwrap();
+
+        assert_eq!(
+            sim.balance(&neutron_dao_addr, &lp_denom).unwrap(),
+            1_000_000_000
+        );
+        assert_eq!(sim.balance("receive_acc", &ntrn_denom).unwrap(), 0);
+        assert_eq!(sim.balance("receive_acc", &dntrn_denom).unwrap(), 0);
+    }
+}
@@ -1,4 +1,4 @@
-use std::{str::FromStr, vec};
+use std::{error::Error, str::FromStr, vec};
 
 use cosmwasm_std::{to_json_binary, Decimal};
 use deployer_lib::EMPTY_VEC;
@@ -15,25 +15,27 @@ use valence_program_manager::{
 };
 
 /// Write your program using the program builder
-pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
+pub fn program_builder(
+    params: deployer_lib::ProgramParams,
+) -> Result<ProgramConfig, Box<dyn Error>> {
     // Get program params from params.toml
-    let owner = params.get("owner");
+    let owner = params.get("owner")?;
     // denoms
-    let ntrn_denom = params.get("ntrn_denom");
-    let dntrn_denom = params.get("dntrn_denom");
+    let ntrn_denom = params.get("ntrn_denom")?;
+    let dntrn_denom = params.get("dntrn_denom")?;
     // pool configuration
-    let astroport_pool_addr = params.get("astroport_pool_addr");
-    let pool_max_spread = params.get("pool_max_spread");
-    let expected_pool_ratio_min = params.get("expected_pool_ratio_min");
-    let expected_pool_ratio_max = params.get("expected_pool_ratio_max");
+    let astroport_pool_addr = params.get("astroport_pool_addr")?;
+    let pool_max_spread = params.get("pool_max_spread")?;
+    let expected_pool_ratio_min = params.get("expected_pool_ratio_min")?;
+    let expected_pool_ratio_max = params.get("expected_pool_ratio_max")?;
     // forwarder configuration
-    let ntrn_forwarder_amount = params.get("ntrn_forwarder_amount");
-    let dntrn_forwarder_amount = params.get("dntrn_forwarder_amount");
-    let forwarder_interval_between_calls = params.get("forwarder_interval_between_calls");
+    let ntrn_forwarder_amount = params.get("ntrn_forwarder_amount")?;
+    let dntrn_forwarder_amount = params.get("dntrn_forwarder_amount")?;
+    let forwarder_interval_between_calls = params.get("forwarder_interval_between_calls")?;
     // allowed addresses
-    let neutron_dao_addr = params.get("neutron_dao_addr");
-    let security_dao_addr = params.get("security_dao_addr");
-    let operator_list = params.get_array("operator_list");
+    let neutron_dao_addr = params.get("neutron_dao_addr")?;
+    let security_dao_addr = params.get("security_dao_addr")?;
+    let operator_list = params.get_array("operator_list")?;
 
     let permissioned_all_mode =
         valence_authorization_utils::authorization::AuthorizationModeInfo::Permissioned(
@@ -66,9 +68,12 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
             None
         } else {
             Some(cw_utils::Duration::Time(
-                forwarder_interval_between_calls
-                    .parse()
-                    .expect("forwarder_interval_between_calls is not a valid number"),
+                forwarder_interval_between_calls.parse().map_err(|e| {
+                    format!(
+                        "forwarder_interval_between_calls is not a valid number: {}",
+                        e
+                    )
+                })?,
             ))
         };
     let return_forwarder_config = valence_forwarder_library::msg::LibraryConfig {
@@ -79,14 +84,14 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
                 cw_denom::UncheckedDenom::Native(ntrn_denom.clone()),
                 ntrn_forwarder_amount
                     .parse()
-                    .expect("ntrn_forwarder_amount is not a valid number"),
+                    .map_err(|e| format!("ntrn_forwarder_amount is not a valid number: {}", e))?,
             )
                 .into(),
             (
                 cw_denom::UncheckedDenom::Native(dntrn_denom.clone()),
                 dntrn_forwarder_amount
                     .parse()
-                    .expect("dntrn_forwarder_amount is not a valid number"),
+                    .map_err(|e| format!("dntrn_forwarder_amount is not a valid number: {}", e))?,
             )
                 .into(),
         ],
@@ -110,7 +115,7 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
     } else {
         Some(
             Decimal::from_str(pool_max_spread.as_str())
-                .expect("pool_max_spread must be valid Decimal string"),
+                .map_err(|e| format!("pool_max_spread must be valid Decimal string: {}", e))?,
         )
     };
     let astroport_lper_config = valence_astroport_lper::msg::LibraryConfig {
@@ -182,10 +187,12 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
     // Provide double sided liquidity
     let expected_pool_ratio_range =
         Some(valence_library_utils::liquidity_utils::DecimalRange::new(
-            Decimal::from_str(expected_pool_ratio_min.as_str())
-                .expect("expected_pool_ratio_min must be parsed into Decimal"),
-            Decimal::from_str(expected_pool_ratio_max.as_str())
-                .expect("expected_pool_ratio_max must be parsed into Decimal"),
+            Decimal::from_str(expected_pool_ratio_min.as_str()).map_err(|e| {
+                format!("expected_pool_ratio_min must be parsed into Decimal: {}", e)
+            })?,
+            Decimal::from_str(expected_pool_ratio_max.as_str()).map_err(|e| {
+                format!("expected_pool_ratio_max must be parsed into Decimal: {}", e)
+            })?,
         ));
     let double_sided_lp_func = AtomicFunctionBuilder::new()
         .with_contract_address(lib_astroport_lper.clone())
@@ -199,8 +206,9 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
                         "provide_double_sided_liquidity".to_string(),
                         "expected_pool_ratio_range".to_string(),
                     ],
-                    to_json_binary(&expected_pool_ratio_range)
-                        .expect("expected_pool_ratio_range must parse to binary"),
+                    to_json_binary(&expected_pool_ratio_range).map_err(|e| {
+                        format!("expected_pool_ratio_range must parse to binary: {}", e)
+                    })?,
                 )]),
             },
         })
@@ -326,5 +334,5 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
 
     builder.add_authorization(authorization);
 
-    builder.build()
+    Ok(builder.build())
 }
@@ -0,0 +1,92 @@
+mod program_builder;
+
+use std::{error::Error, path::PathBuf};
+
+use deployer_lib::{ParamKind, ParamSpec, ProgramDefinition, ProgramIo, ProgramParams};
+use valence_program_manager::program_config::ProgramConfig;
+
+pub struct GradualLs;
+
+impl ProgramDefinition for GradualLs {
+    fn path(&self) -> PathBuf {
+        env!("CARGO_MANIFEST_DIR").into()
+    }
+
+    fn description(&self) -> String {
+        "dICS Program 3: forwards NTRN in batches to be liquid staked with Drop for the Neutron DAO"
+            .to_string()
+    }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::new("owner", ParamKind::Address, "Owner of the program"),
+            ParamSpec::new("ntrn_denom", ParamKind::Denom, "NTRN denom"),
+            ParamSpec::new(
+                "drop_liquid_staker_addr",
+                ParamKind::Address,
+                "Drop staker core contract",
+            ),
+            ParamSpec::new(
+                "max_amount_to_forward",
+                ParamKind::Amount,
+                "Max size of a batch",
+            ),
+            ParamSpec::new(
+                "interval_seconds_between_batches",
+                ParamKind::Seconds,
+                "Min interval between batches, no interval if empty or 0",
+            )
+            .optional(),
+            ParamSpec::new(
+                "neutron_dao_addr",
+                ParamKind::Address,
+                "Receives the dNTRN, can update the forwarder config",
+            ),
+            ParamSpec::new(
+                "security_dao_addr",
+                ParamKind::Address,
+                "Can update the forwarder config",
+            ),
+            ParamSpec::new(
+                "operator_list",
+                ParamKind::AddressList,
+                "Can forward and liquid stake batches",
+            ),
+        ]
+    }
+
+    fn inputs(&self) -> Vec<ProgramIo> {
+        vec![ProgramIo::new(
+            "receiver_acc",
+            "ntrn_denom",
+            "NTRN to liquid stake",
+        )]
+    }
+
+    fn outputs(&self) -> Vec<ProgramIo> {
+        vec![ProgramIo::new(
+            "neutron_dao_addr",
+            "ntrn_denom",
+            "Liquid staked as dNTRN",
+        )]
+    }
+
+    fn build(&self, params: ProgramParams) -> Result<ProgramConfig, Box<dyn Error>> {
+        program_builder::program_builder(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GradualLs;
+
+    #[test]
+    fn program_config_matches_snapshots() {
+        deployer_lib::assert_snapshots(&GradualLs);
+    }
+
+    #[test]
+    fn scenarios_pass() {
+        deployer_lib::assert_scenarios(&GradualLs);
+    }
+}
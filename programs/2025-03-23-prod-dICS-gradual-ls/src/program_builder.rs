@@ -1,3 +1,5 @@
+use std::error::Error;
+
 use deployer_lib::EMPTY_VEC;
 use valence_authorization_utils::{
     authorization_message::{Message, MessageDetails, MessageType, ParamRestriction},
@@ -10,17 +12,21 @@ use valence_program_manager::{
     program_config_builder::ProgramConfigBuilder,
 };
 
-pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
+pub fn program_builder(
+    params: deployer_lib::ProgramParams,
+) -> Result<ProgramConfig, Box<dyn Error>> {
     // program params
 
-    let owner = params.get("owner");
-    let ntrn_denom = params.get("ntrn_denom");
-    let drop_liquid_staker_addr = params.get("drop_liquid_staker_addr");
-    let max_amount_to_forward = params.get("max_amount_to_forward");
-    let interval_seconds_between_batches = params.get("interval_seconds_between_batches");
-    let neutron_dao_addr = params.get("neutron_dao_addr");
-    let security_dao_addr = params.get("security_dao_addr");
-    let operator_list = params.get_array("operator_list");
+    let owner = params.get("owner")?;
+    let ntrn_denom = params.get("ntrn_denom")?;
+    let drop_liquid_staker_addr = params.get("drop_liquid_staker_addr")?;
+    let max_amount_to_forward = params.get("max_amount_to_forward")?;
+    let interval_seconds_between_batches = params
+        .get("interval_seconds_between_batches")
+        .unwrap_or_default();
+    let neutron_dao_addr = params.get("neutron_dao_addr")?;
+    let security_dao_addr = params.get("security_dao_addr")?;
+    let operator_list = params.get_array("operator_list")?;
 
     let permissioned_all_mode =
         valence_authorization_utils::authorization::AuthorizationModeInfo::Permissioned(
@@ -56,7 +62,7 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
             Some(cw_utils::Duration::Time(
                 interval_seconds_between_batches
                     .parse()
-                    .expect("interval_seconds_between_batches is not a valid number"),
+                    .map_err(|_| "interval_seconds_between_batches is not a valid number")?,
             ))
         };
     let drip_forwarder_config = valence_forwarder_library::msg::LibraryConfig {
@@ -66,7 +72,7 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
             cw_denom::UncheckedDenom::Native(ntrn_denom.clone()),
             max_amount_to_forward
                 .parse()
-                .expect("max_amount_to_forward is not a valid number"),
+                .map_err(|_| "max_amount_to_forward is not a valid number")?,
         )
             .into()],
         forwarding_constraints: valence_forwarder_library::msg::ForwardingConstraints::new(
@@ -195,5 +201,5 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
 
     builder.add_authorization(authorization);
 
-    builder.build()
+    Ok(builder.build())
 }
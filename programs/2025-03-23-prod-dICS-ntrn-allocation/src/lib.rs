@@ -0,0 +1,144 @@
+mod program_builder;
+
+use std::{error::Error, path::PathBuf};
+
+use deployer_lib::{ParamKind, ParamSpec, ProgramDefinition, ProgramIo, ProgramParams};
+use valence_program_manager::program_config::ProgramConfig;
+
+pub struct NtrnAllocation;
+
+impl ProgramDefinition for NtrnAllocation {
+    fn path(&self) -> PathBuf {
+        env!("CARGO_MANIFEST_DIR").into()
+    }
+
+    fn description(&self) -> String {
+        "dICS Program 1: splits the NTRN received from the Neutron DAO between dICS programs 2, 3 and 4"
+            .to_string()
+    }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        let mut params = vec![
+            ParamSpec::new("owner", ParamKind::Address, "Owner of the program"),
+            ParamSpec::new("ntrn_denom", ParamKind::Denom, "NTRN denom"),
+        ];
+        for (address, amount, program) in [
+            (
+                "vp2_instant_ls_receiver_address",
+                "vp2_instant_ls_amount",
+                "instant liquid stake",
+            ),
+            (
+                "vp3_gradual_ls_receiver_address",
+                "vp3_gradual_ls_receiver_amount",
+                "gradual liquid stake",
+            ),
+            (
+                "vp4_bootstrap_liquidity_receiver_address",
+                "vp4_bootstrap_liquidity_receiver_amount",
+                "bootstrap NTRN-dNTRN liquidity",
+            ),
+        ] {
+            params.push(ParamSpec::new(
+                address,
+                ParamKind::Address,
+                &format!("Receiver account of the {} program", program),
+            ));
+            params.push(ParamSpec::new(
+                amount,
+                ParamKind::Amount,
+                &format!("NTRN sent to the {} program", program),
+            ));
+        }
+        params.push(ParamSpec::new(
+            "neutron_dao_addr",
+            ParamKind::Address,
+            "Neutron DAO address",
+        ));
+        params.push(ParamSpec::new(
+            "operator_list",
+            ParamKind::AddressList,
+            "Can split the NTRN",
+        ));
+        params
+    }
+
+    fn inputs(&self) -> Vec<ProgramIo> {
+        vec![ProgramIo::new(
+            "ntrn_receiver",
+            "ntrn_denom",
+            "NTRN from the Neutron DAO",
+        )]
+    }
+
+    fn outputs(&self) -> Vec<ProgramIo> {
+        [
+            ("vp2_instant_ls_receiver_address", "Instant liquid stake"),
+            ("vp3_gradual_ls_receiver_address", "Gradual liquid stake"),
+            (
+                "vp4_bootstrap_liquidity_receiver_address",
+                "Bootstrap NTRN-dNTRN liquidity",
+            ),
+        ]
+        .into_iter()
+        .map(|(account, description)| ProgramIo::new(account, "ntrn_denom", description))
+        .collect()
+    }
+
+    fn build(&self, params: ProgramParams) -> Result<ProgramConfig, Box<dyn Error>> {
+        program_builder::program_builder(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use deployer_lib::ProgramDefinition;
+    use deployer_simulator::Simulation;
+
+    use super::NtrnAllocation;
+
+    #[test]
+    fn program_config_matches_snapshots() {
+        deployer_lib::assert_snapshots(&NtrnAllocation);
+    }
+
+    #[test]
+    fn splits_150m_ntrn_allocation() {
+        let params =
+            deployer_lib::get_program_params(Path::new(env!("CARGO_MANIFEST_DIR")), "mainnet")
+                .unwrap();
+        let ntrn_denom = params.get("ntrn_denom").unwrap();
+        let operator = params.get_array("operator_list").unwrap()[0].clone();
+        let receivers = [
+            ("vp2_instant_ls_receiver_address", "vp2_instant_ls_amount"),
+            (
+                "vp3_gradual_ls_receiver_address",
+                "vp3_gradual_ls_receiver_amount",
+            ),
+            (
+                "vp4_bootstrap_liquidity_receiver_address",
+                "vp4_bootstrap_liquidity_receiver_amount",
+            ),
+        ]
+        .map(|(addr, amount)| {
+            (
+                params.get(addr).unwrap(),
+                params.get(amount).unwrap().parse::<u128>().unwrap(),
+            )
+        });
+
+        let mut sim = Simulation::new(&NtrnAllocation.build(params).unwrap()).unwrap();
+        sim.fund("ntrn_receiver", 150_000_000_000_000, &ntrn_denom)
+            .unwrap();
+
+        sim.execute(&operator, "split_ntrn").unwrap();
+        sim.tick().unwrap();
+
+        for (addr, amount) in receivers {
+            assert_eq!(sim.balance(&addr, &ntrn_denom).unwrap(), amount);
+        }
+        assert_eq!(sim.balance("ntrn_receiver", &ntrn_denom).unwrap(), 0);
+    }
+}
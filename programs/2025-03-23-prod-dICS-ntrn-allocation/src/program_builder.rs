@@ -1,3 +1,5 @@
+use std::error::Error;
+
 use deployer_lib::EMPTY_VEC;
 use valence_authorization_utils::{
     authorization_message::{Message, MessageDetails, MessageType, ParamRestriction},
@@ -11,29 +13,31 @@ use valence_program_manager::{
 };
 
 /// Write your program using the program builder
-pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
+pub fn program_builder(
+    params: deployer_lib::ProgramParams,
+) -> Result<ProgramConfig, Box<dyn Error>> {
     // Get program params
-    let owner = params.get("owner");
-    let ntrn_denom = params.get("ntrn_denom");
+    let owner = params.get("owner")?;
+    let ntrn_denom = params.get("ntrn_denom")?;
 
     // Valence Program 2: Instant liquid stake
-    let vp2_instant_ls_receiver_address = params.get("vp2_instant_ls_receiver_address");
-    let vp2_instant_ls_amount = params.get("vp2_instant_ls_amount");
+    let vp2_instant_ls_receiver_address = params.get("vp2_instant_ls_receiver_address")?;
+    let vp2_instant_ls_amount = params.get("vp2_instant_ls_amount")?;
 
     // Valence Program 3: Gradual liquid stake
-    let vp3_gradual_ls_receiver_address = params.get("vp3_gradual_ls_receiver_address");
-    let vp3_gradual_ls_receiver_amount = params.get("vp3_gradual_ls_receiver_amount");
+    let vp3_gradual_ls_receiver_address = params.get("vp3_gradual_ls_receiver_address")?;
+    let vp3_gradual_ls_receiver_amount = params.get("vp3_gradual_ls_receiver_amount")?;
 
     // Valence Program 4: Bootstrap NTRN-dNTRN liquidity
     let vp4_bootstrap_liquidity_receiver_address =
-        params.get("vp4_bootstrap_liquidity_receiver_address");
+        params.get("vp4_bootstrap_liquidity_receiver_address")?;
     let vp4_bootstrap_liquidity_receiver_amount =
-        params.get("vp4_bootstrap_liquidity_receiver_amount");
+        params.get("vp4_bootstrap_liquidity_receiver_amount")?;
 
     // Neutron DAO address
-    let neutron_dao_addr = params.get("neutron_dao_addr");
+    let neutron_dao_addr = params.get("neutron_dao_addr")?;
     // List of address that are allowed to execute low security operations
-    let operator_list = params.get_array("operator_list");
+    let operator_list = params.get_array("operator_list")?;
 
     let permissioned_all_mode =
         valence_authorization_utils::authorization::AuthorizationModeInfo::Permissioned(
@@ -64,27 +68,30 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
                 cw_denom::UncheckedDenom::Native(ntrn_denom.clone()),
                 vp2_instant_ls_receiver_address.as_str(),
                 valence_splitter_library::msg::UncheckedSplitAmount::FixedAmount(
-                    vp2_instant_ls_amount
-                        .parse()
-                        .expect("Failed to parse vp2_instant_ls_amount as Uint128"),
+                    vp2_instant_ls_amount.parse().map_err(|e| {
+                        format!("Failed to parse vp2_instant_ls_amount as Uint128: {}", e)
+                    })?,
                 ),
             ),
             valence_splitter_library::msg::UncheckedSplitConfig::new(
                 cw_denom::UncheckedDenom::Native(ntrn_denom.clone()),
                 vp3_gradual_ls_receiver_address.as_str(),
                 valence_splitter_library::msg::UncheckedSplitAmount::FixedAmount(
-                    vp3_gradual_ls_receiver_amount
-                        .parse()
-                        .expect("Failed to parse vp3_gradual_ls_receiver_amount as Uint128"),
+                    vp3_gradual_ls_receiver_amount.parse().map_err(|e| {
+                        format!("Failed to parse vp3_gradual_ls_receiver_amount as Uint128: {}", e)
+                    })?,
                 ),
             ),
             valence_splitter_library::msg::UncheckedSplitConfig::new(
                 cw_denom::UncheckedDenom::Native(ntrn_denom.clone()),
                 vp4_bootstrap_liquidity_receiver_address.as_str(),
                 valence_splitter_library::msg::UncheckedSplitAmount::FixedAmount(
-                    vp4_bootstrap_liquidity_receiver_amount.parse().expect(
-                        "Failed to parse vp4_bootstrap_liquidity_receiver_amount as Uint128",
-                    ),
+                    vp4_bootstrap_liquidity_receiver_amount.parse().map_err(|e| {
+                        format!(
+                            "Failed to parse vp4_bootstrap_liquidity_receiver_amount as Uint128: {}",
+                            e
+                        )
+                    })?,
                 ),
             ),
         ],
@@ -157,5 +164,5 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
 
     builder.add_authorization(authorization);
 
-    builder.build()
+    Ok(builder.build())
 }
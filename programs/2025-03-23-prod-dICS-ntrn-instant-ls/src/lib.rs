@@ -0,0 +1,32 @@
+mod program_builder;
+
+use std::{error::Error, path::PathBuf};
+
+use deployer_lib::{ProgramDefinition, ProgramParams};
+use valence_program_manager::program_config::ProgramConfig;
+
+pub struct NtrnInstantLs;
+
+impl ProgramDefinition for NtrnInstantLs {
+    fn path(&self) -> PathBuf {
+        env!("CARGO_MANIFEST_DIR").into()
+    }
+
+    fn description(&self) -> String {
+        "dICS Program 2: liquid stakes NTRN with Drop at once".to_string()
+    }
+
+    fn build(&self, params: ProgramParams) -> Result<ProgramConfig, Box<dyn Error>> {
+        program_builder::program_builder(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NtrnInstantLs;
+
+    #[test]
+    fn program_config_matches_snapshots() {
+        deployer_lib::assert_snapshots(&NtrnInstantLs);
+    }
+}
@@ -1,3 +1,5 @@
+use std::error::Error;
+
 use deployer_lib::EMPTY_VEC;
 use valence_authorization_utils::{
     authorization_message::{Message, MessageDetails, MessageType, ParamRestriction},
@@ -11,20 +13,23 @@ use valence_program_manager::{
 };
 
 /// Write your program using the program builder
-pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
+pub fn program_builder(
+    params: deployer_lib::ProgramParams,
+) -> Result<ProgramConfig, Box<dyn Error>> {
     // program params
-    let owner = params.get("owner");
-    let ntrn_denom = params.get("ntrn_denom");
-    let dntrn_denom = params.get("dntrn_denom");
-    let neutron_dao_addr = params.get("neutron_dao_addr");
+    let owner = params.get("owner")?;
+    let ntrn_denom = params.get("ntrn_denom")?;
+    let dntrn_denom = params.get("dntrn_denom")?;
+    let neutron_dao_addr = params.get("neutron_dao_addr")?;
 
     let vp4_receiver_split_normalized_fraction =
-        params.get("vp4_receiver_split_normalized_fraction");
+        params.get("vp4_receiver_split_normalized_fraction")?;
 
     // Addresses
-    let vp4_bootstrap_liquidity_receiver_addr = params.get("vp4_bootstrap_liquidity_receiver_addr");
-    let drop_liquid_staker_addr = params.get("drop_liquid_staker_addr");
-    let operator_list = params.get_array("operator_list");
+    let vp4_bootstrap_liquidity_receiver_addr =
+        params.get("vp4_bootstrap_liquidity_receiver_addr")?;
+    let drop_liquid_staker_addr = params.get("drop_liquid_staker_addr")?;
+    let operator_list = params.get_array("operator_list")?;
 
     let permissioned_all_mode =
         valence_authorization_utils::authorization::AuthorizationModeInfo::Permissioned(
@@ -81,7 +86,12 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
             valence_splitter_library::msg::UncheckedSplitAmount::FixedRatio(
                 vp4_receiver_split_normalized_fraction
                     .parse()
-                    .expect("Failed to parse vp4_receiver_split_normalized_fraction as Decimal"),
+                    .map_err(|e| {
+                        format!(
+                            "Failed to parse vp4_receiver_split_normalized_fraction as Decimal: {}",
+                            e
+                        )
+                    })?,
             ),
         )],
     };
@@ -180,5 +190,5 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
 
     builder.add_authorization(authorization);
 
-    builder.build()
+    Ok(builder.build())
 }
@@ -0,0 +1,37 @@
+mod program_builder;
+
+use std::{error::Error, path::PathBuf};
+
+use deployer_lib::{ProgramDefinition, ProgramParams};
+use valence_program_manager::program_config::ProgramConfig;
+
+pub struct MigrateUsdcNtrnLiquidity;
+
+impl ProgramDefinition for MigrateUsdcNtrnLiquidity {
+    fn path(&self) -> PathBuf {
+        env!("CARGO_MANIFEST_DIR").into()
+    }
+
+    fn description(&self) -> String {
+        "dICS Program 5: migrates USDC-NTRN liquidity to USDC-dNTRN".to_string()
+    }
+
+    fn build(&self, params: ProgramParams) -> Result<ProgramConfig, Box<dyn Error>> {
+        program_builder::program_builder(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MigrateUsdcNtrnLiquidity;
+
+    #[test]
+    fn program_config_matches_snapshots() {
+        deployer_lib::assert_snapshots(&MigrateUsdcNtrnLiquidity);
+    }
+
+    #[test]
+    fn scenarios_pass() {
+        deployer_lib::assert_scenarios(&MigrateUsdcNtrnLiquidity);
+    }
+}
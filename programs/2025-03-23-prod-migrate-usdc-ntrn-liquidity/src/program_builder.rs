@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{error::Error, str::FromStr};
 
 use cosmwasm_std::{to_json_binary, Decimal};
 use deployer_lib::EMPTY_VEC;
@@ -14,34 +14,36 @@ use valence_program_manager::{
 };
 
 /// Write your program using the program builder
-pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
+pub fn program_builder(
+    params: deployer_lib::ProgramParams,
+) -> Result<ProgramConfig, Box<dyn Error>> {
     // program params
-    let owner = params.get("owner");
+    let owner = params.get("owner")?;
     // Get the token denom params
-    let usdc_denom = params.get("usdc_denom");
-    let ntrn_denom = params.get("ntrn_denom");
-    let dntrn_denom = params.get("dntrn_denom");
-    let usdc_ntrn_lp_denom = params.get("usdc_ntrn_lp_denom");
+    let usdc_denom = params.get("usdc_denom")?;
+    let ntrn_denom = params.get("ntrn_denom")?;
+    let dntrn_denom = params.get("dntrn_denom")?;
+    let usdc_ntrn_lp_denom = params.get("usdc_ntrn_lp_denom")?;
     // Get USDC-NTRN lp token batching params
-    let usdc_ntrn_lp_max_batch_size = params.get("usdc_ntrn_lp_max_batch_size");
-    let usdc_ntrn_lp_batch_interval_seconds = params.get("usdc_ntrn_lp_batch_interval_seconds");
+    let usdc_ntrn_lp_max_batch_size = params.get("usdc_ntrn_lp_max_batch_size")?;
+    let usdc_ntrn_lp_batch_interval_seconds = params.get("usdc_ntrn_lp_batch_interval_seconds")?;
     // Get USDC-NTRN pool params
-    let usdc_ntrn_pool_addr = params.get("usdc_ntrn_pool_addr");
+    let usdc_ntrn_pool_addr = params.get("usdc_ntrn_pool_addr")?;
     // Get drop liquid staker params
-    let drop_liquid_staker_addr = params.get("drop_liquid_staker_addr");
+    let drop_liquid_staker_addr = params.get("drop_liquid_staker_addr")?;
     // Get liquidity provisioning params
-    let usdc_dntrn_pool_addr = params.get("usdc_dntrn_pool_addr");
-    let expected_pool_ratio_min = params.get("expected_pool_ratio_min");
-    let expected_pool_ratio_max = params.get("expected_pool_ratio_max");
-    let pool_max_spread = params.get("pool_max_spread");
+    let usdc_dntrn_pool_addr = params.get("usdc_dntrn_pool_addr")?;
+    let expected_pool_ratio_min = params.get("expected_pool_ratio_min")?;
+    let expected_pool_ratio_max = params.get("expected_pool_ratio_max")?;
+    let pool_max_spread = params.get("pool_max_spread")?;
     // Get actor addresses
-    let neutron_dao_addr = params.get("neutron_dao_addr");
-    let security_dao_addr = params.get("security_dao_addr");
-    let operator_list = params.get_array("operator_list");
+    let neutron_dao_addr = params.get("neutron_dao_addr")?;
+    let security_dao_addr = params.get("security_dao_addr")?;
+    let operator_list = params.get_array("operator_list")?;
 
     // Get forwarder params. These are the max amounts that can be forwarded in a single call.
-    let usdc_forwarder_max_amount = params.get("usdc_forwarder_max_amount");
-    let return_forwarder_max_amount = params.get("return_forwarder_max_amount");
+    let usdc_forwarder_max_amount = params.get("usdc_forwarder_max_amount")?;
+    let return_forwarder_max_amount = params.get("return_forwarder_max_amount")?;
 
     let permissioned_all_mode =
         valence_authorization_utils::authorization::AuthorizationModeInfo::Permissioned(
@@ -91,9 +93,12 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
         None
     } else {
         Some(cw_utils::Duration::Time(
-            usdc_ntrn_lp_batch_interval_seconds
-                .parse()
-                .expect("usdc_ntrn_lp_batch_interval_seconds is not a valid number"),
+            usdc_ntrn_lp_batch_interval_seconds.parse().map_err(|e| {
+                format!(
+                    "usdc_ntrn_lp_batch_interval_seconds is not a valid number: {}",
+                    e
+                )
+            })?,
         ))
     };
     let ntrn_usdc_lp_forwarder_config = valence_forwarder_library::msg::LibraryConfig {
@@ -103,7 +108,7 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
             cw_denom::UncheckedDenom::Native(usdc_ntrn_lp_denom.clone()),
             usdc_ntrn_lp_max_batch_size
                 .parse()
-                .expect("usdc_ntrn_lp_max_batch_size is not a valid number"),
+                .map_err(|e| format!("usdc_ntrn_lp_max_batch_size is not a valid number: {}", e))?,
         )
             .into()],
         forwarding_constraints: valence_forwarder_library::msg::ForwardingConstraints::new(
@@ -161,7 +166,7 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
             cw_denom::UncheckedDenom::Native(usdc_denom.clone()),
             usdc_forwarder_max_amount
                 .parse()
-                .expect("usdc_forwarder_max_amount is not a valid number"),
+                .map_err(|e| format!("usdc_forwarder_max_amount is not a valid number: {}", e))?,
         )
             .into()],
         forwarding_constraints: valence_forwarder_library::msg::ForwardingConstraints::new(None),
@@ -211,7 +216,7 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
     } else {
         Some(
             Decimal::from_str(pool_max_spread.as_str())
-                .expect("pool_max_spread must be valid Decimal string"),
+                .map_err(|e| format!("pool_max_spread must be valid Decimal string: {}", e))?,
         )
     };
 
@@ -249,16 +254,16 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
         forwarding_configs: vec![
             (
                 cw_denom::UncheckedDenom::Native(usdc_denom.clone()),
-                return_forwarder_max_amount
-                    .parse()
-                    .expect("return_forwarder_max_amount is not a valid number"),
+                return_forwarder_max_amount.parse().map_err(|e| {
+                    format!("return_forwarder_max_amount is not a valid number: {}", e)
+                })?,
             )
                 .into(),
             (
                 cw_denom::UncheckedDenom::Native(dntrn_denom.clone()),
-                return_forwarder_max_amount
-                    .parse()
-                    .expect("return_forwarder_max_amount is not a valid number"),
+                return_forwarder_max_amount.parse().map_err(|e| {
+                    format!("return_forwarder_max_amount is not a valid number: {}", e)
+                })?,
             )
                 .into(),
         ],
@@ -381,10 +386,12 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
     // Create an authorization to provide double sided liquidity
     let expected_pool_ratio_range =
         Some(valence_library_utils::liquidity_utils::DecimalRange::new(
-            Decimal::from_str(expected_pool_ratio_min.as_str())
-                .expect("expected_pool_ratio_min must be parsed into Decimal"),
-            Decimal::from_str(expected_pool_ratio_max.as_str())
-                .expect("expected_pool_ratio_max must be parsed into Decimal"),
+            Decimal::from_str(expected_pool_ratio_min.as_str()).map_err(|e| {
+                format!("expected_pool_ratio_min must be parsed into Decimal: {}", e)
+            })?,
+            Decimal::from_str(expected_pool_ratio_max.as_str()).map_err(|e| {
+                format!("expected_pool_ratio_max must be parsed into Decimal: {}", e)
+            })?,
         ));
     let provide_double_sided_liquidity_func = AtomicFunctionBuilder::new()
         .with_contract_address(lib_astroport_lper.clone())
@@ -398,8 +405,9 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
                         "provide_double_sided_liquidity".to_string(),
                         "expected_pool_ratio_range".to_string(),
                     ],
-                    to_json_binary(&expected_pool_ratio_range)
-                        .expect("expected_pool_ratio_range must parse to binary"),
+                    to_json_binary(&expected_pool_ratio_range).map_err(|e| {
+                        format!("expected_pool_ratio_range must parse to binary: {}", e)
+                    })?,
                 )]),
             },
         })
@@ -602,5 +610,5 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
 
     builder.add_authorization(authorization);
 
-    builder.build()
+    Ok(builder.build())
 }
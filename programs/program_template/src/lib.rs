@@ -0,0 +1,59 @@
+mod program_builder;
+
+use std::{error::Error, path::PathBuf};
+
+use deployer_lib::{ParamKind, ParamSpec, ProgramDefinition, ProgramIo, ProgramParams};
+use valence_program_manager::program_config::ProgramConfig;
+
+/// Describe your program, tooling reads this without building the program
+pub struct ExampleProgram;
+
+impl ProgramDefinition for ExampleProgram {
+    fn path(&self) -> PathBuf {
+        env!("CARGO_MANIFEST_DIR").into()
+    }
+
+    fn description(&self) -> String {
+        "Example program that moves funds between 2 accounts".to_string()
+    }
+
+    /// The template isn't deployed, remove this when copying it so every env with a params file
+    /// is built and checked against its snapshot
+    fn envs(&self) -> Vec<String> {
+        vec![]
+    }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::new("owner", ParamKind::Address, "Owner of the program"),
+            ParamSpec::new("denom", ParamKind::Denom, "Denom to forward"),
+            ParamSpec::new(
+                "max_forward_amount",
+                ParamKind::Amount,
+                "Max amount to forward at once",
+            ),
+        ]
+    }
+
+    fn inputs(&self) -> Vec<ProgramIo> {
+        vec![ProgramIo::new("first_account", "denom", "Funds to forward")]
+    }
+
+    fn outputs(&self) -> Vec<ProgramIo> {
+        vec![ProgramIo::new("second_account", "denom", "Forwarded funds")]
+    }
+
+    fn build(&self, params: ProgramParams) -> Result<ProgramConfig, Box<dyn Error>> {
+        program_builder::program_builder(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExampleProgram;
+
+    #[test]
+    fn program_config_matches_snapshots() {
+        deployer_lib::assert_snapshots(&ExampleProgram);
+    }
+}
@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{error::Error, str::FromStr};
 
 use cosmwasm_std::Uint128;
 use valence_authorization_utils::{
@@ -29,15 +29,17 @@ use valence_program_manager::{
 //
 // The program config is then built
 
-/// Write your program using the program builder
-pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
+/// Write your program using the program builder, invalid params should return an error instead of panicking
+pub fn program_builder(
+    params: deployer_lib::ProgramParams,
+) -> Result<ProgramConfig, Box<dyn Error>> {
     //---- program params ----//
     // Owner of the program
-    let owner = params.get("owner");
+    let owner = params.get("owner")?;
     // Denom to use for forwarding
-    let denom = params.get("denom");
+    let denom = params.get("denom")?;
     // Max amount to forward
-    let max_forward_amount = params.get("max_forward_amount");
+    let max_forward_amount = Uint128::from_str(params.get("max_forward_amount")?.as_str())?;
 
     //---- Set builder ----//
     let mut builder = ProgramConfigBuilder::new("example-program", owner.as_str());
@@ -68,7 +70,7 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
         output_addr: acc_second.clone(),
         forwarding_configs: vec![valence_forwarder_library::msg::UncheckedForwardingConfig {
             denom: UncheckedDenom::Native(denom.clone()),
-            max_amount: Uint128::from_str(max_forward_amount.as_str()).unwrap(),
+            max_amount: max_forward_amount,
         }],
         forwarding_constraints: ForwardingConstraints::new(None),
     };
@@ -87,7 +89,7 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
         output_addr: acc_first.clone(),
         forwarding_configs: vec![valence_forwarder_library::msg::UncheckedForwardingConfig {
             denom: UncheckedDenom::Native(denom),
-            max_amount: Uint128::from_str(max_forward_amount.as_str()).unwrap(),
+            max_amount: max_forward_amount,
         }],
         forwarding_constraints: ForwardingConstraints::new(None),
     };
@@ -154,5 +156,5 @@ pub fn program_builder(params: deployer_lib::ProgramParams) -> ProgramConfig {
     builder.add_authorization(authorization);
 
     // Build program config
-    builder.build()
+    Ok(builder.build())
 }
@@ -0,0 +1,52 @@
+use cosmwasm_std::Empty;
+use cw_multi_test::{Contract, ContractWrapper};
+
+/// Wrap the entry points of a valence contract crate
+macro_rules! valence_contract {
+    ($contract:ident) => {
+        Box::new(ContractWrapper::new(
+            $contract::contract::execute,
+            $contract::contract::instantiate,
+            $contract::contract::query,
+        ))
+    };
+}
+
+pub(crate) fn base_account() -> Box<dyn Contract<Empty>> {
+    valence_contract!(valence_base_account)
+}
+
+pub(crate) fn storage_account() -> Box<dyn Contract<Empty>> {
+    valence_contract!(valence_storage_account)
+}
+
+pub(crate) fn authorization() -> Box<dyn Contract<Empty>> {
+    valence_contract!(valence_authorization)
+}
+
+pub(crate) fn processor() -> Box<dyn Contract<Empty>> {
+    Box::new(
+        ContractWrapper::new(
+            valence_processor::contract::execute,
+            valence_processor::contract::instantiate,
+            valence_processor::contract::query,
+        )
+        .with_reply(valence_processor::contract::reply),
+    )
+}
+
+/// Get the contract of a library by its `LibraryConfig` variant, Ex: `ValenceForwarderLibrary`
+pub(crate) fn library(variant: &str) -> Option<Box<dyn Contract<Empty>>> {
+    match variant {
+        "ValenceForwarderLibrary" => Some(valence_contract!(valence_forwarder_library)),
+        "ValenceSplitterLibrary" => Some(valence_contract!(valence_splitter_library)),
+        "ValenceReverseSplitterLibrary" => {
+            Some(valence_contract!(valence_reverse_splitter_library))
+        }
+        "ValenceAstroportLper" => Some(valence_contract!(valence_astroport_lper)),
+        "ValenceAstroportWithdrawer" => Some(valence_contract!(valence_astroport_withdrawer)),
+        "ValenceDropLiquidStaker" => Some(valence_contract!(valence_drop_liquid_staker)),
+        "ValenceDropLiquidUnstaker" => Some(valence_contract!(valence_drop_liquid_unstaker)),
+        _ => None,
+    }
+}
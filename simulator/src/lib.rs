@@ -0,0 +1,12 @@
+//! Offline simulation of programs with cw-multi-test, so authorizations can be exercised before
+//! the program touches any chain
+
+mod contracts;
+mod mocks;
+mod scenario;
+mod simulation;
+mod stargate;
+
+pub use mocks::{AstroportPoolMock, DropCoreMock};
+pub use scenario::{interpolate, Scenario, Step, StepResult};
+pub use simulation::{default_messages, SimApp, Simulation, SimulationBuilder};
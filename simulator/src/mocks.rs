@@ -0,0 +1,322 @@
+//! Minimal mocks of the external contracts programs interact with,
+//! they only implement the messages and queries the valence libraries use
+
+use cosmwasm_std::{
+    from_json, to_json_binary, to_json_vec, BankMsg, Binary, Coin, Decimal, Deps, DepsMut, Empty,
+    Env, MessageInfo, Response, StdError, StdResult, Uint128, Uint256,
+};
+use cw_multi_test::{Contract, ContractWrapper};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+const CONFIG_KEY: &[u8] = b"config";
+const TOTAL_SHARE_KEY: &[u8] = b"total_share";
+
+/// Amount of LP/liquid staked tokens mocks are funded with, so they can hand them out
+pub(crate) const MOCK_RESERVE: u128 = 1_000_000_000_000_000_000_000_000_000_000;
+
+/// Astroport pair with a native LP token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AstroportPoolMock {
+    pub addr: String,
+    pub denoms: [String; 2],
+    pub lp_denom: String,
+    /// Defaults to xyk, Ex: `{"custom": "concentrated"}`
+    #[serde(default = "default_pair_type")]
+    pub pair_type: Value,
+    /// Initial liquidity of the pool
+    #[serde(default)]
+    pub reserves: [Uint128; 2],
+    /// LP tokens issued for the initial liquidity
+    #[serde(default)]
+    pub total_share: Uint128,
+}
+
+fn default_pair_type() -> Value {
+    json!({"xyk": {}})
+}
+
+/// Drop core contract, bonds the base denom for the liquid staked denom at a fixed exchange rate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DropCoreMock {
+    pub addr: String,
+    pub base_denom: String,
+    pub d_denom: String,
+    #[serde(default = "Decimal::one")]
+    pub exchange_rate: Decimal,
+}
+
+pub(crate) fn astroport_pool() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        astroport_pool_execute,
+        instantiate::<AstroportPoolMock>,
+        astroport_pool_query,
+    ))
+}
+
+pub(crate) fn drop_core() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        drop_core_execute,
+        instantiate::<DropCoreMock>,
+        drop_core_query,
+    ))
+}
+
+fn instantiate<T: Serialize>(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: T,
+) -> StdResult<Response> {
+    deps.storage.set(CONFIG_KEY, &to_json_vec(&msg)?);
+    Ok(Response::new())
+}
+
+fn load<T: serde::de::DeserializeOwned>(deps: Deps, key: &[u8]) -> StdResult<T> {
+    from_json(
+        deps.storage
+            .get(key)
+            .ok_or_else(|| StdError::not_found(String::from_utf8_lossy(key)))?,
+    )
+}
+
+/// Name of the variant of an externally tagged message
+fn msg_name(msg: &Value) -> &str {
+    match msg {
+        Value::Object(map) => map.keys().next().map(|k| k.as_str()).unwrap_or_default(),
+        Value::String(s) => s.as_str(),
+        _ => "",
+    }
+}
+
+fn unsupported(msg: &Value) -> StdError {
+    StdError::generic_err(format!("Unsupported message by mock: {}", msg))
+}
+
+fn sent(info: &MessageInfo, denom: &str) -> Uint128 {
+    info.funds
+        .iter()
+        .filter(|c| c.denom == denom)
+        .map(|c| c.amount)
+        .sum()
+}
+
+fn send(to: impl Into<String>, amount: Uint128, denom: &str) -> BankMsg {
+    BankMsg::Send {
+        to_address: to.into(),
+        amount: vec![Coin::new(amount, denom)],
+    }
+}
+
+fn native_asset(denom: &str, amount: Uint128) -> Value {
+    json!({"info": {"native_token": {"denom": denom}}, "amount": amount})
+}
+
+fn astroport_pool_execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: Value,
+) -> StdResult<Response> {
+    let config: AstroportPoolMock = load(deps.as_ref(), CONFIG_KEY)?;
+    let total_share: Uint128 = load(deps.as_ref(), TOTAL_SHARE_KEY).unwrap_or(config.total_share);
+    let balance = |denom: &str| -> StdResult<Uint128> {
+        Ok(deps
+            .querier
+            .query_balance(&env.contract.address, denom)?
+            .amount)
+    };
+
+    match msg_name(&msg) {
+        "provide_liquidity" => {
+            let deposits = [
+                sent(&info, &config.denoms[0]),
+                sent(&info, &config.denoms[1]),
+            ];
+            // Funds were already sent to the pool, so the reserves before the deposit are the balance minus them
+            let reserves = [
+                balance(&config.denoms[0])? - deposits[0],
+                balance(&config.denoms[1])? - deposits[1],
+            ];
+
+            let share = if total_share.is_zero() {
+                Uint128::try_from(
+                    (Uint256::from(deposits[0]) * Uint256::from(deposits[1])).isqrt(),
+                )?
+            } else {
+                deposits[0]
+                    .multiply_ratio(total_share, reserves[0])
+                    .min(deposits[1].multiply_ratio(total_share, reserves[1]))
+            };
+
+            deps.storage
+                .set(TOTAL_SHARE_KEY, &to_json_vec(&(total_share + share))?);
+
+            let receiver = msg["provide_liquidity"]["receiver"]
+                .as_str()
+                .map(|r| r.to_string())
+                .unwrap_or_else(|| info.sender.to_string());
+
+            Ok(Response::new()
+                .add_message(send(receiver, share, &config.lp_denom))
+                .add_attribute("share", share))
+        }
+        "withdraw_liquidity" => {
+            let share = sent(&info, &config.lp_denom);
+            if share.is_zero() || total_share.is_zero() {
+                return Err(StdError::generic_err("No LP tokens sent"));
+            }
+
+            deps.storage
+                .set(TOTAL_SHARE_KEY, &to_json_vec(&(total_share - share))?);
+
+            let mut response = Response::new();
+            for denom in &config.denoms {
+                let amount = balance(denom)?.multiply_ratio(share, total_share);
+                response = response.add_message(send(&info.sender, amount, denom));
+            }
+
+            Ok(response)
+        }
+        "swap" => {
+            let offer = info
+                .funds
+                .first()
+                .ok_or_else(|| StdError::generic_err("No offer asset sent"))?;
+            let (offer_index, ask_index) = if offer.denom == config.denoms[0] {
+                (0, 1)
+            } else {
+                (1, 0)
+            };
+
+            let offer_pool = balance(&config.denoms[offer_index])? - offer.amount;
+            let ask_pool = balance(&config.denoms[ask_index])?;
+            let return_amount = ask_pool.multiply_ratio(offer.amount, offer_pool + offer.amount);
+
+            let receiver = msg["swap"]["to"]
+                .as_str()
+                .map(|r| r.to_string())
+                .unwrap_or_else(|| info.sender.to_string());
+
+            Ok(Response::new().add_message(send(
+                receiver,
+                return_amount,
+                &config.denoms[ask_index],
+            )))
+        }
+        _ => Err(unsupported(&msg)),
+    }
+}
+
+fn astroport_pool_query(deps: Deps, env: Env, msg: Value) -> StdResult<Binary> {
+    let config: AstroportPoolMock = load(deps, CONFIG_KEY)?;
+    let total_share: Uint128 = load(deps, TOTAL_SHARE_KEY).unwrap_or(config.total_share);
+    let balance = |denom: &str| -> StdResult<Uint128> {
+        Ok(deps
+            .querier
+            .query_balance(&env.contract.address, denom)?
+            .amount)
+    };
+
+    let response = match msg_name(&msg) {
+        "pair" => json!({
+            "asset_infos": config.denoms.iter().map(|d| json!({"native_token": {"denom": d}})).collect::<Vec<_>>(),
+            "contract_addr": env.contract.address,
+            "liquidity_token": config.lp_denom,
+            "pair_type": config.pair_type,
+        }),
+        "pool" => json!({
+            "assets": config
+                .denoms
+                .iter()
+                .map(|d| Ok(native_asset(d, balance(d)?)))
+                .collect::<StdResult<Vec<_>>>()?,
+            "total_share": total_share,
+        }),
+        "share" => {
+            let amount: Uint128 = serde_json::from_value(msg["share"]["amount"].clone())
+                .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+            json!(config
+                .denoms
+                .iter()
+                .map(|d| Ok(native_asset(
+                    d,
+                    balance(d)?.multiply_ratio(amount, total_share.max(Uint128::one()))
+                )))
+                .collect::<StdResult<Vec<_>>>()?)
+        }
+        "simulation" => {
+            let offer = &msg["simulation"]["offer_asset"];
+            let offer_denom = offer["info"]["native_token"]["denom"]
+                .as_str()
+                .unwrap_or_default();
+            let offer_amount: Uint128 = serde_json::from_value(offer["amount"].clone())
+                .map_err(|e| StdError::generic_err(e.to_string()))?;
+            let ask_denom = config
+                .denoms
+                .iter()
+                .find(|d| d.as_str() != offer_denom)
+                .cloned()
+                .unwrap_or_default();
+
+            let offer_pool = balance(offer_denom)?;
+            let ask_pool = balance(&ask_denom)?;
+
+            json!({
+                "return_amount": ask_pool.multiply_ratio(offer_amount, offer_pool + offer_amount),
+                "spread_amount": Uint128::zero(),
+                "commission_amount": Uint128::zero(),
+            })
+        }
+        _ => return Err(unsupported(&msg)),
+    };
+
+    to_json_binary(&response)
+}
+
+fn drop_core_execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: Value,
+) -> StdResult<Response> {
+    let config: DropCoreMock = load(deps.as_ref(), CONFIG_KEY)?;
+
+    match msg_name(&msg) {
+        "bond" => {
+            let amount = sent(&info, &config.base_denom);
+            let d_amount =
+                amount.multiply_ratio(Decimal::one().atomics(), config.exchange_rate.atomics());
+
+            let receiver = msg["bond"]["receiver"]
+                .as_str()
+                .map(|r| r.to_string())
+                .unwrap_or_else(|| info.sender.to_string());
+
+            Ok(Response::new()
+                .add_message(send(receiver, d_amount, &config.d_denom))
+                .add_attribute("issue_amount", d_amount))
+        }
+        // Unbonding takes the whole unbonding period on chain, so the mock only takes the liquid staked tokens
+        "unbond" => {
+            Ok(Response::new().add_attribute("unbond_amount", sent(&info, &config.d_denom)))
+        }
+        _ => Err(unsupported(&msg)),
+    }
+}
+
+fn drop_core_query(deps: Deps, _env: Env, msg: Value) -> StdResult<Binary> {
+    let config: DropCoreMock = load(deps, CONFIG_KEY)?;
+
+    match msg_name(&msg) {
+        "exchange_rate" => to_json_binary(&config.exchange_rate),
+        "config" => to_json_binary(&json!({
+            "base_denom": config.base_denom,
+            "token_denom": config.d_denom,
+        })),
+        _ => Err(unsupported(&msg)),
+    }
+}
@@ -0,0 +1,378 @@
+use std::{collections::BTreeMap, error::Error, fmt::Display, path::Path};
+
+use cosmwasm_std::Uint128;
+use serde::Deserialize;
+use serde_json::Value;
+use valence_program_manager::program_config::ProgramConfig;
+
+use crate::{
+    mocks::{AstroportPoolMock, DropCoreMock},
+    Simulation, SimulationBuilder,
+};
+
+/// Operator playbook that is run against a simulation of the program,
+/// strings can refer to program params with `${param}`, Ex: `${operator_list.0}`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    #[serde(default)]
+    pub description: String,
+    /// Environment params the program is built with
+    #[serde(default = "default_env")]
+    pub env: String,
+    /// Alias to address, Ex: `operator1 = "${operator_list.0}"`
+    #[serde(default)]
+    pub actors: BTreeMap<String, String>,
+    #[serde(default)]
+    pub mocks: Mocks,
+    /// Initial balances
+    #[serde(default)]
+    pub balances: Vec<Balance>,
+    #[serde(default)]
+    pub steps: Vec<Step>,
+}
+
+fn default_env() -> String {
+    "mainnet".to_string()
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Mocks {
+    #[serde(default)]
+    pub astroport_pools: Vec<AstroportPoolMock>,
+    #[serde(default)]
+    pub drop_cores: Vec<DropCoreMock>,
+}
+
+/// Balance of an account, library, actor or address
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Balance {
+    pub account: String,
+    pub denom: String,
+    pub amount: Uint128,
+}
+
+/// A single step of a scenario, funds are minted first, then the action runs and the expectations are checked
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Step {
+    /// Actor that executes the authorization
+    pub actor: Option<String>,
+    /// Label of the authorization to execute
+    pub execute: Option<String>,
+    /// Messages to execute the authorization with, one per function,
+    /// built from the function restrictions if not set
+    pub messages: Option<Vec<Value>>,
+    /// Seconds to move the block time forward
+    pub advance_time: Option<u64>,
+    /// Tick the processor
+    #[serde(default)]
+    pub tick: bool,
+    #[serde(default)]
+    pub fund: Vec<Balance>,
+    /// The step action must fail with an error that contains this, any error if empty
+    pub expect_error: Option<String>,
+    #[serde(default)]
+    pub expect_balances: Vec<Balance>,
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut actions = vec![];
+
+        if !self.fund.is_empty() {
+            actions.push(format!("fund {} accounts", self.fund.len()));
+        }
+        if let Some(label) = &self.execute {
+            actions.push(format!(
+                "{} executes {}",
+                self.actor.as_deref().unwrap_or("owner"),
+                label
+            ));
+        }
+        if let Some(seconds) = self.advance_time {
+            actions.push(format!("advance time {}s", seconds));
+        }
+        if self.tick {
+            actions.push("tick".to_string());
+        }
+
+        write!(f, "{}", actions.join(", "))
+    }
+}
+
+/// Outcome of a step, the scenario stops at the first step that fails
+#[derive(Debug)]
+pub struct StepResult {
+    pub step: String,
+    pub failures: Vec<String>,
+}
+
+impl Scenario {
+    /// Read a scenario from a toml or json file, resolving `${param}` with the given params
+    pub fn from_file(path: &Path, params: &Value) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_value(interpolate(
+            read_file(path)?,
+            params,
+        )?)?)
+    }
+
+    /// Only the env is needed to build the program, so read it before resolving params
+    pub fn get_env(path: &Path) -> Result<String, Box<dyn Error>> {
+        Ok(read_file(path)?["env"]
+            .as_str()
+            .map(|env| env.to_string())
+            .unwrap_or_else(default_env))
+    }
+
+    /// Simulate the program with the mocks and initial balances of the scenario, without running its steps
+    pub fn simulation(&self, program_config: &ProgramConfig) -> Result<Simulation, Box<dyn Error>> {
+        let mut builder = SimulationBuilder::new(program_config)?;
+        for pool in &self.mocks.astroport_pools {
+            builder = builder.with_astroport_pool(pool.clone());
+        }
+        for drop_core in &self.mocks.drop_cores {
+            builder = builder.with_drop_core(drop_core.clone());
+        }
+
+        let mut sim = builder.build()?;
+        for balance in &self.balances {
+            sim.fund(
+                self.actor(&balance.account),
+                balance.amount.u128(),
+                &balance.denom,
+            )?;
+        }
+
+        Ok(sim)
+    }
+
+    /// Run the scenario against a simulation of the program, errors only if the simulation can't be set up
+    pub fn run(&self, program_config: &ProgramConfig) -> Result<Vec<StepResult>, Box<dyn Error>> {
+        let mut sim = self.simulation(program_config)?;
+
+        let mut results = vec![];
+        for step in &self.steps {
+            let result = self.run_step(&mut sim, step);
+            let failed = !result.failures.is_empty();
+
+            results.push(result);
+            if failed {
+                break;
+            }
+        }
+
+        Ok(results)
+    }
+
+    fn actor<'a>(&'a self, name: &'a str) -> &'a str {
+        self.actors.get(name).map(|a| a.as_str()).unwrap_or(name)
+    }
+
+    fn run_step(&self, sim: &mut Simulation, step: &Step) -> StepResult {
+        let mut failures = vec![];
+
+        let outcome = (|| -> Result<(), Box<dyn Error>> {
+            for balance in &step.fund {
+                sim.fund(
+                    self.actor(&balance.account),
+                    balance.amount.u128(),
+                    &balance.denom,
+                )?;
+            }
+
+            if let Some(label) = &step.execute {
+                let actor = self.actor(step.actor.as_deref().ok_or("Step has no actor")?);
+
+                match &step.messages {
+                    Some(messages) => sim.execute_with_messages(actor, label, messages.clone())?,
+                    None => sim.execute(actor, label)?,
+                };
+            }
+
+            if let Some(seconds) = step.advance_time {
+                sim.advance_time(seconds);
+            }
+
+            if step.tick {
+                sim.tick()?;
+            }
+
+            Ok(())
+        })();
+
+        failures.extend(check_outcome(outcome, step.expect_error.as_deref()));
+
+        for expected in &step.expect_balances {
+            match sim.balance(self.actor(&expected.account), &expected.denom) {
+                Ok(amount) if amount == expected.amount.u128() => (),
+                Ok(amount) => failures.push(format!(
+                    "{} has {}{}, expected {}{}",
+                    expected.account, amount, expected.denom, expected.amount, expected.denom
+                )),
+                Err(e) => failures.push(format!(
+                    "failed to query balance of {}: {}",
+                    expected.account, e
+                )),
+            }
+        }
+
+        StepResult {
+            step: step.to_string(),
+            failures,
+        }
+    }
+}
+
+/// Failure of a step action given the error the step expects, if any
+fn check_outcome(
+    outcome: Result<(), Box<dyn Error>>,
+    expect_error: Option<&str>,
+) -> Option<String> {
+    match (outcome, expect_error) {
+        (Ok(()), Some(expected)) => Some(format!(
+            "expected error {:?} but the step succeeded",
+            expected
+        )),
+        (Err(e), Some(expected)) if !format!("{:?}", e).contains(expected) => {
+            Some(format!("expected error {:?} but got: {}", expected, e))
+        }
+        (Err(e), None) => Some(format!("unexpected error: {}", e)),
+        _ => None,
+    }
+}
+
+fn read_file(path: &Path) -> Result<Value, Box<dyn Error>> {
+    let content = std::fs::read_to_string(path)?;
+
+    Ok(match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => serde_json::from_str(&content)?,
+        _ => toml::from_str(&content)?,
+    })
+}
+
+/// Replace `${param}` in every string with the param value, nested params are separated by dots
+pub fn interpolate(value: Value, params: &Value) -> Result<Value, Box<dyn Error>> {
+    Ok(match value {
+        Value::String(s) => {
+            let mut result = String::new();
+            let mut rest = s.as_str();
+
+            while let Some(start) = rest.find("${") {
+                let end = rest[start..]
+                    .find('}')
+                    .ok_or_else(|| format!("Unclosed param in {:?}", s))?;
+                let key = &rest[start + 2..start + end];
+
+                let param = key
+                    .split('.')
+                    .try_fold(params, |value, segment| match segment.parse::<usize>() {
+                        Ok(index) => value.get(index),
+                        Err(_) => value.get(segment),
+                    })
+                    .ok_or_else(|| format!("Param {} not found", key))?;
+
+                result.push_str(&rest[..start]);
+                match param {
+                    Value::String(param) => result.push_str(param),
+                    param => result.push_str(&param.to_string()),
+                }
+                rest = &rest[start + end + 1..];
+            }
+            result.push_str(rest);
+
+            Value::String(result)
+        }
+        Value::Array(values) => Value::Array(
+            values
+                .into_iter()
+                .map(|v| interpolate(v, params))
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| Ok((k, interpolate(v, params)?)))
+                .collect::<Result<_, Box<dyn Error>>>()?,
+        ),
+        v => v,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use serde_json::json;
+
+    use super::{check_outcome, interpolate, Scenario, Step};
+
+    fn failed(error: &str) -> Result<(), Box<dyn Error>> {
+        Err(error.into())
+    }
+
+    #[test]
+    fn failing_step_is_reported() {
+        assert_eq!(
+            check_outcome(failed("Unauthorized: not an operator"), None),
+            Some("unexpected error: Unauthorized: not an operator".to_string())
+        );
+        assert_eq!(check_outcome(Ok(()), None), None);
+    }
+
+    #[test]
+    fn expected_error_step_passes_when_it_errors() {
+        assert_eq!(
+            check_outcome(
+                failed("Unauthorized: not an operator"),
+                Some("Unauthorized")
+            ),
+            None
+        );
+        // Any error if empty
+        assert_eq!(check_outcome(failed("Insufficient funds"), Some("")), None);
+    }
+
+    #[test]
+    fn expected_error_step_fails_when_it_succeeds_or_errors_otherwise() {
+        assert_eq!(
+            check_outcome(Ok(()), Some("Unauthorized")),
+            Some("expected error \"Unauthorized\" but the step succeeded".to_string())
+        );
+        assert_eq!(
+            check_outcome(failed("Insufficient funds"), Some("Unauthorized")),
+            Some("expected error \"Unauthorized\" but got: Insufficient funds".to_string())
+        );
+    }
+
+    #[test]
+    fn malformed_steps_are_rejected() {
+        let step = |step| serde_json::from_value::<Step>(step).map(|step| step.to_string());
+
+        assert_eq!(
+            step(json!({ "actor": "operator1", "execute": "forward", "tick": true })).unwrap(),
+            "operator1 executes forward, tick"
+        );
+        assert!(step(json!({ "execute": "forward", "expect_errors": "Unauthorized" })).is_err());
+        assert!(step(json!({ "advance_time": "1 day" })).is_err());
+        assert!(serde_json::from_value::<Scenario>(json!({ "step": [] })).is_err());
+    }
+
+    #[test]
+    fn missing_param_is_an_error() {
+        let params = json!({ "operator_list": ["neutron1operator"] });
+
+        assert_eq!(
+            interpolate(json!({ "operator1": "${operator_list.0}" }), &params).unwrap(),
+            json!({ "operator1": "neutron1operator" })
+        );
+        assert_eq!(
+            interpolate(json!("${operator_list.1}"), &params)
+                .unwrap_err()
+                .to_string(),
+            "Param operator_list.1 not found"
+        );
+        assert!(interpolate(json!("${operator_list.0"), &params).is_err());
+    }
+}
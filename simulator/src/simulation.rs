@@ -0,0 +1,530 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    sync::{Arc, Mutex},
+};
+
+use cosmwasm_std::{coins, to_json_binary, Addr, Api, CanonicalAddr, Empty, Storage};
+use cw_multi_test::{
+    error::AnyResult, AddressGenerator, App, AppBuilder, AppResponse, BankKeeper, BankSudo,
+    DistributionKeeper, Executor, FailingModule, GovFailingModule, IbcFailingModule, MockApiBech32,
+    StakeKeeper, SudoMsg, WasmKeeper,
+};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use valence_program_manager::program_config::ProgramConfig;
+
+use crate::{
+    contracts,
+    mocks::{self, AstroportPoolMock, DropCoreMock, MOCK_RESERVE},
+    stargate::TokenFactory,
+};
+
+pub type SimApp = App<
+    BankKeeper,
+    MockApiBech32,
+    cosmwasm_std::testing::MockStorage,
+    FailingModule<Empty, Empty, Empty>,
+    WasmKeeper<Empty, Empty>,
+    StakeKeeper,
+    DistributionKeeper,
+    IbcFailingModule,
+    GovFailingModule,
+    TokenFactory,
+>;
+
+/// Only the main domain can be simulated, there is no IBC between simulated chains
+const SIMULATED_CHAIN: &str = "neutron";
+
+/// Lets us instantiate contracts at the addresses the program config expects them,
+/// falls back to an address derived from the instance id
+#[derive(Debug, Default, Clone)]
+struct NextAddress(Arc<Mutex<Option<Addr>>>);
+
+impl AddressGenerator for NextAddress {
+    fn contract_address(
+        &self,
+        api: &dyn Api,
+        _storage: &mut dyn Storage,
+        _code_id: u64,
+        instance_id: u64,
+    ) -> AnyResult<Addr> {
+        match self.0.lock().unwrap().take() {
+            Some(addr) => Ok(addr),
+            None => {
+                let hash = Sha256::digest(format!("contract{}", instance_id));
+                Ok(api.addr_humanize(&CanonicalAddr::from(hash.as_slice()))?)
+            }
+        }
+    }
+}
+
+/// Builds a simulation of a program, with the external contracts it needs mocked
+pub struct SimulationBuilder {
+    program_config: Value,
+    astroport_pools: Vec<AstroportPoolMock>,
+    drop_cores: Vec<DropCoreMock>,
+}
+
+impl SimulationBuilder {
+    pub fn new(program_config: &ProgramConfig) -> Result<Self, Box<dyn Error>> {
+        Ok(SimulationBuilder {
+            program_config: serde_json::to_value(program_config)?,
+            astroport_pools: vec![],
+            drop_cores: vec![],
+        })
+    }
+
+    /// Mock an astroport pair at the address of the pool
+    pub fn with_astroport_pool(mut self, pool: AstroportPoolMock) -> Self {
+        self.astroport_pools.push(pool);
+        self
+    }
+
+    /// Mock a drop core contract at its address, liquid staking at a fixed exchange rate
+    pub fn with_drop_core(mut self, drop_core: DropCoreMock) -> Self {
+        self.drop_cores.push(drop_core);
+        self
+    }
+
+    /// Instantiate the authorization, processor, accounts and libraries of the program
+    /// the same way the program manager would, and create all authorizations
+    pub fn build(self) -> Result<Simulation, Box<dyn Error>> {
+        let next_address = NextAddress::default();
+        let app = AppBuilder::new()
+            .with_api(MockApiBech32::new(SIMULATED_CHAIN))
+            .with_wasm(WasmKeeper::new().with_address_generator(next_address.clone()))
+            .with_stargate(TokenFactory)
+            .build(|_, _, _| {});
+
+        let owner = Addr::unchecked(
+            self.program_config["owner"]
+                .as_str()
+                .ok_or("Program config has no owner")?,
+        );
+
+        let mut sim = Simulation {
+            authorization: app.api().addr_make("authorization"),
+            processor: app.api().addr_make("processor"),
+            app,
+            next_address,
+            owner,
+            program_config: self.program_config.clone(),
+            addresses: BTreeMap::new(),
+        };
+
+        for pool in &self.astroport_pools {
+            let code_id = sim.app.store_code(mocks::astroport_pool());
+            let addr = Addr::unchecked(&pool.addr);
+            sim.instantiate_at(addr, code_id, &json!(pool), "astroport_pool")?;
+
+            sim.mint(&pool.addr, MOCK_RESERVE, &pool.lp_denom)?;
+            for (denom, amount) in pool.denoms.iter().zip(pool.reserves) {
+                if !amount.is_zero() {
+                    sim.mint(&pool.addr, amount.u128(), denom)?;
+                }
+            }
+        }
+        for drop_core in &self.drop_cores {
+            let code_id = sim.app.store_code(mocks::drop_core());
+            let addr = Addr::unchecked(&drop_core.addr);
+            sim.instantiate_at(addr, code_id, &json!(drop_core), "drop_core")?;
+
+            sim.mint(&drop_core.addr, MOCK_RESERVE, &drop_core.d_denom)?;
+        }
+
+        sim.instantiate_program()?;
+
+        Ok(sim)
+    }
+}
+
+/// A program instantiated in cw-multi-test, operators can execute authorizations
+/// and tick the processor like they would on chain
+pub struct Simulation {
+    app: SimApp,
+    next_address: NextAddress,
+    owner: Addr,
+    authorization: Addr,
+    processor: Addr,
+    /// Program config as serialized by the builder
+    program_config: Value,
+    /// Account and library name to address
+    addresses: BTreeMap<String, Addr>,
+}
+
+impl Simulation {
+    /// Simulate a program that doesn't need any external contract mocked
+    pub fn new(program_config: &ProgramConfig) -> Result<Self, Box<dyn Error>> {
+        SimulationBuilder::new(program_config)?.build()
+    }
+
+    pub fn builder(program_config: &ProgramConfig) -> Result<SimulationBuilder, Box<dyn Error>> {
+        SimulationBuilder::new(program_config)
+    }
+
+    /// Underlying cw-multi-test app, for anything not covered by the simulation
+    pub fn app(&mut self) -> &mut SimApp {
+        &mut self.app
+    }
+
+    /// Address of an account or library by name, `authorization`, `processor`, or the address itself
+    pub fn addr(&self, name: &str) -> Addr {
+        match name {
+            "authorization" => self.authorization.clone(),
+            "processor" => self.processor.clone(),
+            name => self
+                .addresses
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| Addr::unchecked(name)),
+        }
+    }
+
+    /// Mint tokens to an account or address
+    pub fn fund(&mut self, name: &str, amount: u128, denom: &str) -> Result<(), Box<dyn Error>> {
+        let addr = self.addr(name);
+        self.mint(addr.as_str(), amount, denom)
+    }
+
+    pub fn balance(&self, name: &str, denom: &str) -> Result<u128, Box<dyn Error>> {
+        Ok(self
+            .app
+            .wrap()
+            .query_balance(self.addr(name), denom)?
+            .amount
+            .u128())
+    }
+
+    pub fn advance_time(&mut self, seconds: u64) {
+        self.app.update_block(|block| {
+            block.time = block.time.plus_seconds(seconds);
+            block.height += seconds / 6 + 1;
+        });
+    }
+
+    /// Execute an authorization as the given actor, with the messages built from the
+    /// restrictions of every function, Ex: `process_function.forward` sends `{"process_function":{"forward":{}}}`
+    pub fn execute(&mut self, actor: &str, label: &str) -> Result<AppResponse, Box<dyn Error>> {
+        let messages = default_messages(self.get_authorization(label)?);
+
+        self.execute_with_messages(actor, label, messages)
+    }
+
+    /// Execute an authorization as the given actor with custom messages, one per function
+    pub fn execute_with_messages(
+        &mut self,
+        actor: &str,
+        label: &str,
+        messages: Vec<Value>,
+    ) -> Result<AppResponse, Box<dyn Error>> {
+        let authorization = self.get_authorization(label)?;
+
+        // With call limit, the authorization token is spent on every execution
+        let funds = match authorization.pointer("/mode/permissioned/with_call_limit") {
+            Some(_) => coins(1, format!("factory/{}/{}", self.authorization, label)),
+            None => vec![],
+        };
+
+        let messages = messages
+            .iter()
+            .map(|msg| Ok(json!({"cosmwasm_execute_msg": {"msg": to_json_binary(msg)?}})))
+            .collect::<Result<Vec<_>, cosmwasm_std::StdError>>()?;
+
+        let msg = json!({
+            "permissionless_action": {
+                "send_msgs": {
+                    "label": label,
+                    "messages": messages,
+                    "ttl": null,
+                }
+            }
+        });
+
+        Ok(self
+            .app
+            .execute_contract(self.addr(actor), self.authorization.clone(), &msg, &funds)?)
+    }
+
+    /// Tick the processor once, executing the next queued subroutine
+    pub fn tick(&mut self) -> Result<AppResponse, Box<dyn Error>> {
+        Ok(self.app.execute_contract(
+            self.owner.clone(),
+            self.processor.clone(),
+            &json!({"permissionless_action": {"tick": {}}}),
+            &[],
+        )?)
+    }
+
+    fn mint(&mut self, addr: &str, amount: u128, denom: &str) -> Result<(), Box<dyn Error>> {
+        self.app.sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: addr.to_string(),
+            amount: coins(amount, denom),
+        }))?;
+
+        Ok(())
+    }
+
+    fn get_authorization(&self, label: &str) -> Result<&Value, Box<dyn Error>> {
+        self.program_config["authorizations"]
+            .as_array()
+            .and_then(|authorizations| {
+                authorizations
+                    .iter()
+                    .find(|a| a["label"].as_str() == Some(label))
+            })
+            .ok_or_else(|| format!("Authorization {} not found", label).into())
+    }
+
+    fn instantiate_at(
+        &mut self,
+        addr: Addr,
+        code_id: u64,
+        msg: &Value,
+        label: &str,
+    ) -> Result<Addr, Box<dyn Error>> {
+        *self.next_address.0.lock().unwrap() = Some(addr);
+
+        Ok(self.app.instantiate_contract(
+            code_id,
+            self.owner.clone(),
+            msg,
+            &[],
+            label,
+            Some(self.owner.to_string()),
+        )?)
+    }
+
+    fn instantiate_program(&mut self) -> Result<(), Box<dyn Error>> {
+        let config = self.program_config.clone();
+        let entries = |key: &str| {
+            config[key]
+                .as_object()
+                .cloned()
+                .unwrap_or_default()
+                .into_iter()
+                .collect::<Vec<_>>()
+        };
+
+        // Assign addresses first, so every reference can be resolved before instantiating
+        let mut refs = BTreeMap::new();
+        for (id, account) in entries("accounts") {
+            check_domain(&account)?;
+            let name = account["name"].as_str().unwrap_or_default().to_string();
+
+            // Accounts that already exist are used as is
+            let addr = match account["ty"].pointer("/Addr/addr").and_then(|a| a.as_str()) {
+                Some(addr) => Addr::unchecked(addr),
+                None => self.app.api().addr_make(&format!("account:{}", name)),
+            };
+
+            refs.insert(format!("account:{}", id), addr.clone());
+            self.addresses.insert(name, addr);
+        }
+        for (id, library) in entries("libraries") {
+            check_domain(&library)?;
+            let name = library["name"].as_str().unwrap_or_default().to_string();
+            let addr = self.app.api().addr_make(&format!("library:{}", name));
+
+            refs.insert(format!("library:{}", id), addr.clone());
+            self.addresses.insert(name, addr);
+        }
+
+        let processor_code_id = self.app.store_code(contracts::processor());
+        self.instantiate_at(
+            self.processor.clone(),
+            processor_code_id,
+            &json!({"authorization_contract": self.authorization, "polytone_contracts": null}),
+            "processor",
+        )?;
+
+        let authorization_code_id = self.app.store_code(contracts::authorization());
+        self.instantiate_at(
+            self.authorization.clone(),
+            authorization_code_id,
+            &json!({"owner": self.owner, "sub_owners": [], "processor": self.processor}),
+            "authorization",
+        )?;
+
+        let base_account_code_id = self.app.store_code(contracts::base_account());
+        let storage_account_code_id = self.app.store_code(contracts::storage_account());
+        for (id, account) in entries("accounts") {
+            let code_id = match account["ty"].as_object().and_then(|ty| ty.keys().next()) {
+                Some(ty) if ty == "Base" => base_account_code_id,
+                Some(ty) if ty == "Storage" => storage_account_code_id,
+                _ => continue,
+            };
+
+            // Libraries that have the account as input are approved on it
+            let account_id = id.parse::<u64>().ok();
+            let approved_libraries = config["links"]
+                .as_object()
+                .into_iter()
+                .flat_map(|links| links.values())
+                .filter(|link| {
+                    link["input_accounts_id"]
+                        .as_array()
+                        .is_some_and(|ids| ids.iter().any(|i| i.as_u64() == account_id))
+                })
+                .filter_map(|link| refs.get(&format!("library:{}", link["library_id"])))
+                .cloned()
+                .collect::<Vec<_>>();
+
+            let name = account["name"].as_str().unwrap_or_default();
+            self.instantiate_at(
+                refs[&format!("account:{}", id)].clone(),
+                code_id,
+                &json!({"admin": self.owner, "approved_libraries": approved_libraries}),
+                name,
+            )?;
+        }
+
+        for (id, library) in entries("libraries") {
+            let name = library["name"].as_str().unwrap_or_default();
+            let (variant, library_config) = library["config"]
+                .as_object()
+                .and_then(|c| c.iter().next())
+                .ok_or_else(|| format!("Library {} has no config", name))?;
+            let contract = contracts::library(variant)
+                .ok_or_else(|| format!("Library {} can't be simulated", variant))?;
+
+            let code_id = self.app.store_code(contract);
+            self.instantiate_at(
+                refs[&format!("library:{}", id)].clone(),
+                code_id,
+                &json!({
+                    "owner": self.owner,
+                    "processor": self.processor,
+                    "config": resolve_refs(library_config.clone(), &refs),
+                }),
+                name,
+            )?;
+        }
+
+        let authorizations = resolve_refs(config["authorizations"].clone(), &refs);
+        self.app.execute_contract(
+            self.owner.clone(),
+            self.authorization.clone(),
+            &json!({"permissioned_action": {"create_authorizations": {"authorizations": authorizations}}}),
+            &[],
+        )?;
+
+        Ok(())
+    }
+}
+
+fn check_domain(entry: &Value) -> Result<(), Box<dyn Error>> {
+    match entry["domain"]["CosmosCosmwasm"].as_str() {
+        Some(SIMULATED_CHAIN) => Ok(()),
+        _ => Err(format!(
+            "{} is not on {}, only {} can be simulated",
+            entry["name"], SIMULATED_CHAIN, SIMULATED_CHAIN
+        )
+        .into()),
+    }
+}
+
+/// Replace `|account_id|` and `|library_id|` references with the address of the contract
+fn resolve_refs(value: Value, refs: &BTreeMap<String, Addr>) -> Value {
+    match value {
+        Value::Object(map) if map.len() == 1 => {
+            let (key, inner) = map.iter().next().unwrap();
+
+            let lookup = match key.as_str() {
+                "|account_id|" => Some(format!("account:{}", inner)),
+                "|library_id|" => Some(format!("library:{}", inner)),
+                _ => None,
+            };
+
+            match lookup.and_then(|lookup| refs.get(&lookup)) {
+                Some(addr) => json!({"|library_account_addr|": addr}),
+                None => Value::Object(
+                    map.into_iter()
+                        .map(|(k, v)| (k, resolve_refs(v, refs)))
+                        .collect(),
+                ),
+            }
+        }
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| (k, resolve_refs(v, refs)))
+                .collect(),
+        ),
+        Value::Array(values) => {
+            Value::Array(values.into_iter().map(|v| resolve_refs(v, refs)).collect())
+        }
+        v => v,
+    }
+}
+
+/// Messages that satisfy the restrictions of every function of an authorization, in order
+pub fn default_messages(authorization: &Value) -> Vec<Value> {
+    get_functions(authorization)
+        .iter()
+        .map(|function| default_message(&function["message_details"]["message"]))
+        .collect()
+}
+
+/// Get the functions of an atomic or non atomic subroutine
+fn get_functions(authorization: &Value) -> Vec<Value> {
+    authorization["subroutine"]
+        .as_object()
+        .and_then(|subroutine| subroutine.values().next())
+        .and_then(|subroutine| subroutine["functions"].as_array())
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Build the smallest message that satisfies the params restrictions of a function
+fn default_message(message: &Value) -> Value {
+    let mut msg = json!({});
+    let name = message["name"].as_str().unwrap_or_default();
+    msg[name] = json!({});
+
+    for restriction in message["params_restrictions"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default()
+    {
+        if let Some(path) = restriction["must_be_included"].as_array() {
+            insert_path(&mut msg, path, json!({}), false);
+        }
+
+        if let Some([path, value]) = restriction["must_be_value"]
+            .as_array()
+            .map(|r| r.as_slice())
+        {
+            let value = value
+                .as_str()
+                .and_then(|v| cosmwasm_std::Binary::from_base64(v).ok())
+                .and_then(|v| serde_json::from_slice(v.as_slice()).ok())
+                .unwrap_or_default();
+
+            if let Some(path) = path.as_array() {
+                insert_path(&mut msg, path, value, true);
+            }
+        }
+    }
+
+    msg
+}
+
+fn insert_path(msg: &mut Value, path: &[Value], value: Value, overwrite: bool) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+
+    let mut current = msg;
+    for key in parents {
+        let key = key.as_str().unwrap_or_default();
+        if !current[key].is_object() {
+            current[key] = json!({});
+        }
+        current = &mut current[key];
+    }
+
+    let last = last.as_str().unwrap_or_default();
+    if overwrite || current.get(last).is_none() {
+        current[last] = value;
+    }
+}
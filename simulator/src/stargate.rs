@@ -0,0 +1,137 @@
+use cosmwasm_std::{
+    coins, Addr, AnyMsg, Api, BankMsg, Binary, BlockInfo, CosmosMsg, CustomMsg, CustomQuery,
+    Storage,
+};
+use cw_multi_test::{
+    error::{bail, AnyResult},
+    AppResponse, BankSudo, CosmosRouter, Stargate, SudoMsg,
+};
+use serde::de::DeserializeOwned;
+
+/// Token factory messages, the authorization contract uses them to mint the authorization tokens
+#[derive(Clone, PartialEq, prost::Message)]
+struct ProtoCoin {
+    #[prost(string, tag = "1")]
+    denom: String,
+    #[prost(string, tag = "2")]
+    amount: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct MsgMint {
+    #[prost(string, tag = "1")]
+    sender: String,
+    #[prost(message, optional, tag = "2")]
+    amount: Option<ProtoCoin>,
+    #[prost(string, tag = "3")]
+    mint_to_address: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct MsgBurn {
+    #[prost(string, tag = "1")]
+    sender: String,
+    #[prost(message, optional, tag = "2")]
+    amount: Option<ProtoCoin>,
+    #[prost(string, tag = "3")]
+    burn_from_address: String,
+}
+
+/// Handles the token factory messages with the bank module, denoms don't need to be created
+#[derive(Debug, Default, Clone)]
+pub(crate) struct TokenFactory;
+
+impl TokenFactory {
+    fn execute<ExecC, QueryC>(
+        &self,
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        block: &BlockInfo,
+        type_url: &str,
+        value: &Binary,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: CustomMsg + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        // Neutron and osmosis use the same messages under different packages
+        match type_url.rsplit('.').next().unwrap_or_default() {
+            "MsgCreateDenom" => Ok(AppResponse::default()),
+            "MsgMint" => {
+                let msg: MsgMint = prost::Message::decode(value.as_slice())?;
+                let coin = msg.amount.unwrap_or_default();
+                let to_address = if msg.mint_to_address.is_empty() {
+                    msg.sender
+                } else {
+                    msg.mint_to_address
+                };
+
+                router.sudo(
+                    api,
+                    storage,
+                    block,
+                    SudoMsg::Bank(BankSudo::Mint {
+                        to_address,
+                        amount: coins(coin.amount.parse()?, coin.denom),
+                    }),
+                )
+            }
+            "MsgBurn" => {
+                let msg: MsgBurn = prost::Message::decode(value.as_slice())?;
+                let coin = msg.amount.unwrap_or_default();
+                let burn_from = if msg.burn_from_address.is_empty() {
+                    msg.sender
+                } else {
+                    msg.burn_from_address
+                };
+
+                router.execute(
+                    api,
+                    storage,
+                    block,
+                    Addr::unchecked(burn_from),
+                    CosmosMsg::Bank(BankMsg::Burn {
+                        amount: coins(coin.amount.parse()?, coin.denom),
+                    }),
+                )
+            }
+            _ => bail!("Unsupported stargate message in simulation: {}", type_url),
+        }
+    }
+}
+
+impl Stargate for TokenFactory {
+    fn execute_stargate<ExecC, QueryC>(
+        &self,
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        block: &BlockInfo,
+        _sender: Addr,
+        type_url: String,
+        value: Binary,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: CustomMsg + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        self.execute(api, storage, router, block, &type_url, &value)
+    }
+
+    fn execute_any<ExecC, QueryC>(
+        &self,
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        block: &BlockInfo,
+        _sender: Addr,
+        msg: AnyMsg,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: CustomMsg + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        self.execute(api, storage, router, block, &msg.type_url, &msg.value)
+    }
+}